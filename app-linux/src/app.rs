@@ -1,13 +1,17 @@
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
+use std::thread;
 
 use log::{error, info, warn};
 use something_bg_core::command::CommandRunner;
-use something_bg_core::config::{Config, ConfigMonitor};
+use something_bg_core::config::{Config, ConfigMonitor, config_file};
 use something_bg_core::platform::AppPaths;
 use something_bg_core::scheduler::TaskScheduler;
 use something_bg_core::tunnel::TunnelManager;
 
+use crate::notifier::LinuxNotifier;
 use crate::paths::LinuxPaths;
+use crate::tray::{ChannelTray, TrayUpdate};
 
 /// Shared application state for the Linux shell.
 /// Holds the tunnel manager and scheduler so menu handlers can drive them.
@@ -15,8 +19,14 @@ pub struct AppState {
     pub tunnel_manager: TunnelManager,
     pub command_runner: CommandRunner,
     pub scheduler: Arc<TaskScheduler>,
+    /// Tray changes requested by the core, applied by the event loop.
+    pub tray_updates: Receiver<TrayUpdate>,
     pub paths: Arc<LinuxPaths>,
+    pub notifier: LinuxNotifier,
     config_monitor: ConfigMonitor,
+    /// `auto_start` tunnels of the config applied last, so a launch delayed by
+    /// `startup_delay_secs` starts those of a config reloaded in the meantime.
+    auto_start_tunnels: Arc<Mutex<Vec<String>>>,
 }
 
 impl AppState {
@@ -35,18 +45,24 @@ impl AppState {
                 (Config::default(), None)
             }
         };
+        config.apply_log_level();
+        config.apply_time_format();
+        config.report_problems(&LinuxNotifier);
 
         let commands = config.to_tunnel_commands();
         let path = config.get_path();
 
         // Initialize the tunnel manager
-        let tunnel_manager = TunnelManager {
-            commands_config: Arc::new(Mutex::new(commands)),
-            active_tunnels: Arc::new(Mutex::new(Default::default())),
-            active_commands: Arc::new(Mutex::new(Default::default())),
-            generations: Arc::new(Mutex::new(Default::default())),
-            env_path: Arc::new(Mutex::new(config.get_path())),
-        };
+        let mut tunnel_manager = TunnelManager::new(commands, config.get_path());
+        tunnel_manager.set_notifier(Arc::new(LinuxNotifier));
+        let (tray, tray_updates) = ChannelTray::new();
+        let tray = Arc::new(tray);
+        tunnel_manager.set_tray_ui(tray.clone());
+        tunnel_manager.set_max_concurrent_starts(config.max_concurrent_starts);
+        tunnel_manager.set_kill_on_quit(config.kill_tunnels_on_quit);
+        if config.auto_restore_tunnels {
+            tunnel_manager.set_state_file(paths.tunnel_state_path());
+        }
 
         // Initialize the command runner
         let mut command_runner = CommandRunner::new(config.get_path());
@@ -106,7 +122,12 @@ impl AppState {
         command_runner.register_all(&config.commands);
 
         // Initialize the task scheduler
-        let scheduler = Arc::new(TaskScheduler::new(path, paths.as_ref()));
+        let mut scheduler = TaskScheduler::new(path, paths.as_ref());
+        scheduler.set_notifier(Arc::new(LinuxNotifier));
+        scheduler.set_poll_interval(config.poll_interval());
+        scheduler.set_history_size(config.history_size());
+        scheduler.set_tray_ui(tray);
+        let scheduler = Arc::new(scheduler);
 
         // Add scheduled tasks from config
         for (key, task_config) in &config.schedules {
@@ -119,24 +140,51 @@ impl AppState {
         scheduler.save_states();
         info!("Saved initial task states to disk");
 
-        // Start the scheduler
-        scheduler.start();
-        info!(
-            "Task scheduler started with {} tasks",
-            config.schedules.len()
-        );
-
-        // Check for missed tasks on startup (before returning Self)
-        info!("Checking for missed tasks on app startup...");
-        scheduler.check_and_run_missed_tasks();
+        config.report_startup(&scheduler, &LinuxNotifier);
+
+        let auto_start_tunnels = Arc::new(Mutex::new(config.auto_start_tunnels()));
+        let launch = {
+            let scheduler = scheduler.clone();
+            let tunnel_manager = tunnel_manager.clone();
+            let auto_start = auto_start_tunnels.clone();
+            let task_count = config.schedules.len();
+            move || {
+                // Start the scheduler
+                scheduler.start();
+                info!("Task scheduler started with {task_count} tasks");
+
+                // Check for missed tasks on startup
+                info!("Checking for missed tasks on app startup...");
+                scheduler.check_and_run_missed_tasks();
+
+                // Bring back the previous session's tunnels (with `[tunnels] auto_restore`)
+                // and start those marked `auto_start`.
+                tunnel_manager.start_at_launch(&auto_start.lock().unwrap());
+            }
+        };
+        // With `startup_delay_secs`, wait in the background so the menu is usable
+        // while Wi-Fi or a VPN comes up.
+        let delay = config.startup_delay();
+        if delay.is_zero() {
+            launch();
+        } else {
+            info!("Starting tunnels and tasks in {delay:?}");
+            thread::spawn(move || {
+                thread::sleep(delay);
+                launch();
+            });
+        }
 
         (
             Self {
                 tunnel_manager,
                 command_runner,
                 scheduler,
+                tray_updates,
                 paths: paths.clone(),
-                config_monitor: ConfigMonitor::new(paths.config_path(), config_contents),
+                notifier: LinuxNotifier,
+                config_monitor: ConfigMonitor::new(config_file(paths.as_ref()), config_contents),
+                auto_start_tunnels,
             },
             config,
         )
@@ -152,22 +200,33 @@ impl AppState {
     pub fn reload_config(&mut self) -> Result<Config, String> {
         let (config, contents) =
             Config::load_with_snapshot(self.paths.as_ref()).map_err(|e| e.to_string())?;
+        config.apply_log_level();
+        config.apply_time_format();
         let path = config.get_path();
 
-        self.scheduler
-            .reconfigure(path.clone(), &config.schedules)?;
+        self.scheduler.reconfigure(path.clone(), &config.schedules);
+        self.scheduler.set_poll_interval(config.poll_interval());
+        self.scheduler.set_history_size(config.history_size());
         self.tunnel_manager
             .reconfigure(config.to_tunnel_commands(), path.clone());
+        self.tunnel_manager
+            .set_max_concurrent_starts(config.max_concurrent_starts);
+        self.tunnel_manager
+            .set_kill_on_quit(config.kill_tunnels_on_quit);
         self.command_runner.reconfigure(path, &config.commands);
         self.config_monitor.mark_applied(contents);
+        *self.auto_start_tunnels.lock().unwrap() = config.auto_start_tunnels();
 
         info!("Reloaded configuration successfully");
         Ok(config)
     }
 
+    /// Stop tunnels and the scheduler, persisting task state so a "Run Now"
+    /// just before quitting still shows as the last run on the next launch.
     pub fn cleanup(&self) {
         self.tunnel_manager.cleanup();
         self.scheduler.stop();
+        self.scheduler.save_states();
     }
 
     /// Restart active tunnels and catch up on missed tasks after a detected wake.
@@ -0,0 +1,82 @@
+//! "Start at Login" through an XDG autostart entry in `~/.config/autostart`,
+//! which GNOME, KDE, and most other desktops read at login.
+
+use std::fs;
+use std::path::PathBuf;
+
+use log::info;
+use something_bg_core::platform::Autostart;
+
+const ENTRY_NAME: &str = "something_bg.desktop";
+
+#[derive(Default)]
+pub struct LinuxAutostart;
+
+impl LinuxAutostart {
+    fn entry_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("autostart")
+            .join(ENTRY_NAME)
+    }
+}
+
+impl Autostart for LinuxAutostart {
+    /// An entry turned off in the desktop's own startup settings counts as off.
+    fn is_enabled(&self) -> bool {
+        fs::read_to_string(Self::entry_path()).is_ok_and(|entry| {
+            !entry.lines().any(|line| {
+                matches!(
+                    line.trim(),
+                    "Hidden=true" | "X-GNOME-Autostart-enabled=false"
+                )
+            })
+        })
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let path = Self::entry_path();
+        if !enabled {
+            return match fs::remove_file(&path) {
+                Ok(()) => {
+                    info!("removed autostart entry {:?}", path);
+                    Ok(())
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(format!("Failed to remove {}: {e}", path.display())),
+            };
+        }
+
+        let exe = std::env::current_exe()
+            .map_err(|e| format!("Failed to find the app's executable: {e}"))?;
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Something in the Background\n\
+             Exec={}\n\
+             X-GNOME-Autostart-enabled=true\n",
+            quote_exec(&exe.to_string_lossy())
+        );
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        fs::write(&path, entry).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        info!("wrote autostart entry {:?}", path);
+        Ok(())
+    }
+}
+
+/// Quote a program path for a desktop entry's `Exec` key, escaping the
+/// characters the spec reserves inside quotes.
+fn quote_exec(program: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in program.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
@@ -1,13 +1,20 @@
 //! Linux tray shell for something_bg.
-//! Provides a status icon with toggles for tunnels and scheduled tasks.
+//! Provides a status icon with toggles for tunnels and scheduled tasks, or with
+//! `--headless` just the scheduler, for machines without a desktop session.
 
 mod app;
+mod autostart;
 mod menu;
+mod notifier;
 mod paths;
+mod tray;
+mod wake_detector;
 
+use std::cell::RefCell;
 use std::process::Command;
+use std::rc::Rc;
 use std::sync::{
-    Arc,
+    Arc, Mutex,
     atomic::{AtomicBool, Ordering},
 };
 use std::thread;
@@ -16,24 +23,38 @@ use std::time::{Duration, Instant};
 use ctrlc;
 use env_logger;
 use gtk::glib;
+use gtk::glib::thread_guard::ThreadGuard;
 use gtk::prelude::*;
 use log::{error, info, warn};
 use something_bg_core::config::Config;
-use something_bg_core::platform::AppPaths;
+use something_bg_core::instance::InstanceLock;
+use something_bg_core::logging::{self, RotatingFileSink};
+use something_bg_core::platform::{AppPaths, Autostart, Notifier};
+use something_bg_core::scheduler::tray_tooltip;
 use tray_icon::menu::MenuEvent;
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
 use crate::app::AppState;
+use crate::autostart::LinuxAutostart;
 use crate::menu::{MenuAction, MenuHandles, build_id_lookup, build_menu, refresh_task_labels};
+use crate::notifier::LinuxNotifier;
+use crate::paths::LinuxPaths;
+use crate::tray::TrayUpdate;
+use crate::wake_detector::WakeDetector;
 
 fn main() {
-    env_logger::init();
+    if std::env::args().skip(1).any(|arg| arg == "--headless") {
+        run_headless();
+        return;
+    }
+
+    init_logger(env_logger::Target::Stderr);
     info!("starting something_bg (linux tray)");
+    let _instance = claim_single_instance(Some(&LinuxNotifier));
 
     gtk::init().expect("failed to init GTK"); // required for tray-icon on Linux
 
     let (app_state, config) = AppState::new();
-    let running = Arc::new(AtomicBool::new(true));
 
     let (active_icon, idle_icon) = build_icons();
     let (menu, handles) = build_menu(&config, app_state.scheduler.as_ref(), false);
@@ -42,25 +63,26 @@ fn main() {
     let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
         .with_icon(idle_icon.clone())
-        .with_tooltip("something_bg")
+        .with_tooltip(tray_tooltip(
+            app_state.tunnel_manager.active_count(),
+            &app_state.scheduler,
+        ))
         .build()
         .expect("failed to create tray icon");
 
-    // Ctrl+C cleanup
+    let main_loop = glib::MainLoop::new(None, false);
+
+    // Ctrl+C only ends the loop; the cleanup after it does the shutdown once.
     {
-        let tm = app_state.tunnel_manager.clone();
-        let sched = app_state.scheduler.clone();
-        let running = running.clone();
+        let main_loop = main_loop.clone();
         ctrlc::set_handler(move || {
-            info!("received signal, cleaning up tunnels and exiting");
-            tm.cleanup();
-            sched.stop();
-            running.store(false, Ordering::SeqCst);
+            info!("received signal, exiting");
+            main_loop.quit();
         })
         .expect("Error setting Ctrl-C handler");
     }
 
-    let mut looper = EventLoop {
+    let looper = Rc::new(RefCell::new(EventLoop {
         tray_icon,
         handles,
         id_lookup,
@@ -68,14 +90,100 @@ fn main() {
         config,
         active_icon,
         idle_icon,
-        running,
-        last_task_refresh: Instant::now(),
-        last_config_check: Instant::now(),
+        main_loop: main_loop.clone(),
         reload_available: false,
         last_tick: Instant::now(),
-    };
+        wake_detector: WakeDetector::start(),
+    }));
+
+    EventLoop::attach(&looper);
+    info!("tray icon ready; entering event loop");
+    main_loop.run();
+
+    info!("exiting event loop; cleaning up");
+    looper.borrow().app_state.cleanup();
+}
+
+/// Run the scheduler without GTK or a tray icon, logging to stdout, until
+/// SIGINT/SIGTERM. The same config drives it; edits apply on restart.
+fn run_headless() {
+    init_logger(env_logger::Target::Stdout);
+    info!("starting something_bg (headless)");
+    let _instance = claim_single_instance(None);
+
+    let (app_state, _config) = AppState::new();
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        ctrlc::set_handler(move || {
+            info!("received signal, shutting down");
+            running.store(false, Ordering::SeqCst);
+        })
+        .expect("Error setting signal handler");
+    }
+
+    // No GTK loop here, so pump the default GLib context for the D-Bus signal.
+    let wake_detector = WakeDetector::start();
+    let context = glib::MainContext::default();
+    let mut last_tick = Instant::now();
+    while running.load(Ordering::SeqCst) {
+        while context.iteration(false) {}
+
+        let elapsed = last_tick.elapsed();
+        if elapsed > Duration::from_secs(30) {
+            info!("Detected system wake (gap {:?})", elapsed);
+            app_state.handle_wake();
+        } else if wake_detector.as_ref().is_some_and(WakeDetector::take_wake) {
+            info!("Detected system wake (logind resume)");
+            app_state.handle_wake();
+        }
+        while let Ok(update) = app_state.tray_updates.try_recv() {
+            if let TrayUpdate::SetItemState(key, active) = update {
+                let state = if active { "active" } else { "inactive" };
+                info!("tunnel '{}' is now {}", key, state);
+            }
+        }
+        last_tick = Instant::now();
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    app_state.cleanup();
+    info!("headless shutdown complete");
+}
 
-    looper.run();
+/// Log to `target` and to the rotating log file next to the task state.
+fn init_logger(target: env_logger::Target) {
+    // Everything passes here; the config's `log_level` sets the real filter.
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
+            .target(target)
+            .build();
+    let file = RotatingFileSink::new(LinuxPaths.log_path());
+    if let Err(e) = logging::install(Box::new(logger), vec![Box::new(file)]) {
+        eprintln!("Failed to install logger: {e}");
+    }
+}
+
+/// Exit if another copy is already running. The returned lock must live until
+/// shutdown; without a usable lock file the app runs unguarded.
+fn claim_single_instance(notifier: Option<&dyn Notifier>) -> Option<InstanceLock> {
+    match InstanceLock::acquire(&LinuxPaths.lock_path()) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            let title = "something_bg is already running";
+            let body =
+                "Another copy is managing the tunnels and scheduled tasks; this one will exit.";
+            match notifier {
+                Some(notifier) => notifier.warn(title, body),
+                None => error!("{title}: {body}"),
+            }
+            std::process::exit(1);
+        }
+        Err(e) => {
+            warn!("{e}; continuing without the single-instance guard");
+            None
+        }
+    }
 }
 
 struct EventLoop {
@@ -86,65 +194,81 @@ struct EventLoop {
     config: Config,
     active_icon: Icon,
     idle_icon: Icon,
-    running: Arc<AtomicBool>,
-    last_task_refresh: Instant,
-    last_config_check: Instant,
+    main_loop: glib::MainLoop,
     reload_available: bool,
     last_tick: Instant,
+    wake_detector: Option<WakeDetector>,
 }
 
 impl EventLoop {
-    fn run(&mut self) {
-        info!("tray icon ready; entering event loop");
-
-        while self.running.load(Ordering::SeqCst) {
-            let elapsed = self.last_tick.elapsed();
-            if elapsed > Duration::from_secs(30) {
-                self.on_wake(elapsed);
-            }
-            self.last_tick = Instant::now();
-
-            // Process menu events (non-blocking)
-            while let Ok(event) = MenuEvent::receiver().try_recv() {
-                self.handle_menu_event(event.id);
-            }
-
-            if self.last_config_check.elapsed() > Duration::from_millis(500) {
-                let changed = self.app_state.config_changed();
-                if changed != self.reload_available {
-                    self.reload_available = changed;
-                    self.rebuild_menu();
-                }
-                self.last_config_check = Instant::now();
-            }
+    /// Hook the loop up to the default GLib context: menu clicks are handled as
+    /// soon as GTK delivers them, everything else on timers, so nothing spins.
+    fn attach(looper: &Rc<RefCell<Self>>) {
+        // muda calls the handler from GTK's signal dispatch on this thread; defer
+        // to an idle callback so the menu is never rebuilt from inside its own signal.
+        let handler_loop = Mutex::new(ThreadGuard::new(Rc::clone(looper)));
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let looper = Rc::clone(handler_loop.lock().unwrap().get_ref());
+            glib::idle_add_local_once(move || {
+                let mut looper = looper.borrow_mut();
+                looper.handle_menu_event(event.id);
+                looper.drain_tray_updates();
+            });
+        }));
+
+        let tick_loop = Rc::clone(looper);
+        glib::timeout_add_local(Duration::from_millis(500), move || {
+            tick_loop.borrow_mut().tick();
+            glib::ControlFlow::Continue
+        });
+
+        // Periodically refresh task labels so "Last run" stays current
+        let refresh_loop = Rc::clone(looper);
+        glib::timeout_add_seconds_local(15, move || {
+            let looper = refresh_loop.borrow();
+            refresh_task_labels(&looper.handles, looper.app_state.scheduler.as_ref());
+            looper.update_tooltip();
+            glib::ControlFlow::Continue
+        });
+    }
 
-            // Periodically refresh task labels so "Last run" stays current
-            if self.last_task_refresh.elapsed() > Duration::from_secs(15) {
-                refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
-                self.last_task_refresh = Instant::now();
-            }
+    /// Wake checks, tray updates from the core, and config change polling.
+    fn tick(&mut self) {
+        // The monotonic clock pauses during suspend, so the gap check only
+        // catches a stalled loop; logind's resume signal is the reliable path.
+        let elapsed = self.last_tick.elapsed();
+        if elapsed > Duration::from_secs(30) {
+            self.on_wake(&format!("gap {:?}", elapsed));
+        } else if self
+            .wake_detector
+            .as_ref()
+            .is_some_and(WakeDetector::take_wake)
+        {
+            self.on_wake("logind resume");
+        }
+        self.last_tick = Instant::now();
 
-            glib::idle_add_local_once(|| {}); // allow GTK to process pending work
-            while gtk::events_pending() {
-                gtk::main_iteration_do(false);
-            }
+        self.drain_tray_updates();
 
-            thread::sleep(Duration::from_millis(50));
+        let changed = self.app_state.config_changed();
+        if changed != self.reload_available {
+            self.reload_available = changed;
+            self.rebuild_menu();
         }
+    }
 
-        info!("exiting event loop; cleaning up");
-        self.app_state.cleanup();
+    fn drain_tray_updates(&mut self) {
+        while let Ok(update) = self.app_state.tray_updates.try_recv() {
+            self.apply_tray_update(update);
+        }
     }
 
-    fn on_wake(&mut self, gap: Duration) {
+    fn on_wake(&mut self, source: &str) {
         info!(
-            "Detected system wake (gap {:?}); recycling active tunnels",
-            gap
+            "Detected system wake ({}); recycling active tunnels",
+            source
         );
         self.app_state.handle_wake();
-
-        let any_active = self.app_state.tunnel_manager.has_active_tunnels();
-        self.update_icon(any_active);
         refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
     }
 
@@ -154,23 +278,57 @@ impl EventLoop {
                 MenuAction::ToggleTunnel(key) => {
                     self.toggle_tunnel(&key);
                 }
+                MenuAction::ReconnectTunnel(key) => {
+                    self.app_state.tunnel_manager.reconnect(&key);
+                }
+                MenuAction::TestTunnel(key) => {
+                    // Takes up to the test timeout; the manager notifies the result.
+                    let tunnel_manager = self.app_state.tunnel_manager.clone();
+                    thread::spawn(move || {
+                        let _ = tunnel_manager.test(&key);
+                    });
+                }
                 MenuAction::RunCommand(key) => {
                     if let Err(e) = self.app_state.command_runner.run_by_key(&key) {
                         error!("command '{}' failed: {}", key, e);
                     }
                 }
                 MenuAction::RunTask(key) => {
-                    if let Err(e) = self.app_state.scheduler.run_task_now(&key) {
-                        error!("task '{}' failed: {}", key, e);
-                    } else {
-                        refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
+                    // Wait for the exit code off the UI thread; failures reach the
+                    // user through the scheduler's notifier and the periodic label refresh.
+                    let scheduler = Arc::clone(&self.app_state.scheduler);
+                    thread::spawn(move || {
+                        if let Err(e) = scheduler.run_task_now_blocking(&key) {
+                            error!("task '{}' failed: {}", key, e);
+                        }
+                    });
+                }
+                MenuAction::ToggleTaskEnabled(key) => {
+                    let scheduler = &self.app_state.scheduler;
+                    let enabled = scheduler.get_task(&key).is_some_and(|task| task.enabled);
+                    if let Err(e) = scheduler.set_enabled(&key, !enabled) {
+                        error!("failed to toggle task '{}': {}", key, e);
                     }
                 }
+                MenuAction::CopyTunnelCommand(key) => copy_command(
+                    self.app_state.tunnel_manager.effective_command_line(&key),
+                    &self.app_state.notifier,
+                ),
+                MenuAction::CopyTaskCommand(key) => copy_command(
+                    self.app_state.scheduler.effective_command_line(&key),
+                    &self.app_state.notifier,
+                ),
                 MenuAction::About => {
                     open_about();
                 }
                 MenuAction::ReloadConfig => self.reload_config(),
-                MenuAction::OpenConfig => open_config(&self.app_state.paths),
+                MenuAction::OpenConfig => {
+                    open_config(&self.app_state.paths, &self.app_state.notifier)
+                }
+                MenuAction::RevealLog => {
+                    reveal_log(&self.app_state.paths, &self.app_state.notifier)
+                }
+                MenuAction::ToggleStartAtLogin => self.toggle_start_at_login(),
                 MenuAction::DisconnectAll => {
                     self.disconnect_all();
                 }
@@ -178,7 +336,7 @@ impl EventLoop {
                     open_history(&self.app_state.command_runner);
                 }
                 MenuAction::Quit => {
-                    self.running.store(false, Ordering::SeqCst);
+                    self.main_loop.quit();
                 }
             }
         }
@@ -196,6 +354,18 @@ impl EventLoop {
         }
     }
 
+    fn toggle_start_at_login(&self) {
+        let enabled = !LinuxAutostart.is_enabled();
+        if let Err(e) = LinuxAutostart.set_enabled(enabled) {
+            error!("failed to change start at login: {e}");
+            self.app_state.notifier.error("Start at Login", &e);
+        }
+        // The item flips its own check mark; show what actually took effect.
+        self.handles
+            .start_at_login
+            .set_checked(LinuxAutostart.is_enabled());
+    }
+
     fn rebuild_menu(&mut self) {
         let (menu, handles) = build_menu(
             &self.config,
@@ -203,15 +373,10 @@ impl EventLoop {
             self.reload_available,
         );
 
-        let active = self
-            .app_state
-            .tunnel_manager
-            .active_tunnels
-            .lock()
-            .unwrap()
-            .clone();
+        let active = self.app_state.tunnel_manager.active_keys();
         for handle in &handles.tunnels {
             handle.item.set_checked(active.contains(&handle.key));
+            handle.reconnect.set_enabled(active.contains(&handle.key));
         }
         handles.disconnect_all.set_enabled(!active.is_empty());
 
@@ -221,40 +386,48 @@ impl EventLoop {
     }
 
     fn toggle_tunnel(&mut self, key: &str) {
-        let is_active = {
-            let active = self.app_state.tunnel_manager.active_tunnels.lock().unwrap();
-            active.contains(key)
-        };
-        let any_active = self.app_state.tunnel_manager.toggle(key, !is_active);
-        self.update_icon(any_active);
-        self.update_checked_state(key, !is_active);
-        self.set_disconnect_all_enabled(any_active);
+        let is_active = self.app_state.tunnel_manager.is_active(key);
+        // A refused start reaches the user through the tunnel manager's notifier,
+        // and the tray update that follows leaves the item unchecked.
+        if let Err(e) = self.app_state.tunnel_manager.toggle(key, !is_active) {
+            error!("tunnel '{}' not started: {}", key, e);
+        }
     }
 
     fn disconnect_all(&mut self) {
-        let active_keys: Vec<String> = {
-            let active = self.app_state.tunnel_manager.active_tunnels.lock().unwrap();
-            active.iter().cloned().collect()
-        };
+        let active_keys = self.app_state.tunnel_manager.active_keys();
 
         if active_keys.is_empty() {
             return;
         }
 
         for key in active_keys {
-            self.app_state.tunnel_manager.toggle(&key, false);
-            self.update_checked_state(&key, false);
+            let _ = self.app_state.tunnel_manager.toggle(&key, false);
         }
+    }
 
-        let any_active = self.app_state.tunnel_manager.has_active_tunnels();
-        self.update_icon(any_active);
-        self.set_disconnect_all_enabled(any_active);
+    /// Apply a tray change from the core; tunnel and task state always flows back this
+    /// way, so menu handlers only drive the core and never touch checkboxes themselves.
+    fn apply_tray_update(&mut self, update: TrayUpdate) {
+        match update {
+            TrayUpdate::Refresh => {
+                refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
+                self.update_tooltip();
+            }
+            TrayUpdate::SetActive(any_active) => {
+                self.update_icon(any_active);
+                self.set_disconnect_all_enabled(any_active);
+                self.update_tooltip();
+            }
+            TrayUpdate::SetItemState(key, active) => self.update_checked_state(&key, active),
+        }
     }
 
     fn update_checked_state(&mut self, key: &str, checked: bool) {
         for handle in &self.handles.tunnels {
             if handle.key == key {
                 handle.item.set_checked(checked);
+                handle.reconnect.set_enabled(checked);
             }
         }
     }
@@ -270,23 +443,72 @@ impl EventLoop {
         }
     }
 
+    /// Active tunnels and the next task, so the status shows without opening the menu.
+    fn update_tooltip(&self) {
+        let tooltip = tray_tooltip(
+            self.app_state.tunnel_manager.active_count(),
+            &self.app_state.scheduler,
+        );
+        if let Err(e) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            warn!("failed to update tray tooltip: {e}");
+        }
+    }
+
     fn set_disconnect_all_enabled(&self, enabled: bool) {
         self.handles.disconnect_all.set_enabled(enabled);
     }
 }
 
-fn open_config(paths: &std::sync::Arc<crate::paths::LinuxPaths>) {
+fn open_config(paths: &std::sync::Arc<crate::paths::LinuxPaths>, notifier: &dyn Notifier) {
     let config_path = paths.config_path();
     let parent = config_path
         .parent()
         .map(|p| p.to_path_buf())
         .unwrap_or(config_path);
 
+    if let Err(e) = std::fs::create_dir_all(&parent) {
+        warn!("failed to create config folder {:?}: {e}", parent);
+    }
+
     info!("opening config folder at {:?}", parent);
-    let result = Command::new("xdg-open").arg(&parent).spawn();
-    if let Err(e) = result {
-        warn!("xdg-open failed: {e}");
+    open_folder(&parent, "Config folder", notifier);
+}
+
+/// Open the folder holding the log file, e.g. to attach it to an issue report.
+fn reveal_log(paths: &std::sync::Arc<crate::paths::LinuxPaths>, notifier: &dyn Notifier) {
+    let log_path = paths.log_path();
+    let Some(folder) = log_path.parent() else {
+        return;
+    };
+    info!("opening log folder at {:?}", folder);
+    open_folder(folder, "Log folder", notifier);
+}
+
+/// Open `folder` in the file manager; `what` names it in the fallback notification.
+fn open_folder(folder: &std::path::Path, what: &str, notifier: &dyn Notifier) {
+    let openers: [(&str, &[&str]); 2] = [("xdg-open", &[]), ("gio", &["open"])];
+    for (program, args) in openers {
+        match Command::new(program).args(args).arg(folder).spawn() {
+            Ok(_) => return,
+            Err(e) => warn!("{program} failed: {e}"),
+        }
     }
+
+    // No GUI opener available; at least tell the user where it lives.
+    notifier.info(what, &folder.display().to_string());
+}
+
+/// Put an item's effective command on the clipboard and show it, so a failing
+/// run can be reproduced in a terminal.
+fn copy_command(line: Option<String>, notifier: &dyn Notifier) {
+    let Some(line) = line else {
+        return;
+    };
+    let clipboard = gtk::Clipboard::get(&gtk::gdk::SELECTION_CLIPBOARD);
+    clipboard.set_text(&line);
+    // Keep the text available after the app exits, where a clipboard manager runs.
+    clipboard.store();
+    notifier.info("Command copied to clipboard", &line);
 }
 
 fn open_history(command_runner: &something_bg_core::command::CommandRunner) {
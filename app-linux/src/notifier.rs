@@ -0,0 +1,37 @@
+use std::process::Command;
+
+use log::{error, info, warn};
+use something_bg_core::platform::Notifier;
+
+/// Desktop notifications via `notify-send`.
+/// Every message is logged as well, so it stays visible when no notification daemon is running.
+#[derive(Default, Clone, Copy)]
+pub struct LinuxNotifier;
+
+impl LinuxNotifier {
+    fn send(&self, urgency: &str, title: &str, body: &str) {
+        if let Err(e) = Command::new("notify-send")
+            .args(["-u", urgency, title, body])
+            .spawn()
+        {
+            warn!("Failed to send notification: {e}");
+        }
+    }
+}
+
+impl Notifier for LinuxNotifier {
+    fn info(&self, title: &str, body: &str) {
+        info!("{title}: {body}");
+        self.send("normal", title, body);
+    }
+
+    fn warn(&self, title: &str, body: &str) {
+        warn!("{title}: {body}");
+        self.send("normal", title, body);
+    }
+
+    fn error(&self, title: &str, body: &str) {
+        error!("{title}: {body}");
+        self.send("critical", title, body);
+    }
+}
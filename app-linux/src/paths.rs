@@ -1,23 +1,27 @@
 use std::path::PathBuf;
 
-use something_bg_core::platform::AppPaths;
+use something_bg_core::platform::{AppPaths, CONFIG_PATH_ENV, STATE_PATH_ENV, path_override};
 
 #[derive(Default)]
 pub struct LinuxPaths;
 
 impl AppPaths for LinuxPaths {
     fn config_path(&self) -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("something_bg")
-            .join("config.toml")
+        path_override(CONFIG_PATH_ENV, || {
+            dirs::config_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("something_bg")
+                .join("config.toml")
+        })
     }
 
     fn state_path(&self) -> PathBuf {
-        dirs::data_local_dir()
-            .or_else(dirs::data_dir)
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("something_bg")
-            .join("task_state.toml")
+        path_override(STATE_PATH_ENV, || {
+            dirs::data_local_dir()
+                .or_else(dirs::data_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("something_bg")
+                .join("task_state.toml")
+        })
     }
 }
@@ -0,0 +1,68 @@
+//! Detects resume from suspend through systemd-logind's `PrepareForSleep` D-Bus
+//! signal, so missed tasks run and tunnels reconnect as soon as the machine wakes.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gtk::gio;
+use log::{info, warn};
+
+/// Listens on the system bus for as long as it is alive. The signal is delivered
+/// on the default GLib main context, so the owning loop must keep iterating it.
+pub struct WakeDetector {
+    connection: gio::DBusConnection,
+    subscription: Option<gio::SignalSubscriptionId>,
+    woke: Rc<Cell<bool>>,
+}
+
+impl WakeDetector {
+    /// Subscribe to logind. Returns `None` when the system bus is unavailable
+    /// (e.g. in a container), leaving the event loop's clock-gap check as the fallback.
+    pub fn start() -> Option<Self> {
+        let connection = match gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE) {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("System D-Bus unavailable; wake detection falls back to clock gaps: {e}");
+                return None;
+            }
+        };
+
+        let woke = Rc::new(Cell::new(false));
+        let flag = woke.clone();
+        let subscription = connection.signal_subscribe(
+            Some("org.freedesktop.login1"),
+            Some("org.freedesktop.login1.Manager"),
+            Some("PrepareForSleep"),
+            Some("/org/freedesktop/login1"),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, parameters| {
+                // `true` just before suspending, `false` once resumed.
+                if let Some((false,)) = parameters.get::<(bool,)>() {
+                    info!("logind reported resume from sleep");
+                    flag.set(true);
+                }
+            },
+        );
+        info!("Listening for logind sleep/wake signals");
+
+        Some(Self {
+            connection,
+            subscription: Some(subscription),
+            woke,
+        })
+    }
+
+    /// Whether a resume was reported since the last call.
+    pub fn take_wake(&self) -> bool {
+        self.woke.replace(false)
+    }
+}
+
+impl Drop for WakeDetector {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            self.connection.signal_unsubscribe(subscription);
+        }
+    }
+}
@@ -8,17 +8,18 @@ use objc2::rc::Retained;
 use objc2::runtime::{AnyClass, AnyObject};
 use objc2::{ClassType, MainThreadOnly, define_class};
 use objc2_app_kit::NSStatusItem;
-use objc2_foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSString};
-use std::collections::HashSet;
+use objc2_foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSString, NSUserDefaults};
 use std::sync::{Arc, Mutex};
 
 use something_bg_core::command::{CommandRunner, format_duration as format_elapsed};
-use something_bg_core::config::{Config, ConfigMonitor};
+use something_bg_core::config::{Config, ConfigMonitor, TimeFormat, config_file};
 use something_bg_core::platform::AppPaths;
 use something_bg_core::scheduler::TaskScheduler;
 use something_bg_core::tunnel::TunnelManager;
 
+use crate::notifier::MacNotifier;
 use crate::paths::MacPaths;
+use crate::tray::MacTray;
 
 // Wrapper type to make the status item thread-safe
 pub struct StatusItemWrapper(pub Retained<NSStatusItem>);
@@ -33,12 +34,17 @@ pub struct App {
     pub task_scheduler: TaskScheduler,
     pub paths: Arc<MacPaths>,
     pub status_item: Option<Arc<Mutex<StatusItemWrapper>>>,
+    /// Receives tunnel and task state changes from the core.
+    pub tray: Arc<MacTray>,
+    /// The parsed config the tunnels, tasks, and menu were built from; only
+    /// replaced by a reload, so opening the menu never rereads the file.
+    config: Mutex<Arc<Config>>,
     config_monitor: ConfigMonitor,
 }
 
 impl App {
     /// Creates a new `App` with commands loaded from config file.
-    pub fn new() -> (Self, Config) {
+    pub fn new() -> Self {
         let paths = Arc::new(MacPaths::default());
 
         // Load configuration from TOML file
@@ -53,18 +59,23 @@ impl App {
                 (Config::default(), None)
             }
         };
+        config.apply_log_level();
+        config.apply_time_format_with(system_time_format);
+        config.report_problems(&MacNotifier);
 
         let commands = config.to_tunnel_commands();
         let path = config.get_path();
 
         // Initialize the tunnel manager
-        let tunnel_manager = TunnelManager {
-            commands_config: Arc::new(Mutex::new(commands)),
-            active_tunnels: Arc::new(Mutex::new(HashSet::new())),
-            active_commands: Arc::new(Mutex::new(Default::default())),
-            generations: Arc::new(Mutex::new(Default::default())),
-            env_path: Arc::new(Mutex::new(config.get_path())),
-        };
+        let mut tunnel_manager = TunnelManager::new(commands, config.get_path());
+        tunnel_manager.set_notifier(Arc::new(MacNotifier));
+        let tray = Arc::new(MacTray::default());
+        tunnel_manager.set_tray_ui(tray.clone());
+        tunnel_manager.set_max_concurrent_starts(config.max_concurrent_starts);
+        tunnel_manager.set_kill_on_quit(config.kill_tunnels_on_quit);
+        if config.auto_restore_tunnels {
+            tunnel_manager.set_state_file(paths.tunnel_state_path());
+        }
 
         // Initialize the command runner
         let mut command_runner = CommandRunner::new(config.get_path());
@@ -129,7 +140,11 @@ impl App {
         command_runner.register_all(&config.commands);
 
         // Initialize the task scheduler
-        let task_scheduler = TaskScheduler::new(path, paths.as_ref());
+        let mut task_scheduler = TaskScheduler::new(path, paths.as_ref());
+        task_scheduler.set_notifier(Arc::new(MacNotifier));
+        task_scheduler.set_poll_interval(config.poll_interval());
+        task_scheduler.set_history_size(config.history_size());
+        task_scheduler.set_tray_ui(tray.clone());
 
         // Add scheduled tasks from config
         for (key, task_config) in &config.schedules {
@@ -142,27 +157,18 @@ impl App {
         task_scheduler.save_states();
         info!("Saved initial task states to disk");
 
-        // Start the scheduler
-        task_scheduler.start();
-        info!(
-            "Task scheduler started with {} tasks",
-            config.schedules.len()
-        );
-
-        // Check for missed tasks on startup (before returning Self)
-        info!("Checking for missed tasks on app startup...");
-        task_scheduler.check_and_run_missed_tasks();
+        config.report_startup(&task_scheduler, &MacNotifier);
 
-        let app = Self {
+        Self {
             tunnel_manager,
             command_runner: Mutex::new(command_runner),
             task_scheduler,
             paths: paths.clone(),
             status_item: None,
-            config_monitor: ConfigMonitor::new(paths.config_path(), config_contents),
-        };
-
-        (app, config)
+            tray,
+            config: Mutex::new(Arc::new(config)),
+            config_monitor: ConfigMonitor::new(config_file(paths.as_ref()), config_contents),
+        }
     }
 
     pub fn set_status_item(&mut self, item: Retained<NSStatusItem>) {
@@ -181,6 +187,27 @@ impl App {
         self.task_scheduler.stop();
     }
 
+    /// Start the scheduler, catch up on missed tasks, then bring back the previous
+    /// session's tunnels (with `[tunnels] auto_restore`) and start those marked
+    /// `auto_start`. Called once the menu exists to reflect them.
+    pub fn start_at_launch(&self) {
+        let config = self.config();
+
+        // Start the scheduler
+        self.task_scheduler.start();
+        info!(
+            "Task scheduler started with {} tasks",
+            config.schedules.len()
+        );
+
+        // Check for missed tasks on startup
+        info!("Checking for missed tasks on app startup...");
+        self.task_scheduler.check_and_run_missed_tasks();
+
+        self.tunnel_manager
+            .start_at_launch(&config.auto_start_tunnels());
+    }
+
     /// Called when the system wakes from sleep to check for and run any missed scheduled tasks
     pub fn handle_wake_from_sleep(&self) {
         info!("System woke from sleep - restarting active tunnels and checking tasks");
@@ -193,7 +220,12 @@ impl App {
     }
 
     pub fn config_path(&self) -> std::path::PathBuf {
-        self.paths.config_path()
+        config_file(self.paths.as_ref())
+    }
+
+    /// The config currently applied, as loaded at launch or by the last reload.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.lock().unwrap().clone()
     }
 
     pub fn config_changed(&self) -> bool {
@@ -203,21 +235,32 @@ impl App {
         })
     }
 
-    pub fn reload_config(&self) -> Result<Config, String> {
+    pub fn reload_config(&self) -> Result<Arc<Config>, String> {
         let (config, contents) =
             Config::load_with_snapshot(self.paths.as_ref()).map_err(|e| e.to_string())?;
+        config.apply_log_level();
+        config.apply_time_format_with(system_time_format);
         let path = config.get_path();
 
         self.task_scheduler
-            .reconfigure(path.clone(), &config.schedules)?;
+            .reconfigure(path.clone(), &config.schedules);
+        self.task_scheduler
+            .set_poll_interval(config.poll_interval());
+        self.task_scheduler.set_history_size(config.history_size());
         self.tunnel_manager
             .reconfigure(config.to_tunnel_commands(), path.clone());
+        self.tunnel_manager
+            .set_max_concurrent_starts(config.max_concurrent_starts);
+        self.tunnel_manager
+            .set_kill_on_quit(config.kill_tunnels_on_quit);
         self.command_runner
             .lock()
             .unwrap()
             .reconfigure(path, &config.commands);
         self.config_monitor.mark_applied(contents);
 
+        let config = Arc::new(config);
+        *self.config.lock().unwrap() = config.clone();
         info!("Reloaded configuration successfully");
         Ok(config)
     }
@@ -263,6 +306,23 @@ impl NotifDelegate {
     }
 }
 
+/// The clock set in System Settings. Apps started from the Dock or at login get no
+/// `LANG`, so the "24-hour time" switch and the region in `AppleLocale` are read from
+/// the user's defaults, with the locale variables as a last resort.
+fn system_time_format() -> TimeFormat {
+    let defaults = NSUserDefaults::standardUserDefaults();
+    if defaults.boolForKey(&NSString::from_str("AppleICUForce24HourTime")) {
+        return TimeFormat::TwentyFourHour;
+    }
+    if defaults.boolForKey(&NSString::from_str("AppleICUForce12HourTime")) {
+        return TimeFormat::TwelveHour;
+    }
+    defaults
+        .stringForKey(&NSString::from_str("AppleLocale"))
+        .map(|locale| TimeFormat::from_locale(&locale.to_string()))
+        .unwrap_or_else(TimeFormat::from_locale_env)
+}
+
 /// Set up native notification delivery with the app's icon and click-to-view-history.
 /// Must be called on the main thread after GLOBAL_APP is set.
 pub fn setup_notification_center(mtm: MainThreadMarker) {
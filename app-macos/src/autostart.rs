@@ -0,0 +1,78 @@
+// src/autostart.rs
+//
+// "Start at Login" through a per-user LaunchAgent in ~/Library/LaunchAgents,
+// which launchd runs at login without needing the ServiceManagement framework.
+
+use std::fs;
+use std::path::PathBuf;
+
+use log::info;
+use something_bg_core::platform::Autostart;
+
+use crate::paths::BUNDLE_ID;
+
+#[derive(Default)]
+pub struct MacAutostart;
+
+impl MacAutostart {
+    fn agent_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Library/LaunchAgents")
+            .join(format!("{BUNDLE_ID}.plist"))
+    }
+}
+
+impl Autostart for MacAutostart {
+    fn is_enabled(&self) -> bool {
+        Self::agent_path().is_file()
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let path = Self::agent_path();
+        if !enabled {
+            return match fs::remove_file(&path) {
+                Ok(()) => {
+                    info!("Removed launch agent {:?}", path);
+                    Ok(())
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(format!("Failed to remove {}: {e}", path.display())),
+            };
+        }
+
+        let exe = std::env::current_exe()
+            .map_err(|e| format!("Failed to find the app's executable: {e}"))?;
+        let agent = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{BUNDLE_ID}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            escape_xml(&exe.to_string_lossy())
+        );
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        fs::write(&path, agent).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        info!("Wrote launch agent {:?}", path);
+        Ok(())
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
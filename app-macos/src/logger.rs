@@ -4,12 +4,17 @@
 
 use log::LevelFilter;
 use oslog::OsLogger;
+use something_bg_core::logging::{self, RotatingFileSink};
+use something_bg_core::platform::AppPaths;
 
-/// Initializes the logger for the entire application.
-/// Typically called early in `main()`.
+use crate::paths::MacPaths;
+
+/// Initializes the logger for the entire application: the unified system log
+/// plus a rotating file users can attach to a bug report.
+/// Typically called early in `main()`. Every level passes here; the config's
+/// `log_level` sets the real filter once it is loaded.
 pub fn init_logger() {
-    OsLogger::new("com.vim-zz.something-bg")
-        .level_filter(LevelFilter::Debug)
-        .init()
-        .unwrap();
+    let os_log = OsLogger::new("com.vim-zz.something-bg").level_filter(LevelFilter::Trace);
+    let file = RotatingFileSink::new(MacPaths.log_path());
+    logging::install(Box::new(os_log), vec![Box::new(file)]).unwrap();
 }
@@ -5,20 +5,27 @@
 // look up the instance of `App` easily. Alternatively, you can store the `App` reference
 // inside the Objective-C handler class.
 
-use log::info;
+use log::{info, warn};
 use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy};
-use objc2_foundation::{MainThreadMarker, NSNotificationCenter};
+use objc2_foundation::{MainThreadMarker, NSNotificationCenter, NSTimer};
+use something_bg_core::instance::InstanceLock;
+use something_bg_core::platform::{AppPaths, Notifier};
 use std::sync::OnceLock;
 
 mod about;
 mod app;
+mod autostart;
 mod logger;
 mod menu;
+mod notifier;
 mod paths;
+mod tray;
 mod updater;
 mod wake_detector;
 
 use app::App;
+use notifier::MacNotifier;
+use paths::MacPaths;
 
 // Expose the global App so that `toggleTunnel` can access it.
 // This is just an example—there are alternative approaches for bridging
@@ -38,6 +45,25 @@ fn main() {
     logger::init_logger();
     info!("Application starting up");
 
+    // 1a. Move files from the pre-Application Support layout before anything reads them.
+    MacPaths.migrate_legacy_files();
+
+    // 1b. Refuse to run next to another copy; the lock lives until the process exits.
+    let _instance = match InstanceLock::acquire(&MacPaths.lock_path()) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            MacNotifier.warn(
+                "Something in the Background is already running",
+                "Another copy is managing the tunnels and scheduled tasks; this one will exit.",
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            warn!("{e}; continuing without the single-instance guard");
+            None
+        }
+    };
+
     // 2. Get the main thread marker (required for AppKit APIs)
     let mtm = MainThreadMarker::new().expect("Must be on main thread");
 
@@ -48,15 +74,37 @@ fn main() {
     // 4. Create the handler (Objective-C class) for menu events
     let handler = menu::MenuHandler::new(mtm);
 
-    let (mut the_app, config) = App::new();
+    let mut the_app = App::new();
+    let config = the_app.config();
 
     // 5. Create the status bar item with attached menu
-    let status_item = menu::create_status_item(&handler, &config, mtm);
+    let failed_tasks = the_app.task_scheduler.failed_tasks();
+    let status_item = menu::create_status_item(&handler, &config, &failed_tasks, mtm);
 
     // Store the app in the global variable
     the_app.set_status_item(status_item);
     GLOBAL_APP.set(the_app).ok().unwrap();
 
+    // Start tasks and tunnels now that the menu exists to reflect them. With
+    // `startup_delay_secs`, wait in the background so Wi-Fi or a VPN can come up.
+    let delay = config.startup_delay();
+    if delay.is_zero() {
+        if let Some(app) = GLOBAL_APP.get() {
+            app.start_at_launch();
+            if let Some(status_item) = app.get_status_item() {
+                menu::update_status_item_tooltip(&status_item, mtm);
+            }
+        }
+    } else {
+        info!("Starting tunnels and tasks in {delay:?}");
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            if let Some(app) = GLOBAL_APP.get() {
+                app.start_at_launch();
+            }
+        });
+    }
+
     // 5b. Set up native notification center (shows app icon, handles "Show" clicks)
     app::setup_notification_center(mtm);
 
@@ -68,6 +116,19 @@ fn main() {
     });
     let _wake_observer = wake_detector::setup_wake_observer();
 
+    // 6b. Apply tray updates queued by tunnel and scheduler threads (e.g. a tunnel
+    // that gave up retrying); AppKit objects may only be touched from the main thread.
+    // SAFETY: the handler implements `drainTrayUpdates:` and outlives the run loop.
+    let _tunnel_status_timer = unsafe {
+        NSTimer::scheduledTimerWithTimeInterval_target_selector_userInfo_repeats(
+            1.0,
+            &handler,
+            objc2::sel!(drainTrayUpdates:),
+            None,
+            true,
+        )
+    };
+
     // 7. Observe application termination
     let notification_center = NSNotificationCenter::defaultCenter();
     unsafe {
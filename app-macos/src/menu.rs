@@ -4,20 +4,29 @@
 // that receives menu events. We keep the function references the same, but route
 // the logic to `toggleTunnel` in `tunnel.rs`.
 
+use std::collections::HashMap;
+
 use log::{error, warn};
 use objc2::{
-    ClassType, MainThreadOnly, define_class, rc::Retained, runtime::AnyObject,
+    AllocAnyThread, ClassType, MainThreadOnly, define_class, rc::Retained, runtime::AnyObject,
     runtime::ProtocolObject, sel,
 };
-use objc2_app_kit::{NSImage, NSMenu, NSMenuDelegate, NSMenuItem, NSStatusBar, NSStatusItem};
+use objc2_app_kit::{
+    NSImage, NSMenu, NSMenuDelegate, NSMenuItem, NSPasteboard, NSPasteboardTypeString, NSStatusBar,
+    NSStatusItem,
+};
 use objc2_foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSString, ns_string};
 
 use crate::GLOBAL_APP;
+use crate::autostart::MacAutostart;
+use crate::notifier::MacNotifier;
 use crate::paths::MacPaths;
+use crate::tray::TrayUpdate;
 use something_bg_core::config::{
-    CommandConfig, Config, ScheduledTaskConfig, SectionKind, TunnelConfig,
+    CommandConfig, Config, IconSpec, ScheduledTaskConfig, SectionKind, TunnelConfig,
 };
-use something_bg_core::platform::AppPaths;
+use something_bg_core::platform::{AppPaths, Autostart, Notifier};
+use something_bg_core::scheduler::tray_tooltip;
 
 // These are backup icons if image loading fails
 const ICON_INACTIVE: &str = "○"; // Empty circle for idle
@@ -27,6 +36,9 @@ const ICON_ACTIVE: &str = "●"; // Filled circle for active
 const DISCONNECT_ALL_TAG: isize = 9999;
 const RELOAD_CONFIG_TAG: isize = 10_000;
 const CHECK_FOR_UPDATES_TAG: isize = 10_001;
+const RECONNECT_TAG: isize = 10_002;
+const COPY_COMMAND_TAG: isize = 10_003;
+const TEST_TAG: isize = 10_004;
 
 // Declare the MenuHandler class using objc2's define_class! macro
 define_class!(
@@ -44,6 +56,7 @@ define_class!(
         #[unsafe(method(menuNeedsUpdate:))]
         fn menu_needs_update(&self, menu: &NSMenu) {
             update_scheduled_task_items(menu);
+            update_reconnect_items(menu);
             update_reload_item(menu);
             update_check_for_updates_item(menu);
         }
@@ -55,6 +68,30 @@ define_class!(
             toggle_tunnel_handler(item);
         }
 
+        #[unsafe(method(reconnectTunnel:))]
+        fn reconnect_tunnel(&self, item: &NSMenuItem) {
+            reconnect_tunnel_handler(item);
+        }
+
+        #[unsafe(method(testTunnel:))]
+        fn test_tunnel(&self, item: &NSMenuItem) {
+            test_tunnel_handler(item);
+        }
+
+        #[unsafe(method(copyTunnelCommand:))]
+        fn copy_tunnel_command(&self, item: &NSMenuItem) {
+            copy_command_handler(item, |app, key| {
+                app.tunnel_manager.effective_command_line(key)
+            });
+        }
+
+        #[unsafe(method(copyTaskCommand:))]
+        fn copy_task_command(&self, item: &NSMenuItem) {
+            copy_command_handler(item, |app, key| {
+                app.task_scheduler.effective_command_line(key)
+            });
+        }
+
         #[unsafe(method(applicationWillTerminate:))]
         fn application_will_terminate(&self, _notification: &NSObject) {
             crate::application_will_terminate_handler();
@@ -72,6 +109,16 @@ define_class!(
             edit_config_handler();
         }
 
+        #[unsafe(method(toggleStartAtLogin:))]
+        fn toggle_start_at_login(&self, item: &NSMenuItem) {
+            toggle_start_at_login_handler(item);
+        }
+
+        #[unsafe(method(revealLog:))]
+        fn reveal_log(&self, _item: &NSMenuItem) {
+            reveal_log_handler();
+        }
+
         #[unsafe(method(reloadConfig:))]
         fn reload_config(&self, _item: &NSMenuItem) {
             reload_config_handler(self);
@@ -82,6 +129,11 @@ define_class!(
             run_scheduled_task_handler(item);
         }
 
+        #[unsafe(method(toggleTaskEnabled:))]
+        fn toggle_task_enabled(&self, item: &NSMenuItem) {
+            toggle_task_enabled_handler(item);
+        }
+
         #[unsafe(method(displayAppInfo:))]
         fn display_app_info(&self, _item: &NSMenuItem) {
             crate::about::show_about_window();
@@ -118,6 +170,13 @@ define_class!(
         fn view_command_history(&self, _item: &NSMenuItem) {
             view_command_history_handler();
         }
+
+        #[unsafe(method(drainTrayUpdates:))]
+        fn drain_tray_updates(&self, _timer: &AnyObject) {
+            if let Some(app) = GLOBAL_APP.get() {
+                app.tray.drain();
+            }
+        }
     }
 );
 
@@ -155,13 +214,36 @@ fn run_scheduled_task_handler(item: &NSMenuItem) {
 
         info!("Manually triggering scheduled task: {}", task_id_str);
 
-        if let Some(app) = crate::GLOBAL_APP.get() {
-            if let Err(e) = app.task_scheduler.run_task_now(&task_id_str) {
+        // Wait for the exit code off the main thread; the scheduler's notifier
+        // reports failures.
+        std::thread::spawn(move || {
+            if let Some(app) = crate::GLOBAL_APP.get()
+                && let Err(e) = app.task_scheduler.run_task_now_blocking(&task_id_str)
+            {
                 error!("Failed to run task '{}': {}", task_id_str, e);
             }
-            // Note: The menu will update automatically next time it's opened
-            // via the menuNeedsUpdate delegate method
-        }
+        });
+        // Note: The menu will update automatically next time it's opened
+        // via the menuNeedsUpdate delegate method
+    }
+}
+
+/// Pause or resume a scheduled task; the checkmark follows on the next menu update.
+fn toggle_task_enabled_handler(item: &NSMenuItem) {
+    let Some(represented_obj) = item.representedObject() else {
+        return;
+    };
+    let task_id_str = extract_nsstring_from_object(&represented_obj);
+    let Some(app) = crate::GLOBAL_APP.get() else {
+        return;
+    };
+    let enabled = app
+        .task_scheduler
+        .get_task(&task_id_str)
+        .is_some_and(|task| task.enabled);
+    match app.task_scheduler.set_enabled(&task_id_str, !enabled) {
+        Ok(()) => item.setState(if enabled { 0 } else { 1 }),
+        Err(e) => error!("Failed to toggle task '{}': {}", task_id_str, e),
     }
 }
 
@@ -251,11 +333,13 @@ fn edit_config_handler() {
     let config_path = if let Some(app) = GLOBAL_APP.get() {
         app.config_path()
     } else {
-        MacPaths::default().config_path()
+        something_bg_core::config::config_file(&MacPaths::default())
     };
 
-    // Ensure the config file exists by triggering load (creates default if missing)
-    let _ = Config::load_with(&MacPaths::default());
+    // Loading creates the default file; only needed if it was deleted since launch.
+    if !config_path.exists() {
+        let _ = Config::load_with(&MacPaths::default());
+    }
 
     match Command::new("open").arg(&config_path).spawn() {
         Ok(_) => info!("Opened config file for editing"),
@@ -263,6 +347,35 @@ fn edit_config_handler() {
     }
 }
 
+/// Register or unregister the app as a login item, then show what took effect.
+fn toggle_start_at_login_handler(item: &NSMenuItem) {
+    let enabled = !MacAutostart.is_enabled();
+    if let Err(e) = MacAutostart.set_enabled(enabled) {
+        error!("Failed to change start at login: {}", e);
+        MacNotifier.error("Start at Login", &e);
+    }
+    item.setState(if MacAutostart.is_enabled() { 1 } else { 0 });
+}
+
+/// Select the log file in Finder, e.g. to attach it to an issue report.
+fn reveal_log_handler() {
+    use log::{error, info};
+    use std::process::Command;
+
+    let log_path = MacPaths.log_path();
+    // `open -R` needs an existing file; before the first write, show its folder.
+    let mut command = Command::new("open");
+    if log_path.exists() {
+        command.arg("-R").arg(&log_path);
+    } else {
+        command.arg(log_path.parent().unwrap_or(&log_path));
+    }
+    match command.spawn() {
+        Ok(_) => info!("Revealed log file {:?}", log_path),
+        Err(e) => error!("Failed to reveal log file: {}", e),
+    }
+}
+
 /// Apply the changed config and rebuild the status menu.
 fn reload_config_handler(handler: &MenuHandler) {
     let Some(app) = GLOBAL_APP.get() else {
@@ -275,7 +388,8 @@ fn reload_config_handler(handler: &MenuHandler) {
                 return;
             };
             if let Some(status_item) = app.get_status_item() {
-                status_item.setMenu(Some(&create_menu(handler, &config, mtm)));
+                let failed_tasks = app.task_scheduler.failed_tasks();
+                status_item.setMenu(Some(&create_menu(handler, &config, &failed_tasks, mtm)));
                 update_status_item_title(
                     &status_item,
                     app.tunnel_manager.has_active_tunnels(),
@@ -288,53 +402,80 @@ fn reload_config_handler(handler: &MenuHandler) {
 }
 
 /// Handle toggling a tunnel menu item by delegating into the shared App state.
+/// The checkbox, icon, and "Disconnect All" follow through the app's `TrayUi`.
 fn toggle_tunnel_handler(item: &NSMenuItem) {
-    // Identify if the menu item is currently active or not.
-    let state = item.state();
-    let new_state = if state == 1 { 0 } else { 1 }; // NSOnState = 1, NSOffState = 0
-    item.setState(new_state);
+    // NSOnState = 1, NSOffState = 0
+    let enable = item.state() != 1;
 
     // Extract the command key from the menu item
     if let Some(command_id) = item.representedObject() {
         let command_key = extract_nsstring_from_object(&command_id);
 
         if let Some(app) = GLOBAL_APP.get() {
-            let enable = new_state == 1;
-            let any_active = app.tunnel_manager.toggle(&command_key, enable);
-
-            // Update the status item icon if we have a reference to it
-            if let Some(status_item) = app.get_status_item() {
-                if let Some(mtm) = objc2_foundation::MainThreadMarker::new() {
-                    update_status_item_title(&status_item, any_active, mtm);
-
-                    // Enable/disable "Disconnect All" menu item based on active state
-                    if let Some(menu) = status_item.menu(mtm) {
-                        let num_items = menu.numberOfItems();
-                        for i in 0..num_items {
-                            if let Some(menu_item) = menu.itemAtIndex(i) {
-                                if menu_item.tag() == DISCONNECT_ALL_TAG {
-                                    menu_item.setEnabled(any_active);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
+            // A refused start reaches the user through the tunnel manager's notifier.
+            if let Err(e) = app.tunnel_manager.toggle(&command_key, enable) {
+                error!("tunnel '{}' not started: {}", command_key, e);
             }
         }
     }
 }
 
+/// Kill and restart an active tunnel's process without switching it off.
+fn reconnect_tunnel_handler(item: &NSMenuItem) {
+    if let Some(command_id) = item.representedObject() {
+        let command_key = extract_nsstring_from_object(&command_id);
+
+        if let Some(app) = GLOBAL_APP.get() {
+            app.tunnel_manager.reconnect(&command_key);
+        }
+    }
+}
+
+/// Run the tunnel's command briefly off the main thread; the tunnel manager's
+/// notifier reports whether it worked.
+fn test_tunnel_handler(item: &NSMenuItem) {
+    if let Some(command_id) = item.representedObject() {
+        let command_key = extract_nsstring_from_object(&command_id);
+
+        std::thread::spawn(move || {
+            if let Some(app) = GLOBAL_APP.get() {
+                let _ = app.tunnel_manager.test(&command_key);
+            }
+        });
+    }
+}
+
+/// Put the effective command of the item's tunnel or task on the pasteboard and
+/// show it, so a failing run can be reproduced in Terminal.
+fn copy_command_handler(
+    item: &NSMenuItem,
+    command_line: impl Fn(&crate::app::App, &str) -> Option<String>,
+) {
+    let Some(represented_obj) = item.representedObject() else {
+        return;
+    };
+    let key = extract_nsstring_from_object(&represented_obj);
+    let Some(line) = GLOBAL_APP.get().and_then(|app| command_line(app, &key)) else {
+        return;
+    };
+
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.clearContents();
+    // SAFETY: NSPasteboardTypeString is an immutable AppKit constant.
+    let string_type = unsafe { NSPasteboardTypeString };
+    if pasteboard.setString_forType(&NSString::from_str(&line), string_type) {
+        MacNotifier.info("Command copied to clipboard", &line);
+    } else {
+        MacNotifier.warn("Failed to copy the command", &line);
+    }
+}
+
 /// Handler to disconnect all active tunnels
 fn disconnect_all_handler() {
     use log::info;
 
     if let Some(app) = GLOBAL_APP.get() {
-        // Get all active tunnel keys
-        let active_keys: Vec<String> = {
-            let tunnels = app.tunnel_manager.active_tunnels.lock().unwrap();
-            tunnels.iter().cloned().collect()
-        };
+        let active_keys = app.tunnel_manager.active_keys();
 
         if active_keys.is_empty() {
             return;
@@ -342,28 +483,53 @@ fn disconnect_all_handler() {
 
         info!("Disconnecting {} tunnel(s)", active_keys.len());
 
-        // Disconnect each tunnel
+        // Disconnect each tunnel; the menu and icon follow through the app's `TrayUi`.
         for key in &active_keys {
-            app.tunnel_manager.toggle(key, false);
+            let _ = app.tunnel_manager.toggle(key, false);
         }
+    }
+}
 
-        // Update UI
-        if let Some(status_item) = app.get_status_item() {
-            if let Some(mtm) = objc2_foundation::MainThreadMarker::new() {
-                update_status_item_title(&status_item, false, mtm);
+/// Reflect a core state change in the status item and menu. Main thread only.
+pub fn apply_tray_update(update: TrayUpdate) {
+    let (Some(app), Some(mtm)) = (GLOBAL_APP.get(), MainThreadMarker::new()) else {
+        return;
+    };
+    let Some(status_item) = app.get_status_item() else {
+        return;
+    };
 
-                if let Some(menu) = status_item.menu(mtm) {
-                    let num_items = menu.numberOfItems();
-                    for i in 0..num_items {
-                        if let Some(item) = menu.itemAtIndex(i) {
-                            // Uncheck all tunnel items (have represented object, no submenu)
-                            if item.representedObject().is_some() && item.submenu().is_none() {
-                                item.setState(0);
-                            }
-                            // Disable "Disconnect All" item
-                            if item.tag() == DISCONNECT_ALL_TAG {
-                                item.setEnabled(false);
-                            }
+    if let TrayUpdate::SetActive(any_active) = update {
+        update_status_item_title(&status_item, any_active, mtm);
+    }
+    if matches!(update, TrayUpdate::Refresh | TrayUpdate::SetActive(_)) {
+        update_status_item_tooltip(&status_item, mtm);
+    }
+    let Some(menu) = status_item.menu(mtm) else {
+        return;
+    };
+
+    match update {
+        TrayUpdate::Refresh => update_scheduled_task_items(&menu),
+        TrayUpdate::SetActive(any_active) => {
+            for i in 0..menu.numberOfItems() {
+                if let Some(item) = menu.itemAtIndex(i) {
+                    if item.tag() == DISCONNECT_ALL_TAG {
+                        item.setEnabled(any_active);
+                        break;
+                    }
+                }
+            }
+        }
+        TrayUpdate::SetItemState(key, active) => {
+            for i in 0..menu.numberOfItems() {
+                if let Some(item) = menu.itemAtIndex(i) {
+                    if item.action() != Some(sel!(toggleTunnel:)) {
+                        continue;
+                    }
+                    if let Some(command_id) = item.representedObject() {
+                        if extract_nsstring_from_object(&command_id) == key {
+                            item.setState(if active { 1 } else { 0 });
                         }
                     }
                 }
@@ -372,6 +538,29 @@ fn disconnect_all_handler() {
     }
 }
 
+/// Enable each "Reconnect" entry only while its tunnel is active.
+fn update_reconnect_items(menu: &NSMenu) {
+    let Some(app) = GLOBAL_APP.get() else {
+        return;
+    };
+    let Some(reconnect_menu) = menu
+        .itemWithTag(RECONNECT_TAG)
+        .and_then(|item| item.submenu())
+    else {
+        return;
+    };
+    for i in 0..reconnect_menu.numberOfItems() {
+        if let Some(item) = reconnect_menu.itemAtIndex(i)
+            && let Some(command_id) = item.representedObject()
+        {
+            item.setEnabled(
+                app.tunnel_manager
+                    .is_active(&extract_nsstring_from_object(&command_id)),
+            );
+        }
+    }
+}
+
 /// Enable the reload action only while the config differs from the applied snapshot.
 fn update_reload_item(menu: &NSMenu) {
     let changed = GLOBAL_APP.get().is_some_and(|app| app.config_changed());
@@ -412,7 +601,7 @@ fn update_check_for_updates_item(menu: &NSMenu) {
 
 /// Update scheduled task items in the menu to show current "Last run" times
 fn update_scheduled_task_items(menu: &NSMenu) {
-    use something_bg_core::scheduler::format_last_run;
+    use something_bg_core::scheduler::{format_last_result, format_next_run};
 
     // Get the app to access the scheduler
     let Some(app) = crate::GLOBAL_APP.get() else {
@@ -424,17 +613,23 @@ fn update_scheduled_task_items(menu: &NSMenu) {
     for i in 0..num_items {
         if let Some(item) = menu.itemAtIndex(i) {
             // Check if this item has a submenu (scheduled tasks have submenus)
+            if matches!(item.tag(), RECONNECT_TAG | COPY_COMMAND_TAG | TEST_TAG) {
+                continue;
+            }
             if let Some(submenu) = item.submenu() {
                 // The submenu should have items in this order:
                 // 0: Schedule: ...
                 // 1: Next run: ...
                 // 2: Last run: ...
-                // 3: Separator
-                // 4: Run Now
+                // 3: History >
+                // 4: Separator
+                // 5: Run Now
+                // 6: Enabled
+                // 7: Copy Command
 
                 if submenu.numberOfItems() >= 3 {
-                    // Try to get the task ID from the "Run Now" item (index 4)
-                    if let Some(run_now_item) = submenu.itemAtIndex(4) {
+                    // Try to get the task ID from the "Run Now" item (index 5)
+                    if let Some(run_now_item) = submenu.itemAtIndex(5) {
                         if let Some(represented_obj) = run_now_item.representedObject() {
                             let task_id_str = extract_nsstring_from_object(&represented_obj);
 
@@ -442,7 +637,7 @@ fn update_scheduled_task_items(menu: &NSMenu) {
                             if let Some(task) = app.task_scheduler.get_task(&task_id_str) {
                                 // Update "Next run" item (index 1)
                                 if let Some(next_run_item) = submenu.itemAtIndex(1) {
-                                    let next_run_text = format_last_run(&task.next_run);
+                                    let next_run_text = format_next_run(&task);
                                     let new_title =
                                         NSString::from_str(&format!("Next run: {}", next_run_text));
                                     next_run_item.setTitle(&new_title);
@@ -450,11 +645,23 @@ fn update_scheduled_task_items(menu: &NSMenu) {
 
                                 // Update "Last run" item (index 2)
                                 if let Some(last_run_item) = submenu.itemAtIndex(2) {
-                                    let last_run_text = format_last_run(&task.last_run);
+                                    let last_run_text = format_last_result(&task);
                                     let new_title =
                                         NSString::from_str(&format!("Last run: {}", last_run_text));
                                     last_run_item.setTitle(&new_title);
                                 }
+
+                                // Rebuild the "History" submenu (index 3)
+                                if let Some(history_menu) =
+                                    submenu.itemAtIndex(3).and_then(|item| item.submenu())
+                                {
+                                    fill_history_menu(&history_menu, &task_id_str, menu.mtm());
+                                }
+
+                                // Update the "Enabled" checkmark (index 6)
+                                if let Some(enabled_item) = submenu.itemAtIndex(6) {
+                                    enabled_item.setState(if task.enabled { 1 } else { 0 });
+                                }
                             }
                         }
                     }
@@ -464,10 +671,12 @@ fn update_scheduled_task_items(menu: &NSMenu) {
     }
 }
 
-/// Create the NSMenu for the status item.
+/// Create the NSMenu for the status item. Scheduled tasks in `failed_tasks`
+/// (see `TaskScheduler::failed_tasks`) are shown with their error instead.
 pub fn create_menu(
     handler: &MenuHandler,
     config: &Config,
+    failed_tasks: &HashMap<String, String>,
     mtm: MainThreadMarker,
 ) -> Retained<NSMenu> {
     let menu = NSMenu::new(mtm);
@@ -485,6 +694,10 @@ pub fn create_menu(
         .iter()
         .rposition(|section| section.kind == SectionKind::Command && !section.item_ids.is_empty());
     let mut rendered_section = false;
+    let reconnect_menu = NSMenu::new(mtm);
+    reconnect_menu.setAutoenablesItems(false);
+    let copy_command_menu = NSMenu::new(mtm);
+    let test_menu = NSMenu::new(mtm);
     for (section_index, section) in config.sections.iter().enumerate() {
         if section.item_ids.is_empty() {
             continue;
@@ -503,6 +716,21 @@ pub fn create_menu(
                 SectionKind::Tunnel => {
                     if let Some(tunnel) = config.tunnel(key) {
                         menu.addItem(&create_menu_item(handler, tunnel, key, mtm));
+                        reconnect_menu.addItem(&create_reconnect_item(handler, tunnel, key, mtm));
+                        copy_command_menu.addItem(&create_action_item(
+                            handler,
+                            &tunnel.name,
+                            sel!(copyTunnelCommand:),
+                            key,
+                            mtm,
+                        ));
+                        test_menu.addItem(&create_action_item(
+                            handler,
+                            &tunnel.name,
+                            sel!(testTunnel:),
+                            key,
+                            mtm,
+                        ));
                     }
                 }
                 SectionKind::Command => {
@@ -512,7 +740,14 @@ pub fn create_menu(
                 }
                 SectionKind::ScheduledTask => {
                     if let Some(task) = config.schedule(key) {
-                        menu.addItem(&create_scheduled_task_item(handler, task, key, mtm));
+                        match failed_tasks.get(key) {
+                            Some(reason) => {
+                                menu.addItem(&create_failed_task_item(&task.name, reason, mtm))
+                            }
+                            None => {
+                                menu.addItem(&create_scheduled_task_item(handler, task, key, mtm))
+                            }
+                        }
                     }
                 }
             }
@@ -559,9 +794,54 @@ pub fn create_menu(
     set_menu_item_target(&edit_config_item, handler as &AnyObject);
     settings_menu.addItem(&edit_config_item);
 
+    let reveal_log_item = create_menu_item_with_action(
+        ns_string!("Reveal Log"),
+        Some(sel!(revealLog:)),
+        ns_string!(""),
+        mtm,
+    );
+    set_menu_item_target(&reveal_log_item, handler as &AnyObject);
+    settings_menu.addItem(&reveal_log_item);
+
+    let start_at_login_item = create_menu_item_with_action(
+        ns_string!("Start at Login"),
+        Some(sel!(toggleStartAtLogin:)),
+        ns_string!(""),
+        mtm,
+    );
+    set_menu_item_target(&start_at_login_item, handler as &AnyObject);
+    start_at_login_item.setState(if MacAutostart.is_enabled() { 1 } else { 0 });
+    settings_menu.addItem(&start_at_login_item);
+
     settings_item.setSubmenu(Some(&settings_menu));
     menu.addItem(&settings_item);
 
+    // Add the "Reconnect" submenu, one entry per tunnel
+    if reconnect_menu.numberOfItems() > 0 {
+        let reconnect_item =
+            create_menu_item_with_action(ns_string!("Reconnect"), None, ns_string!(""), mtm);
+        reconnect_item.setTag(RECONNECT_TAG);
+        reconnect_item.setSubmenu(Some(&reconnect_menu));
+        menu.addItem(&reconnect_item);
+    }
+
+    // Add the "Copy Command" submenu, one entry per tunnel
+    if copy_command_menu.numberOfItems() > 0 {
+        let copy_command_item =
+            create_menu_item_with_action(ns_string!("Copy Command"), None, ns_string!(""), mtm);
+        copy_command_item.setTag(COPY_COMMAND_TAG);
+        copy_command_item.setSubmenu(Some(&copy_command_menu));
+        menu.addItem(&copy_command_item);
+    }
+
+    // Add the "Test" submenu, one entry per tunnel
+    if test_menu.numberOfItems() > 0 {
+        let test_item = create_menu_item_with_action(ns_string!("Test"), None, ns_string!(""), mtm);
+        test_item.setTag(TEST_TAG);
+        test_item.setSubmenu(Some(&test_menu));
+        menu.addItem(&test_item);
+    }
+
     // Add "Disconnect All" item
     let disconnect_all_item = create_menu_item_with_action(
         ns_string!("Disconnect All"),
@@ -652,13 +932,9 @@ fn create_menu_item(
     let command_id_ns = NSString::from_str(command_id);
     set_menu_item_represented_object(&item, &command_id_ns);
     set_menu_item_target(&item, handler as &AnyObject);
-    let active = GLOBAL_APP.get().is_some_and(|app| {
-        app.tunnel_manager
-            .active_tunnels
-            .lock()
-            .unwrap()
-            .contains(command_id)
-    });
+    let active = GLOBAL_APP
+        .get()
+        .is_some_and(|app| app.tunnel_manager.is_active(command_id));
     item.setState(if active { 1 } else { 0 });
 
     item
@@ -682,6 +958,62 @@ fn create_command_menu_item(
     item
 }
 
+/// Helper to create a tunnel's entry in the "Reconnect" submenu
+fn create_reconnect_item(
+    handler: &MenuHandler,
+    tunnel: &TunnelConfig,
+    key: &str,
+    mtm: MainThreadMarker,
+) -> Retained<NSMenuItem> {
+    let title_ns = NSString::from_str(&tunnel.name);
+    let item =
+        create_menu_item_with_action(&title_ns, Some(sel!(reconnectTunnel:)), ns_string!(""), mtm);
+    let key_ns = NSString::from_str(key);
+    set_menu_item_represented_object(&item, &key_ns);
+    set_menu_item_target(&item, handler as &AnyObject);
+    item.setEnabled(
+        GLOBAL_APP
+            .get()
+            .is_some_and(|app| app.tunnel_manager.is_active(key)),
+    );
+    item
+}
+
+/// Helper to create a menu item that sends `action` with `key` as its represented object
+fn create_action_item(
+    handler: &MenuHandler,
+    title: &str,
+    action: objc2::runtime::Sel,
+    key: &str,
+    mtm: MainThreadMarker,
+) -> Retained<NSMenuItem> {
+    let title_ns = NSString::from_str(title);
+    let item = create_menu_item_with_action(&title_ns, Some(action), ns_string!(""), mtm);
+    let key_ns = NSString::from_str(key);
+    set_menu_item_represented_object(&item, &key_ns);
+    set_menu_item_target(&item, handler as &AnyObject);
+    item
+}
+
+/// Helper to create the item for a task the scheduler rejected: its name flagged,
+/// with only the (disabled) reason in its submenu, so it is not mistaken for one
+/// that runs.
+fn create_failed_task_item(
+    name: &str,
+    reason: &str,
+    mtm: MainThreadMarker,
+) -> Retained<NSMenuItem> {
+    let title_ns = NSString::from_str(&format!("{name} ⚠ invalid schedule"));
+    let item = create_menu_item_with_action(&title_ns, None, ns_string!(""), mtm);
+    let submenu = NSMenu::new(mtm);
+    let reason_ns = NSString::from_str(reason);
+    let reason_item = create_menu_item_with_action(&reason_ns, None, ns_string!(""), mtm);
+    reason_item.setEnabled(false);
+    submenu.addItem(&reason_item);
+    item.setSubmenu(Some(&submenu));
+    item
+}
+
 /// Helper to create a menu item for a scheduled task with submenu
 fn create_scheduled_task_item(
     handler: &MenuHandler,
@@ -699,13 +1031,19 @@ fn create_scheduled_task_item(
     // Get task info from scheduler if available
     let (schedule_text, last_run_text) = if let Some(app) = crate::GLOBAL_APP.get() {
         let schedule = if let Some(task) = app.task_scheduler.get_task(task_id) {
-            something_bg_core::scheduler::cron_to_human_readable(&task.cron_schedule)
+            something_bg_core::scheduler::cron_to_human_readable(
+                &task.cron_schedule,
+                task.timezone.as_deref(),
+            )
         } else {
-            something_bg_core::scheduler::cron_to_human_readable(&task_config.cron_schedule)
+            something_bg_core::scheduler::cron_to_human_readable(
+                &task_config.cron_schedule,
+                task_config.timezone.as_deref(),
+            )
         };
 
         let last_run = if let Some(task) = app.task_scheduler.get_task(task_id) {
-            something_bg_core::scheduler::format_last_run(&task.last_run)
+            something_bg_core::scheduler::format_last_result(&task)
         } else {
             "Never".to_string()
         };
@@ -713,7 +1051,10 @@ fn create_scheduled_task_item(
         (schedule, last_run)
     } else {
         (
-            something_bg_core::scheduler::cron_to_human_readable(&task_config.cron_schedule),
+            something_bg_core::scheduler::cron_to_human_readable(
+                &task_config.cron_schedule,
+                task_config.timezone.as_deref(),
+            ),
             "Never".to_string(),
         )
     };
@@ -727,7 +1068,7 @@ fn create_scheduled_task_item(
     // Add next run info (disabled/grayed out)
     let next_run_text = if let Some(app) = crate::GLOBAL_APP.get() {
         if let Some(task) = app.task_scheduler.get_task(task_id) {
-            something_bg_core::scheduler::format_last_run(&task.next_run)
+            something_bg_core::scheduler::format_next_run(&task)
         } else {
             "Unknown".to_string()
         }
@@ -745,6 +1086,15 @@ fn create_scheduled_task_item(
     last_run_item.setEnabled(false);
     submenu.addItem(&last_run_item);
 
+    // Add recent runs, newest first
+    let history_item =
+        create_menu_item_with_action(ns_string!("History"), None, ns_string!(""), mtm);
+    let history_menu = NSMenu::new(mtm);
+    history_menu.setAutoenablesItems(false);
+    fill_history_menu(&history_menu, task_id, mtm);
+    history_item.setSubmenu(Some(&history_menu));
+    submenu.addItem(&history_item);
+
     // Add separator
     let separator = NSMenuItem::separatorItem(mtm);
     submenu.addItem(&separator);
@@ -761,45 +1111,107 @@ fn create_scheduled_task_item(
     set_menu_item_target(&run_now_item, handler as &AnyObject);
     submenu.addItem(&run_now_item);
 
+    // Add "Enabled" toggle; unchecked tasks keep their menu entry but skip their schedule
+    let enabled_item = create_menu_item_with_action(
+        ns_string!("Enabled"),
+        Some(sel!(toggleTaskEnabled:)),
+        ns_string!(""),
+        mtm,
+    );
+    let enabled = crate::GLOBAL_APP
+        .get()
+        .and_then(|app| app.task_scheduler.get_task(task_id))
+        .map_or(task_config.enabled, |task| task.enabled);
+    enabled_item.setState(if enabled { 1 } else { 0 });
+    set_menu_item_represented_object(&enabled_item, &task_id_ns);
+    set_menu_item_target(&enabled_item, handler as &AnyObject);
+    submenu.addItem(&enabled_item);
+
+    // Add "Copy Command", the exact invocation for reproducing a run in Terminal
+    submenu.addItem(&create_action_item(
+        handler,
+        "Copy Command",
+        sel!(copyTaskCommand:),
+        task_id,
+        mtm,
+    ));
+
     // Attach submenu to main item
     item.setSubmenu(Some(&submenu));
 
     item
 }
 
-/// Load an icon from an SF Symbol.
-/// SF Symbol format: "sf:symbol.name"
+/// Replace the entries of a task's "History" submenu with its recorded runs.
+fn fill_history_menu(history_menu: &NSMenu, task_id: &str, mtm: MainThreadMarker) {
+    let history = crate::GLOBAL_APP
+        .get()
+        .map(|app| app.task_scheduler.get_history(task_id))
+        .unwrap_or_default();
+    let labels: Vec<String> = if history.is_empty() {
+        vec!["No runs yet".to_string()]
+    } else {
+        history
+            .iter()
+            .map(something_bg_core::scheduler::format_run_record)
+            .collect()
+    };
+
+    history_menu.removeAllItems();
+    for label in labels {
+        let title = NSString::from_str(&label);
+        let item = create_menu_item_with_action(&title, None, ns_string!(""), mtm);
+        item.setEnabled(false);
+        history_menu.addItem(&item);
+    }
+}
+
+/// Load a section header icon: an SF Symbol (`sf:`), or an image file shown as is
+/// (`file:`) or tinted like a symbol (`template:`).
 fn load_icon(icon_spec: &str) -> Option<Retained<NSImage>> {
-    if icon_spec.starts_with("sf:") {
-        // Load SF Symbol (macOS 11+)
-        let symbol_name = &icon_spec[3..];
-        let symbol_ns = NSString::from_str(symbol_name);
-
-        let image = NSImage::imageWithSystemSymbolName_accessibilityDescription(&symbol_ns, None);
-        if let Some(img) = image {
-            // Set image size to 16x16 for menu items
-            img.setSize(objc2_foundation::NSSize {
-                width: 16.0,
-                height: 16.0,
-            });
-            Some(img)
-        } else {
-            warn!("Failed to load SF Symbol: {}", symbol_name);
+    let image = match IconSpec::parse(icon_spec) {
+        Ok(IconSpec::Symbol(symbol_name)) => {
+            // Load SF Symbol (macOS 11+)
+            let symbol_ns = NSString::from_str(&symbol_name);
+            let image =
+                NSImage::imageWithSystemSymbolName_accessibilityDescription(&symbol_ns, None);
+            if image.is_none() {
+                warn!("Failed to load SF Symbol: {}", symbol_name);
+            }
+            image
+        }
+        Ok(IconSpec::File(path)) => load_image_file(&path, false),
+        Ok(IconSpec::Template(path)) => load_image_file(&path, true),
+        Err(e) => {
+            warn!("Unsupported section {e}");
             None
         }
-    } else {
-        warn!(
-            "Unsupported icon format: {}. Use 'sf:symbol.name'",
-            icon_spec
-        );
-        None
+    }?;
+
+    // Set image size to 16x16 for menu items
+    image.setSize(objc2_foundation::NSSize {
+        width: 16.0,
+        height: 16.0,
+    });
+    Some(image)
+}
+
+/// Load a PNG (or any format NSImage reads) from disk.
+fn load_image_file(path: &std::path::Path, template: bool) -> Option<Retained<NSImage>> {
+    let path_ns = NSString::from_str(&path.to_string_lossy());
+    let image = NSImage::initWithContentsOfFile(NSImage::alloc(), &path_ns);
+    match &image {
+        Some(image) => image.setTemplate(template),
+        None => warn!("Failed to load icon image: {}", path.display()),
     }
+    image
 }
 
 /// Creates a status bar item and attaches the menu to it.
 pub fn create_status_item(
     handler: &MenuHandler,
     config: &Config,
+    failed_tasks: &HashMap<String, String>,
     mtm: MainThreadMarker,
 ) -> Retained<NSStatusItem> {
     let status_bar = NSStatusBar::systemStatusBar();
@@ -810,10 +1222,19 @@ pub fn create_status_item(
         button.setTitle(&title);
     }
 
-    status_item.setMenu(Some(&create_menu(handler, config, mtm)));
+    status_item.setMenu(Some(&create_menu(handler, config, failed_tasks, mtm)));
     status_item
 }
 
+/// Show the active tunnels and the next task when hovering over the status item.
+pub fn update_status_item_tooltip(status_item: &NSStatusItem, mtm: MainThreadMarker) {
+    let (Some(app), Some(button)) = (GLOBAL_APP.get(), status_item.button(mtm)) else {
+        return;
+    };
+    let tooltip = tray_tooltip(app.tunnel_manager.active_count(), &app.task_scheduler);
+    button.setToolTip(Some(&NSString::from_str(&tooltip)));
+}
+
 pub fn update_status_item_title(status_item: &NSStatusItem, active: bool, mtm: MainThreadMarker) {
     if let Some(button) = status_item.button(mtm) {
         let title_str = if active { ICON_ACTIVE } else { ICON_INACTIVE };
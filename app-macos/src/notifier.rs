@@ -0,0 +1,26 @@
+use log::{error, info, warn};
+use something_bg_core::platform::Notifier;
+
+use crate::app::send_notification;
+
+/// Native notifications via NSUserNotificationCenter.
+/// Every message is logged as well, so it is not lost when notifications are disabled.
+#[derive(Default, Clone, Copy)]
+pub struct MacNotifier;
+
+impl Notifier for MacNotifier {
+    fn info(&self, title: &str, body: &str) {
+        info!("{title}: {body}");
+        send_notification(title, body);
+    }
+
+    fn warn(&self, title: &str, body: &str) {
+        warn!("{title}: {body}");
+        send_notification(title, body);
+    }
+
+    fn error(&self, title: &str, body: &str) {
+        error!("{title}: {body}");
+        send_notification(title, body);
+    }
+}
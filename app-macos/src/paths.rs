@@ -1,26 +1,94 @@
+use std::fs;
 use std::path::PathBuf;
 
-use something_bg_core::platform::AppPaths;
+use log::{info, warn};
+use something_bg_core::platform::{
+    AppPaths, CONFIG_PATH_ENV, STATE_PATH_ENV, beside_state, path_override,
+};
 
-/// macOS implementation of application paths.
-/// Keeps existing layout under ~/.config/something_bg for now.
+/// Folder under `~/Library/Application Support`; the bundle id also used for logging.
+pub(crate) const BUNDLE_ID: &str = "com.vim-zz.something-bg";
+
+/// Files moved out of `~/.config/something_bg` by [`MacPaths::migrate_legacy_files`].
+/// Anything else there (e.g. a scripts folder the config points at) stays put.
+const LEGACY_FILES: &[&str] = &[
+    "config.toml",
+    "config.json",
+    "task_state.toml",
+    "tunnel_state.toml",
+];
+
+/// macOS implementation of application paths, under
+/// `~/Library/Application Support/com.vim-zz.something-bg`.
 #[derive(Default)]
 pub struct MacPaths;
 
+impl MacPaths {
+    /// Move files from the old `~/.config/something_bg` layout into Application
+    /// Support, once, skipping any that already exist there. A file that cannot be
+    /// moved keeps being read from the old folder.
+    pub fn migrate_legacy_files(&self) {
+        let (legacy, support) = (legacy_dir(), support_dir());
+        for name in LEGACY_FILES {
+            let (from, to) = (legacy.join(name), support.join(name));
+            if !from.is_file() || to.exists() {
+                continue;
+            }
+            match fs::create_dir_all(&support).and_then(|()| fs::rename(&from, &to)) {
+                Ok(()) => info!("Moved {} to {}", from.display(), to.display()),
+                Err(e) => warn!(
+                    "Failed to move {} to {}: {e}; still using the old location",
+                    from.display(),
+                    to.display()
+                ),
+            }
+        }
+    }
+}
+
 impl AppPaths for MacPaths {
     fn config_path(&self) -> PathBuf {
-        let mut base = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        base.push(".config");
-        base.push("something_bg");
-        base.push("config.toml");
-        base
+        path_override(CONFIG_PATH_ENV, || {
+            dir_holding(&["config.toml", "config.json"]).join("config.toml")
+        })
     }
 
     fn state_path(&self) -> PathBuf {
-        let mut base = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        base.push(".config");
-        base.push("something_bg");
-        base.push("task_state.toml");
-        base
+        path_override(STATE_PATH_ENV, || {
+            dir_holding(&["task_state.toml"]).join("task_state.toml")
+        })
+    }
+
+    /// Always in Application Support (unless the state path is overridden): the
+    /// logger starts before the legacy files are moved, and the log is new anyway.
+    fn log_path(&self) -> PathBuf {
+        let state = path_override(STATE_PATH_ENV, || support_dir().join("task_state.toml"));
+        beside_state(&state, "something_bg.log", ".log")
+    }
+}
+
+fn support_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(BUNDLE_ID)
+}
+
+fn legacy_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join("something_bg")
+}
+
+/// Application Support, unless none of `files` are there yet but some are
+/// still in the legacy folder.
+fn dir_holding(files: &[&str]) -> PathBuf {
+    let support = support_dir();
+    let legacy = legacy_dir();
+    let found = |dir: &PathBuf| files.iter().any(|name| dir.join(name).exists());
+    if !found(&support) && found(&legacy) {
+        legacy
+    } else {
+        support
     }
 }
@@ -0,0 +1,55 @@
+// src/tray.rs
+//
+// `TrayUi` implementation handed to the core. AppKit objects may only be touched on
+// the main thread, so updates from tunnel and scheduler threads are queued and
+// applied by a main-thread timer; updates made on the main thread apply at once.
+
+use objc2_foundation::MainThreadMarker;
+use something_bg_core::platform::TrayUi;
+use std::sync::Mutex;
+
+/// A tray change requested by the core.
+pub enum TrayUpdate {
+    Refresh,
+    SetActive(bool),
+    SetItemState(String, bool),
+}
+
+#[derive(Default)]
+pub struct MacTray {
+    pending: Mutex<Vec<TrayUpdate>>,
+}
+
+impl MacTray {
+    fn dispatch(&self, update: TrayUpdate) {
+        if MainThreadMarker::new().is_some() {
+            // Apply anything queued earlier first so updates never arrive out of order.
+            self.drain();
+            crate::menu::apply_tray_update(update);
+        } else {
+            self.pending.lock().unwrap().push(update);
+        }
+    }
+
+    /// Apply updates queued from background threads. Must run on the main thread.
+    pub fn drain(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for update in pending {
+            crate::menu::apply_tray_update(update);
+        }
+    }
+}
+
+impl TrayUi for MacTray {
+    fn refresh(&self) {
+        self.dispatch(TrayUpdate::Refresh);
+    }
+
+    fn set_active(&self, any_active: bool) {
+        self.dispatch(TrayUpdate::SetActive(any_active));
+    }
+
+    fn set_menu_item_state(&self, key: &str, active: bool) {
+        self.dispatch(TrayUpdate::SetItemState(key.to_string(), active));
+    }
+}
@@ -0,0 +1,101 @@
+//! "Start at Login" through the per-user `Run` registry key.
+//! Non-Windows builds (used for cross-checking) report it as unavailable.
+
+use something_bg_core::platform::Autostart;
+
+#[derive(Default)]
+pub struct WindowsAutostart;
+
+#[cfg(windows)]
+impl Autostart for WindowsAutostart {
+    fn is_enabled(&self) -> bool {
+        imp::is_registered()
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        if enabled {
+            let exe = std::env::current_exe()
+                .map_err(|e| format!("Failed to find the app's executable: {e}"))?;
+            imp::register(&format!("\"{}\"", exe.display()))
+        } else {
+            imp::unregister()
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS};
+    use windows_sys::Win32::System::Registry::{
+        HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ, RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW,
+    };
+
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE_NAME: &str = "something_bg";
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    pub fn is_registered() -> bool {
+        let (key, name) = (wide(RUN_KEY), wide(VALUE_NAME));
+        // SAFETY: both strings are NUL-terminated; no data is read back.
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_SZ,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        status == ERROR_SUCCESS
+    }
+
+    /// Run `command_line` at login.
+    pub fn register(command_line: &str) -> Result<(), String> {
+        let (key, name, data) = (wide(RUN_KEY), wide(VALUE_NAME), wide(command_line));
+        let bytes = (data.len() * size_of::<u16>()) as u32;
+        // SAFETY: all strings are NUL-terminated and `bytes` covers `data` exactly.
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                REG_SZ,
+                data.as_ptr().cast(),
+                bytes,
+            )
+        };
+        if status == ERROR_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!("Failed to add the login entry (error {status})"))
+        }
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        let (key, name) = (wide(RUN_KEY), wide(VALUE_NAME));
+        // SAFETY: both strings are NUL-terminated.
+        let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key.as_ptr(), name.as_ptr()) };
+        match status {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            _ => Err(format!("Failed to remove the login entry (error {status})")),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl Autostart for WindowsAutostart {
+    fn is_enabled(&self) -> bool {
+        false
+    }
+
+    fn set_enabled(&self, _enabled: bool) -> Result<(), String> {
+        Err("Start at Login is only available on Windows".to_string())
+    }
+}
@@ -0,0 +1,64 @@
+//! Plain-text clipboard access through the Win32 clipboard API.
+//! Non-Windows builds (used for cross-checking) report it as unavailable.
+
+#[cfg(windows)]
+pub use imp::set_text;
+
+#[cfg(windows)]
+mod imp {
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::GlobalFree;
+    use windows_sys::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData,
+    };
+    use windows_sys::Win32::System::Memory::{
+        GMEM_MOVEABLE, GlobalAlloc, GlobalLock, GlobalUnlock,
+    };
+
+    /// `CF_UNICODETEXT`, spelled out to avoid pulling in the OLE bindings.
+    const CF_UNICODETEXT: u32 = 13;
+
+    /// Replace the clipboard contents with `text`.
+    pub fn set_text(text: &str) -> Result<(), String> {
+        let wide: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+        let bytes = wide.len() * size_of::<u16>();
+
+        // SAFETY: no owner window; the clipboard is closed again below on every path.
+        if unsafe { OpenClipboard(ptr::null_mut()) } == 0 {
+            return Err("Failed to open the clipboard".to_string());
+        }
+        // SAFETY: the clipboard is open; the buffer is sized for `wide` and, once
+        // handed to SetClipboardData, owned by the system and not freed here.
+        let result = unsafe {
+            EmptyClipboard();
+            let memory = GlobalAlloc(GMEM_MOVEABLE, bytes);
+            if memory.is_null() {
+                Err("Failed to allocate clipboard memory".to_string())
+            } else {
+                let target = GlobalLock(memory).cast::<u16>();
+                if target.is_null() {
+                    GlobalFree(memory);
+                    Err("Failed to lock clipboard memory".to_string())
+                } else {
+                    ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+                    GlobalUnlock(memory);
+                    if SetClipboardData(CF_UNICODETEXT, memory).is_null() {
+                        GlobalFree(memory);
+                        Err("Failed to set the clipboard contents".to_string())
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        };
+        // SAFETY: opened above.
+        unsafe { CloseClipboard() };
+        result
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_text(_text: &str) -> Result<(), String> {
+    Err("The clipboard is only available on Windows".to_string())
+}
@@ -1,8 +1,14 @@
 //! Windows tray shell for something_bg.
 
 mod app;
+mod autostart;
+mod clipboard;
 mod menu;
+mod notifier;
 mod paths;
+mod spawner;
+mod tray;
+mod wake_detector;
 
 use std::process::Command;
 use std::sync::{
@@ -16,16 +22,25 @@ use ctrlc;
 use env_logger;
 use log::{error, info, warn};
 use something_bg_core::config::Config;
-use something_bg_core::platform::AppPaths;
+use something_bg_core::instance::InstanceLock;
+use something_bg_core::logging::{self, RotatingFileSink};
+use something_bg_core::platform::{AppPaths, Autostart, Notifier};
+use something_bg_core::scheduler::tray_tooltip;
 use tray_icon::menu::MenuEvent;
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
 use crate::app::AppState;
+use crate::autostart::WindowsAutostart;
 use crate::menu::{MenuAction, MenuHandles, build_id_lookup, build_menu, refresh_task_labels};
+use crate::notifier::WindowsNotifier;
+use crate::paths::WindowsPaths;
+use crate::tray::TrayUpdate;
+use crate::wake_detector::{WakeDetector, pump_messages};
 
 fn main() {
-    env_logger::init();
+    init_logger();
     info!("starting something_bg (windows tray)");
+    let _instance = claim_single_instance();
 
     let (app_state, config) = AppState::new();
     let running = Arc::new(AtomicBool::new(true));
@@ -36,19 +51,19 @@ fn main() {
 
     let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
-        .with_tooltip("something_bg")
+        .with_tooltip(tray_tooltip(
+            app_state.tunnel_manager.active_count(),
+            &app_state.scheduler,
+        ))
         .with_icon(idle_icon.clone())
         .build()
         .expect("failed to create tray icon");
 
+    // Ctrl+C only ends the loop; the cleanup after it does the shutdown once.
     {
-        let tm = app_state.tunnel_manager.clone();
-        let sched = app_state.scheduler.clone();
         let running = running.clone();
         ctrlc::set_handler(move || {
-            info!("received signal, cleaning up tunnels and exiting");
-            tm.cleanup();
-            sched.stop();
+            info!("received signal, exiting");
             running.store(false, Ordering::SeqCst);
         })
         .expect("Error setting Ctrl-C handler");
@@ -67,11 +82,45 @@ fn main() {
         last_config_check: Instant::now(),
         reload_available: false,
         last_tick: Instant::now(),
+        wake_detector: WakeDetector::start(),
     };
 
+    // Tunnels auto-started by `AppState::new` came up before the menu existed.
+    looper.sync_checked_states();
     looper.run();
 }
 
+/// Log to stderr and to the rotating log file next to the task state.
+fn init_logger() {
+    // Everything passes here; the config's `log_level` sets the real filter.
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
+            .build();
+    let file = RotatingFileSink::new(WindowsPaths.log_path());
+    if let Err(e) = logging::install(Box::new(logger), vec![Box::new(file)]) {
+        eprintln!("Failed to install logger: {e}");
+    }
+}
+
+/// Exit if another copy is already running. The returned lock must live until
+/// shutdown; without a usable lock file the app runs unguarded.
+fn claim_single_instance() -> Option<InstanceLock> {
+    match InstanceLock::acquire(&WindowsPaths.lock_path()) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            WindowsNotifier.warn(
+                "something_bg is already running",
+                "Another copy is managing the tunnels and scheduled tasks; this one will exit.",
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            warn!("{e}; continuing without the single-instance guard");
+            None
+        }
+    }
+}
+
 struct EventLoop {
     tray_icon: TrayIcon,
     handles: MenuHandles,
@@ -85,6 +134,7 @@ struct EventLoop {
     last_config_check: Instant,
     reload_available: bool,
     last_tick: Instant,
+    wake_detector: Option<WakeDetector>,
 }
 
 impl EventLoop {
@@ -92,9 +142,17 @@ impl EventLoop {
         info!("tray icon ready; entering event loop");
 
         while self.running.load(Ordering::SeqCst) {
+            pump_messages();
+
             let elapsed = self.last_tick.elapsed();
             if elapsed > Duration::from_secs(30) {
-                self.on_wake(elapsed);
+                self.on_wake(&format!("gap {:?}", elapsed));
+            } else if self
+                .wake_detector
+                .as_ref()
+                .is_some_and(WakeDetector::take_wake)
+            {
+                self.on_wake("resume broadcast");
             }
             self.last_tick = Instant::now();
 
@@ -102,6 +160,10 @@ impl EventLoop {
                 self.handle_menu_event(event.id);
             }
 
+            while let Ok(update) = self.app_state.tray_updates.try_recv() {
+                self.apply_tray_update(update);
+            }
+
             if self.last_config_check.elapsed() > Duration::from_millis(500) {
                 let changed = self.app_state.config_changed();
                 if changed != self.reload_available {
@@ -113,6 +175,7 @@ impl EventLoop {
 
             if self.last_task_refresh.elapsed() > Duration::from_secs(15) {
                 refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
+                self.update_tooltip();
                 self.last_task_refresh = Instant::now();
             }
 
@@ -123,15 +186,12 @@ impl EventLoop {
         self.app_state.cleanup();
     }
 
-    fn on_wake(&mut self, gap: Duration) {
+    fn on_wake(&mut self, source: &str) {
         info!(
-            "Detected system wake (gap {:?}); recycling active tunnels",
-            gap
+            "Detected system wake ({}); recycling active tunnels",
+            source
         );
         self.app_state.handle_wake();
-
-        let any_active = self.app_state.tunnel_manager.has_active_tunnels();
-        self.update_icon(any_active);
         refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
     }
 
@@ -141,21 +201,49 @@ impl EventLoop {
                 MenuAction::ToggleTunnel(key) => {
                     self.toggle_tunnel(&key);
                 }
+                MenuAction::ReconnectTunnel(key) => {
+                    self.app_state.tunnel_manager.reconnect(&key);
+                }
+                MenuAction::TestTunnel(key) => {
+                    // Takes up to the test timeout; the manager notifies the result.
+                    let tunnel_manager = self.app_state.tunnel_manager.clone();
+                    thread::spawn(move || {
+                        let _ = tunnel_manager.test(&key);
+                    });
+                }
                 MenuAction::RunCommand(key) => {
                     if let Err(e) = self.app_state.command_runner.run_by_key(&key) {
                         error!("command '{}' failed: {}", key, e);
                     }
                 }
                 MenuAction::RunTask(key) => {
-                    if let Err(e) = self.app_state.scheduler.run_task_now(&key) {
-                        error!("task '{}' failed: {}", key, e);
-                    } else {
-                        refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
+                    // Wait for the exit code off the UI thread; failures reach the
+                    // user through the scheduler's notifier and the periodic label refresh.
+                    let scheduler = Arc::clone(&self.app_state.scheduler);
+                    thread::spawn(move || {
+                        if let Err(e) = scheduler.run_task_now_blocking(&key) {
+                            error!("task '{}' failed: {}", key, e);
+                        }
+                    });
+                }
+                MenuAction::ToggleTaskEnabled(key) => {
+                    let scheduler = &self.app_state.scheduler;
+                    let enabled = scheduler.get_task(&key).is_some_and(|task| task.enabled);
+                    if let Err(e) = scheduler.set_enabled(&key, !enabled) {
+                        error!("failed to toggle task '{}': {}", key, e);
                     }
                 }
+                MenuAction::CopyTunnelCommand(key) => {
+                    copy_command(self.app_state.tunnel_manager.effective_command_line(&key));
+                }
+                MenuAction::CopyTaskCommand(key) => {
+                    copy_command(self.app_state.scheduler.effective_command_line(&key));
+                }
                 MenuAction::About => open_about(),
                 MenuAction::ReloadConfig => self.reload_config(),
                 MenuAction::OpenConfig => open_config(&self.app_state.paths),
+                MenuAction::RevealLog => reveal_log(&self.app_state.paths),
+                MenuAction::ToggleStartAtLogin => self.toggle_start_at_login(),
                 MenuAction::ViewHistory => {
                     open_history(&self.app_state.command_runner);
                 }
@@ -178,6 +266,18 @@ impl EventLoop {
         }
     }
 
+    fn toggle_start_at_login(&self) {
+        let enabled = !WindowsAutostart.is_enabled();
+        if let Err(e) = WindowsAutostart.set_enabled(enabled) {
+            error!("failed to change start at login: {e}");
+            WindowsNotifier.error("Start at login", &e);
+        }
+        // The item flips its own check mark; show what actually took effect.
+        self.handles
+            .start_at_login
+            .set_checked(WindowsAutostart.is_enabled());
+    }
+
     fn rebuild_menu(&mut self) {
         let (menu, handles) = build_menu(
             &self.config,
@@ -185,40 +285,72 @@ impl EventLoop {
             self.reload_available,
         );
 
-        let active = self
-            .app_state
-            .tunnel_manager
-            .active_tunnels
-            .lock()
-            .unwrap()
-            .clone();
-        for handle in &handles.tunnels {
-            handle.item.set_checked(active.contains(&handle.key));
-        }
-
         self.id_lookup = build_id_lookup(&handles);
         self.handles = handles;
+        self.sync_checked_states();
         self.tray_icon.set_menu(Some(Box::new(menu)));
     }
 
+    /// Set every tunnel checkbox from the manager's active set.
+    fn sync_checked_states(&mut self) {
+        let active = self.app_state.tunnel_manager.active_keys();
+        for handle in &self.handles.tunnels {
+            handle.item.set_checked(active.contains(&handle.key));
+            handle.reconnect.set_enabled(active.contains(&handle.key));
+        }
+    }
+
     fn toggle_tunnel(&mut self, key: &str) {
-        let is_active = {
-            let active = self.app_state.tunnel_manager.active_tunnels.lock().unwrap();
-            active.contains(key)
-        };
-        let any_active = self.app_state.tunnel_manager.toggle(key, !is_active);
-        self.update_icon(any_active);
-        self.update_checked_state(key, !is_active);
+        let is_active = self.app_state.tunnel_manager.is_active(key);
+        // A refused start reaches the user through the tunnel manager's notifier.
+        if let Err(e) = self.app_state.tunnel_manager.toggle(key, !is_active) {
+            error!("tunnel '{}' not started: {}", key, e);
+        }
+        // Windows flips a check item as soon as it is clicked; put it back to what the
+        // core decided, so a refused start (e.g. a missing dependency) stays unchecked.
+        let active = self.app_state.tunnel_manager.is_active(key);
+        self.update_checked_state(key, active);
+    }
+
+    /// Apply a tray change from the core; tunnel and task state always flows back this
+    /// way, so menu handlers only drive the core and only undo the click's own check flip.
+    fn apply_tray_update(&mut self, update: TrayUpdate) {
+        match update {
+            TrayUpdate::Refresh => {
+                refresh_task_labels(&self.handles, self.app_state.scheduler.as_ref());
+                self.update_tooltip();
+            }
+            TrayUpdate::SetActive(any_active) => {
+                self.update_icon(any_active);
+                self.update_tooltip();
+                // Tunnels started or stopped alongside another (dependencies, quit)
+                // only announce the overall state, so re-read every checkbox.
+                self.sync_checked_states();
+            }
+            TrayUpdate::SetItemState(key, active) => self.update_checked_state(&key, active),
+        }
     }
 
     fn update_checked_state(&mut self, key: &str, checked: bool) {
         for handle in &self.handles.tunnels {
             if handle.key == key {
                 handle.item.set_checked(checked);
+                handle.reconnect.set_enabled(checked);
             }
         }
     }
 
+    /// Active tunnels and the next task, so the status shows without opening the menu.
+    fn update_tooltip(&self) {
+        let tooltip = tray_tooltip(
+            self.app_state.tunnel_manager.active_count(),
+            &self.app_state.scheduler,
+        );
+        if let Err(e) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            warn!("failed to update tray tooltip: {e}");
+        }
+    }
+
     fn update_icon(&mut self, any_active: bool) {
         let icon = if any_active {
             self.active_icon.clone()
@@ -238,10 +370,45 @@ fn open_config(paths: &Arc<crate::paths::WindowsPaths>) {
         .map(|p| p.to_path_buf())
         .unwrap_or(config_path);
 
+    if let Err(e) = std::fs::create_dir_all(&parent) {
+        warn!("failed to create config folder {:?}: {e}", parent);
+    }
+
     info!("opening config folder at {:?}", parent);
     let result = Command::new("explorer.exe").arg(&parent).spawn();
     if let Err(e) = result {
-        warn!("explorer.exe failed: {e}");
+        warn!("explorer.exe failed: {e}; config folder is {:?}", parent);
+    }
+}
+
+/// Show the log file selected in Explorer, e.g. to attach it to an issue report.
+fn reveal_log(paths: &Arc<crate::paths::WindowsPaths>) {
+    let log_path = paths.log_path();
+    info!("revealing log file at {:?}", log_path);
+    // Explorer only accepts the path glued to `/select,`; a missing file opens
+    // the default folder instead, so fall back to the log's folder.
+    let result = if log_path.exists() {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(&log_path);
+        Command::new("explorer.exe").arg(select).spawn()
+    } else {
+        let folder = log_path.parent().unwrap_or(&log_path);
+        Command::new("explorer.exe").arg(folder).spawn()
+    };
+    if let Err(e) = result {
+        warn!("explorer.exe failed: {e}; log file is {:?}", log_path);
+    }
+}
+
+/// Put an item's effective command on the clipboard and show it, so a failing
+/// run can be reproduced in a terminal.
+fn copy_command(line: Option<String>) {
+    let Some(line) = line else {
+        return;
+    };
+    match clipboard::set_text(&line) {
+        Ok(()) => WindowsNotifier.info("Command copied to clipboard", &line),
+        Err(e) => WindowsNotifier.warn(&e, &line),
     }
 }
 
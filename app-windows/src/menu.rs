@@ -2,8 +2,14 @@ use std::collections::HashMap;
 
 use log::debug;
 use something_bg_core::config::{Config, SectionKind};
-use something_bg_core::scheduler::{TaskScheduler, cron_to_human_readable, format_last_run};
-use tray_icon::menu::{CheckMenuItem, Menu, MenuId, MenuItem, PredefinedMenuItem};
+use something_bg_core::platform::Autostart;
+use something_bg_core::scheduler::{
+    RunRecord, TaskScheduler, cron_to_human_readable, format_last_result, format_last_run,
+    format_next_run, format_run_record,
+};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+
+use crate::autostart::WindowsAutostart;
 
 pub struct MenuHandles {
     pub tunnels: Vec<TunnelHandle>,
@@ -12,6 +18,9 @@ pub struct MenuHandles {
     pub about_id: MenuId,
     pub reload_config_id: Option<MenuId>,
     pub open_config_id: MenuId,
+    pub reveal_log_id: MenuId,
+    /// Checked while the app is registered to start at login.
+    pub start_at_login: CheckMenuItem,
     pub view_history_id: Option<MenuId>,
     pub quit_id: MenuId,
 }
@@ -20,6 +29,12 @@ pub struct TunnelHandle {
     pub id: MenuId,
     pub key: String,
     pub item: CheckMenuItem,
+    /// Entry in the "Reconnect" submenu, enabled while the tunnel is active.
+    pub reconnect: MenuItem,
+    /// Entry in the "Copy command" submenu.
+    pub copy_command_id: MenuId,
+    /// Entry in the "Test" submenu.
+    pub test_id: MenuId,
 }
 
 pub struct CommandHandle {
@@ -30,7 +45,12 @@ pub struct CommandHandle {
 pub struct TaskHandle {
     pub key: String,
     pub run_id: MenuId,
+    pub copy_command_id: MenuId,
+    /// Checked while the task runs on schedule.
+    pub enabled_item: CheckMenuItem,
+    pub next_run_item: MenuItem,
     pub last_run_item: MenuItem,
+    pub history: Submenu,
 }
 
 pub fn build_menu(
@@ -44,6 +64,10 @@ pub fn build_menu(
     let mut commands = Vec::new();
     let mut tasks = Vec::new();
     let mut view_history_id = None;
+    let reconnect = Submenu::new("Reconnect", true);
+    let copy_command = Submenu::new("Copy command", true);
+    let test = Submenu::new("Test", true);
+    let failed_tasks = scheduler.failed_tasks();
     let last_command_section = config
         .sections
         .iter()
@@ -71,10 +95,25 @@ pub fn build_menu(
                     if let Err(e) = menu.append(&item) {
                         debug!("failed to append tunnel item: {e}");
                     }
+                    let reconnect_item = MenuItem::new(&tunnel.name, false, None);
+                    if let Err(e) = reconnect.append(&reconnect_item) {
+                        debug!("failed to append reconnect item: {e}");
+                    }
+                    let copy_command_item = MenuItem::new(&tunnel.name, true, None);
+                    if let Err(e) = copy_command.append(&copy_command_item) {
+                        debug!("failed to append copy-command item: {e}");
+                    }
+                    let test_item = MenuItem::new(&tunnel.name, true, None);
+                    if let Err(e) = test.append(&test_item) {
+                        debug!("failed to append test item: {e}");
+                    }
                     tunnels.push(TunnelHandle {
                         id,
                         key: key.clone(),
                         item: item.clone(),
+                        reconnect: reconnect_item,
+                        copy_command_id: copy_command_item.id().clone(),
+                        test_id: test_item.id().clone(),
                     });
                 }
                 SectionKind::Command => {
@@ -95,18 +134,47 @@ pub fn build_menu(
                     let Some(task) = config.schedule(key) else {
                         continue;
                     };
+                    if let Some(reason) = failed_tasks.get(key) {
+                        // Disabled, so a rejected task is not mistaken for a scheduled one.
+                        for label in [format!("{} ⚠ invalid schedule", task.name), reason.clone()]
+                        {
+                            if let Err(e) = menu.append(&MenuItem::new(label, false, None)) {
+                                debug!("failed to append failed-task label: {e}");
+                            }
+                        }
+                        continue;
+                    }
                     let schedule_item = MenuItem::new(
-                        format!("Schedule: {}", cron_to_human_readable(&task.cron_schedule)),
+                        format!(
+                            "Schedule: {}",
+                            cron_to_human_readable(&task.cron_schedule, task.timezone.as_deref())
+                        ),
                         false,
                         None,
                     );
                     if let Err(e) = menu.append(&schedule_item) {
                         debug!("failed to append schedule label: {e}");
                     }
+                    let live_task = scheduler.get_task(key);
+                    let next_run_item = MenuItem::new(
+                        format!(
+                            "Next run: {}",
+                            live_task
+                                .as_ref()
+                                .map_or_else(|| format_last_run(&None), format_next_run)
+                        ),
+                        false,
+                        None,
+                    );
+                    if let Err(e) = menu.append(&next_run_item) {
+                        debug!("failed to append next-run label: {e}");
+                    }
                     let last_run_item = MenuItem::new(
                         format!(
                             "Last run: {}",
-                            format_last_run(&scheduler.get_task(key).and_then(|t| t.last_run))
+                            live_task
+                                .as_ref()
+                                .map_or_else(|| format_last_run(&None), format_last_result)
                         ),
                         false,
                         None,
@@ -114,15 +182,37 @@ pub fn build_menu(
                     if let Err(e) = menu.append(&last_run_item) {
                         debug!("failed to append last-run label: {e}");
                     }
+                    let history = Submenu::new("History", true);
+                    fill_history(&history, &scheduler.get_history(key));
+                    if let Err(e) = menu.append(&history) {
+                        debug!("failed to append history submenu: {e}");
+                    }
                     let run_now = MenuItem::new("Run now", true, None);
                     let run_id = run_now.id().clone();
                     if let Err(e) = menu.append(&run_now) {
                         debug!("failed to append run-now item: {e}");
                     }
+                    let enabled_item = CheckMenuItem::new(
+                        "Enabled",
+                        true,
+                        live_task.as_ref().is_none_or(|t| t.enabled),
+                        None,
+                    );
+                    if let Err(e) = menu.append(&enabled_item) {
+                        debug!("failed to append task enabled item: {e}");
+                    }
+                    let copy_command_item = MenuItem::new("Copy command", true, None);
+                    if let Err(e) = menu.append(&copy_command_item) {
+                        debug!("failed to append copy-command item: {e}");
+                    }
                     tasks.push(TaskHandle {
                         key: key.clone(),
                         run_id,
+                        copy_command_id: copy_command_item.id().clone(),
+                        enabled_item,
+                        next_run_item: next_run_item.clone(),
                         last_run_item: last_run_item.clone(),
+                        history,
                     });
                 }
             }
@@ -152,6 +242,22 @@ pub fn build_menu(
         None
     };
 
+    if !tunnels.is_empty()
+        && let Err(e) = menu.append(&reconnect)
+    {
+        debug!("failed to append reconnect submenu: {e}");
+    }
+    if !tunnels.is_empty()
+        && let Err(e) = menu.append(&copy_command)
+    {
+        debug!("failed to append copy-command submenu: {e}");
+    }
+    if !tunnels.is_empty()
+        && let Err(e) = menu.append(&test)
+    {
+        debug!("failed to append test submenu: {e}");
+    }
+
     let about = MenuItem::new("About", true, None);
     let about_id = about.id().clone();
     if let Err(e) = menu.append(&about) {
@@ -164,6 +270,18 @@ pub fn build_menu(
         debug!("failed to append open-config item: {e}");
     }
 
+    let reveal_log = MenuItem::new("Reveal log", true, None);
+    let reveal_log_id = reveal_log.id().clone();
+    if let Err(e) = menu.append(&reveal_log) {
+        debug!("failed to append reveal-log item: {e}");
+    }
+
+    let start_at_login =
+        CheckMenuItem::new("Start at login", true, WindowsAutostart.is_enabled(), None);
+    if let Err(e) = menu.append(&start_at_login) {
+        debug!("failed to append start-at-login item: {e}");
+    }
+
     let quit = MenuItem::new("Quit", true, None);
     let quit_id = quit.id().clone();
     if let Err(e) = menu.append(&quit) {
@@ -179,6 +297,8 @@ pub fn build_menu(
             about_id,
             reload_config_id,
             open_config_id,
+            reveal_log_id,
+            start_at_login,
             view_history_id,
             quit_id,
         },
@@ -194,12 +314,38 @@ fn maybe_add_group_header(menu: &Menu, header: Option<&str>) {
     }
 }
 
+/// Replace the history submenu's entries with `records`, newest first.
+fn fill_history(submenu: &Submenu, records: &[RunRecord]) {
+    for item in submenu.items() {
+        if let Some(item) = item.as_menuitem()
+            && let Err(e) = submenu.remove(item)
+        {
+            debug!("failed to remove history entry: {e}");
+        }
+    }
+    let labels: Vec<String> = if records.is_empty() {
+        vec!["No runs yet".to_string()]
+    } else {
+        records.iter().map(format_run_record).collect()
+    };
+    for label in labels {
+        if let Err(e) = submenu.append(&MenuItem::new(label, false, None)) {
+            debug!("failed to append history entry: {e}");
+        }
+    }
+}
+
+/// Refresh "Next run" and "Last run" labels for all tasks. Call this periodically.
 pub fn refresh_task_labels(handles: &MenuHandles, scheduler: &TaskScheduler) {
     let mut updated = 0;
     for handle in &handles.tasks {
         if let Some(task) = scheduler.get_task(&handle.key) {
-            let label = format!("Last run: {}", format_last_run(&task.last_run));
+            let next_label = format!("Next run: {}", format_next_run(&task));
+            let label = format!("Last run: {}", format_last_result(&task));
+            handle.next_run_item.set_text(&next_label);
             handle.last_run_item.set_text(&label);
+            handle.enabled_item.set_checked(task.enabled);
+            fill_history(&handle.history, &scheduler.get_history(&handle.key));
             updated += 1;
         }
     }
@@ -212,18 +358,40 @@ pub fn build_id_lookup(handles: &MenuHandles) -> HashMap<MenuId, MenuAction> {
     let mut map = HashMap::new();
     for t in &handles.tunnels {
         map.insert(t.id.clone(), MenuAction::ToggleTunnel(t.key.clone()));
+        map.insert(
+            t.reconnect.id().clone(),
+            MenuAction::ReconnectTunnel(t.key.clone()),
+        );
+        map.insert(
+            t.copy_command_id.clone(),
+            MenuAction::CopyTunnelCommand(t.key.clone()),
+        );
+        map.insert(t.test_id.clone(), MenuAction::TestTunnel(t.key.clone()));
     }
     for c in &handles.commands {
         map.insert(c.id.clone(), MenuAction::RunCommand(c.key.clone()));
     }
     for t in &handles.tasks {
         map.insert(t.run_id.clone(), MenuAction::RunTask(t.key.clone()));
+        map.insert(
+            t.enabled_item.id().clone(),
+            MenuAction::ToggleTaskEnabled(t.key.clone()),
+        );
+        map.insert(
+            t.copy_command_id.clone(),
+            MenuAction::CopyTaskCommand(t.key.clone()),
+        );
     }
     map.insert(handles.about_id.clone(), MenuAction::About);
     if let Some(id) = &handles.reload_config_id {
         map.insert(id.clone(), MenuAction::ReloadConfig);
     }
     map.insert(handles.open_config_id.clone(), MenuAction::OpenConfig);
+    map.insert(handles.reveal_log_id.clone(), MenuAction::RevealLog);
+    map.insert(
+        handles.start_at_login.id().clone(),
+        MenuAction::ToggleStartAtLogin,
+    );
     if let Some(id) = &handles.view_history_id {
         map.insert(id.clone(), MenuAction::ViewHistory);
     }
@@ -234,11 +402,18 @@ pub fn build_id_lookup(handles: &MenuHandles) -> HashMap<MenuId, MenuAction> {
 #[derive(Clone, Debug)]
 pub enum MenuAction {
     ToggleTunnel(String),
+    ReconnectTunnel(String),
+    TestTunnel(String),
     RunCommand(String),
     RunTask(String),
+    ToggleTaskEnabled(String),
+    CopyTunnelCommand(String),
+    CopyTaskCommand(String),
     About,
     ReloadConfig,
     OpenConfig,
+    RevealLog,
+    ToggleStartAtLogin,
     ViewHistory,
     Quit,
 }
@@ -0,0 +1,47 @@
+use std::process::Command;
+
+use log::{error, info, warn};
+use something_bg_core::platform::Notifier;
+
+/// Toast notifications via PowerShell.
+/// Every message is logged as well, so it stays visible when toasts are suppressed.
+#[derive(Default, Clone, Copy)]
+pub struct WindowsNotifier;
+
+impl Notifier for WindowsNotifier {
+    fn info(&self, title: &str, body: &str) {
+        info!("{title}: {body}");
+        show_toast(title, body);
+    }
+
+    fn warn(&self, title: &str, body: &str) {
+        warn!("{title}: {body}");
+        show_toast(title, body);
+    }
+
+    fn error(&self, title: &str, body: &str) {
+        error!("{title}: {body}");
+        show_toast(title, body);
+    }
+}
+
+/// Show a two-line toast notification using the WinRT toast API from PowerShell.
+pub fn show_toast(title: &str, body: &str) {
+    let ps_script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $xml.GetElementsByTagName('text'); \
+         $text[0].AppendChild($xml.CreateTextNode('{}')) > $null; \
+         $text[1].AppendChild($xml.CreateTextNode('{}')) > $null; \
+         $toast = [Windows.UI.Notifications.ToastNotification]::new($xml); \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('something_bg').Show($toast)",
+        title.replace('\'', "''"),
+        body.replace('\'', "''")
+    );
+    if let Err(e) = Command::new("powershell")
+        .args(["-Command", &ps_script])
+        .spawn()
+    {
+        warn!("Failed to send notification: {}", e);
+    }
+}
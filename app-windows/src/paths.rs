@@ -1,22 +1,26 @@
 use std::path::PathBuf;
 
-use something_bg_core::platform::AppPaths;
+use something_bg_core::platform::{AppPaths, CONFIG_PATH_ENV, STATE_PATH_ENV, path_override};
 
 #[derive(Default)]
 pub struct WindowsPaths;
 
 impl AppPaths for WindowsPaths {
     fn config_path(&self) -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("something_bg")
-            .join("config.toml")
+        path_override(CONFIG_PATH_ENV, || {
+            dirs::config_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("something_bg")
+                .join("config.toml")
+        })
     }
 
     fn state_path(&self) -> PathBuf {
-        dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("something_bg")
-            .join("task_state.toml")
+        path_override(STATE_PATH_ENV, || {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("something_bg")
+                .join("task_state.toml")
+        })
     }
 }
@@ -0,0 +1,112 @@
+//! Starts tunnels and tasks inside a Job Object, so stopping one also ends the
+//! helper processes it launched instead of leaving them holding a port.
+//! Non-Windows builds (used for cross-checking) fall back to the core spawner.
+
+#[cfg(windows)]
+pub use imp::JobObjectSpawner;
+
+#[cfg(windows)]
+mod imp {
+    use std::collections::HashMap;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::{Child, Command};
+    use std::ptr;
+    use std::sync::Mutex;
+
+    use log::warn;
+    use something_bg_core::platform::ProcessSpawner;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JobObjectBasicAccountingInformation, QueryInformationJobObject, TerminateJobObject,
+    };
+
+    /// Owned job handle, closed on drop. Closing it does not end the processes.
+    struct Job(HANDLE);
+
+    // SAFETY: a job handle is a kernel handle usable from any thread.
+    unsafe impl Send for Job {}
+
+    impl Job {
+        fn create() -> Option<Self> {
+            // SAFETY: default security attributes and an unnamed job.
+            let handle = unsafe { CreateJobObjectW(ptr::null(), ptr::null()) };
+            (!handle.is_null()).then_some(Self(handle))
+        }
+
+        /// Whether any process in the job is still running.
+        fn is_active(&self) -> bool {
+            let mut info = JOBOBJECT_BASIC_ACCOUNTING_INFORMATION::default();
+            // SAFETY: `info` is a correctly sized out-buffer for this information class.
+            let ok = unsafe {
+                QueryInformationJobObject(
+                    self.0,
+                    JobObjectBasicAccountingInformation,
+                    (&mut info as *mut JOBOBJECT_BASIC_ACCOUNTING_INFORMATION).cast(),
+                    size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as u32,
+                    ptr::null_mut(),
+                )
+            };
+            ok == 0 || info.ActiveProcesses > 0
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle came from `CreateJobObjectW` and is closed once.
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    /// Puts every child in its own job and terminates the whole job on `kill`.
+    #[derive(Default)]
+    pub struct JobObjectSpawner {
+        /// Jobs by child pid; pruned once all of a job's processes have exited.
+        jobs: Mutex<HashMap<u32, Job>>,
+    }
+
+    impl ProcessSpawner for JobObjectSpawner {
+        fn spawn(&self, command: &mut Command) -> std::io::Result<Child> {
+            let child = command.spawn()?;
+            let Some(job) = Job::create() else {
+                warn!(
+                    "Could not create a job object for pid {}; only it will be stopped",
+                    child.id()
+                );
+                return Ok(child);
+            };
+            // SAFETY: both handles are valid; the child handle stays owned by `child`.
+            let assigned =
+                unsafe { AssignProcessToJobObject(job.0, child.as_raw_handle() as HANDLE) } != 0;
+            if !assigned {
+                warn!(
+                    "Could not assign pid {} to a job object; only it will be stopped",
+                    child.id()
+                );
+                return Ok(child);
+            }
+
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.retain(|_, job| job.is_active());
+            jobs.insert(child.id(), job);
+            Ok(child)
+        }
+
+        fn kill(&self, child: &mut Child) -> std::io::Result<()> {
+            if let Some(job) = self.jobs.lock().unwrap().remove(&child.id()) {
+                // SAFETY: the handle is a live job created by `spawn`.
+                if unsafe { TerminateJobObject(job.0, 1) } != 0 {
+                    return Ok(());
+                }
+                warn!(
+                    "Failed to terminate the job for pid {}: {}",
+                    child.id(),
+                    std::io::Error::last_os_error()
+                );
+            }
+            child.kill()
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! `TrayUi` for the core: queues tray updates for the event loop, which owns the menu.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use something_bg_core::platform::TrayUi;
+
+/// A tray change requested by the core, applied on the event loop thread.
+pub enum TrayUpdate {
+    Refresh,
+    SetActive(bool),
+    SetItemState(String, bool),
+}
+
+pub struct ChannelTray(Sender<TrayUpdate>);
+
+impl ChannelTray {
+    pub fn new() -> (Self, Receiver<TrayUpdate>) {
+        let (tx, rx) = mpsc::channel();
+        (Self(tx), rx)
+    }
+
+    fn send(&self, update: TrayUpdate) {
+        // The receiver only goes away while the app is shutting down.
+        let _ = self.0.send(update);
+    }
+}
+
+impl TrayUi for ChannelTray {
+    fn refresh(&self) {
+        self.send(TrayUpdate::Refresh);
+    }
+
+    fn set_active(&self, any_active: bool) {
+        self.send(TrayUpdate::SetActive(any_active));
+    }
+
+    fn set_menu_item_state(&self, key: &str, active: bool) {
+        self.send(TrayUpdate::SetItemState(key.to_string(), active));
+    }
+}
@@ -0,0 +1,134 @@
+//! Detects resume from sleep through `WM_POWERBROADCAST`, so missed tasks run and
+//! tunnels reconnect as soon as the machine wakes.
+//!
+//! Power broadcasts only reach top-level windows, so this owns a hidden one.
+//! Non-Windows builds (used for cross-checking) get a detector that never fires.
+
+#[cfg(windows)]
+pub use imp::{WakeDetector, pump_messages};
+
+#[cfg(windows)]
+mod imp {
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use log::{info, warn};
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, MSG,
+        PBT_APMRESUMEAUTOMATIC, PM_REMOVE, PeekMessageW, RegisterClassW, TranslateMessage,
+        WM_POWERBROADCAST, WNDCLASSW,
+    };
+
+    /// Set by the window procedure; there is only ever one detector per process.
+    static RESUMED: AtomicBool = AtomicBool::new(false);
+
+    pub struct WakeDetector {
+        hwnd: HWND,
+    }
+
+    impl WakeDetector {
+        /// Create the hidden window. Returns `None` if Windows refuses, leaving the
+        /// event loop's clock-gap check as the fallback.
+        pub fn start() -> Option<Self> {
+            let class_name: Vec<u16> = "something_bg_power\0".encode_utf16().collect();
+            // SAFETY: the class name outlives both calls and the window procedure
+            // matches the signature Windows expects.
+            let hwnd = unsafe {
+                let instance = GetModuleHandleW(ptr::null());
+                let class = WNDCLASSW {
+                    lpfnWndProc: Some(window_proc),
+                    hInstance: instance,
+                    lpszClassName: class_name.as_ptr(),
+                    ..std::mem::zeroed()
+                };
+                if RegisterClassW(&class) == 0 {
+                    warn!(
+                        "Could not register power window class; wake detection falls back to clock gaps"
+                    );
+                    return None;
+                }
+                CreateWindowExW(
+                    0,
+                    class_name.as_ptr(),
+                    class_name.as_ptr(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    instance,
+                    ptr::null(),
+                )
+            };
+            if hwnd.is_null() {
+                warn!("Could not create power window; wake detection falls back to clock gaps");
+                return None;
+            }
+            info!("Listening for WM_POWERBROADCAST resume events");
+            Some(Self { hwnd })
+        }
+
+        /// Whether a resume was reported since the last call.
+        pub fn take_wake(&self) -> bool {
+            RESUMED.swap(false, Ordering::SeqCst)
+        }
+    }
+
+    impl Drop for WakeDetector {
+        fn drop(&mut self) {
+            // SAFETY: the window was created on this thread and is destroyed once.
+            unsafe {
+                DestroyWindow(self.hwnd);
+            }
+        }
+    }
+
+    /// Drain this thread's message queue. Besides delivering power broadcasts, this
+    /// services tray-icon's own hidden window, which also needs a Win32 message loop.
+    pub fn pump_messages() {
+        // SAFETY: `msg` is a valid out-pointer and only dispatched after a successful peek.
+        unsafe {
+            let mut msg: MSG = std::mem::zeroed();
+            while PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_POWERBROADCAST && wparam as u32 == PBT_APMRESUMEAUTOMATIC {
+            info!("Windows reported resume from sleep");
+            RESUMED.store(true, Ordering::SeqCst);
+            return 1;
+        }
+        // SAFETY: forwarding the arguments Windows passed in.
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+}
+
+#[cfg(not(windows))]
+pub struct WakeDetector;
+
+#[cfg(not(windows))]
+impl WakeDetector {
+    pub fn start() -> Option<Self> {
+        None
+    }
+
+    pub fn take_wake(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(not(windows))]
+pub fn pump_messages() {}
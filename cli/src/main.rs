@@ -0,0 +1,360 @@
+//! Command-line control surface for something_bg, for scripts and CI.
+//! Each invocation loads the config and acts once; it does not talk to a
+//! running tray app. `list --json` output and the exit codes are stable.
+
+mod paths;
+
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::Serialize;
+use something_bg_core::config::Config;
+use something_bg_core::events::Event;
+use something_bg_core::scheduler::{
+    TaskScheduler, cron_to_human_readable, format_last_result, format_next_run,
+};
+use something_bg_core::tunnel::TunnelManager;
+
+use crate::paths::CliPaths;
+
+/// The command succeeded.
+const EXIT_OK: i32 = 0;
+/// The tunnel or task failed (non-zero exit, timeout, or could not start).
+const EXIT_FAILED: i32 = 1;
+/// Bad arguments or an unknown tunnel/task key.
+const EXIT_USAGE: i32 = 2;
+/// The config file could not be loaded, or `check` found problems.
+const EXIT_CONFIG: i32 = 3;
+
+const USAGE: &str = "\
+Usage: something_bg_cli <command>
+
+Commands:
+  list [--json]    Print tunnels and scheduled tasks
+  start <tunnel>   Run a tunnel in the foreground until interrupted
+  stop <tunnel>    Run a tunnel's stop command
+  test <tunnel>    Run a tunnel briefly and report whether it stayed up, with its stderr
+  run <task>       Run a scheduled task once and wait for it to finish
+  check            Validate the config and show each task's next run, without running anything
+
+Exit codes: 0 success, 1 the tunnel or task failed, 2 usage error, 3 config error.";
+
+#[derive(Debug, PartialEq, Eq)]
+enum CliCommand {
+    Help,
+    List { json: bool },
+    Start(String),
+    Stop(String),
+    Test(String),
+    Run(String),
+    Check,
+}
+
+fn parse_args(args: &[String]) -> Result<CliCommand, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] | ["help" | "-h" | "--help"] => Ok(CliCommand::Help),
+        ["list"] => Ok(CliCommand::List { json: false }),
+        ["list", "--json"] => Ok(CliCommand::List { json: true }),
+        ["start", key] => Ok(CliCommand::Start(key.to_string())),
+        ["stop", key] => Ok(CliCommand::Stop(key.to_string())),
+        ["test", key] => Ok(CliCommand::Test(key.to_string())),
+        ["run", key] => Ok(CliCommand::Run(key.to_string())),
+        ["check" | "--check"] => Ok(CliCommand::Check),
+        [command, ..] => Err(format!("Unrecognized arguments for '{command}'")),
+    }
+}
+
+fn main() {
+    // Warnings only by default, so `list --json` stays clean for pipes.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(EXIT_USAGE);
+        }
+    };
+    if command == CliCommand::Help {
+        println!("{USAGE}");
+        return;
+    }
+
+    let paths = CliPaths;
+    let config = match Config::load_with(&paths) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {e}");
+            process::exit(EXIT_CONFIG);
+        }
+    };
+    config.apply_time_format();
+
+    let code = match command {
+        CliCommand::Help => EXIT_OK,
+        CliCommand::List { json } => list(&config, &paths, json),
+        CliCommand::Start(key) => start_tunnel(&config, &key),
+        CliCommand::Stop(key) => stop_tunnel(&config, &key),
+        CliCommand::Test(key) => test_tunnel(&config, &key),
+        CliCommand::Run(key) => run_task(&config, &paths, &key),
+        CliCommand::Check => check(&config),
+    };
+    process::exit(code);
+}
+
+/// `list --json` output. Fields are only ever added, never renamed or removed.
+#[derive(Debug, Serialize)]
+struct Listing {
+    tunnels: Vec<TunnelEntry>,
+    tasks: Vec<TaskEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct TunnelEntry {
+    key: String,
+    name: String,
+    auto_start: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskEntry {
+    key: String,
+    name: String,
+    /// The `cron` or `interval` value as written in the config.
+    schedule: String,
+    description: String,
+    /// `false` while the task's schedule is paused.
+    enabled: bool,
+    last_run: Option<DateTime<Utc>>,
+    next_run: Option<DateTime<Utc>>,
+    last_exit_code: Option<i32>,
+    timed_out: bool,
+}
+
+fn list(config: &Config, paths: &CliPaths, json: bool) -> i32 {
+    let scheduler = load_scheduler(config, paths);
+    let listing = Listing {
+        tunnels: config
+            .tunnels
+            .iter()
+            .map(|(key, tunnel)| TunnelEntry {
+                key: key.clone(),
+                name: tunnel.name.clone(),
+                auto_start: tunnel.auto_start,
+            })
+            .collect(),
+        tasks: config
+            .schedules
+            .iter()
+            .filter_map(|(key, _)| Some((key, scheduler.get_task(key)?)))
+            .map(|(key, task)| TaskEntry {
+                key: key.clone(),
+                description: cron_to_human_readable(&task.cron_schedule, task.timezone.as_deref()),
+                schedule: task.cron_schedule,
+                name: task.name,
+                enabled: task.enabled,
+                last_run: task.last_run,
+                next_run: task.next_run,
+                last_exit_code: task.last_exit_code,
+                timed_out: task.last_timed_out,
+            })
+            .collect(),
+    };
+
+    if json {
+        match serde_json::to_string_pretty(&listing) {
+            Ok(out) => println!("{out}"),
+            Err(e) => {
+                eprintln!("Failed to serialize listing: {e}");
+                return EXIT_FAILED;
+            }
+        }
+        return EXIT_OK;
+    }
+
+    println!("Tunnels:");
+    for tunnel in &listing.tunnels {
+        let auto_start = if tunnel.auto_start {
+            " (auto start)"
+        } else {
+            ""
+        };
+        println!("  {}  {}{}", tunnel.key, tunnel.name, auto_start);
+    }
+    println!("Scheduled tasks:");
+    for entry in &listing.tasks {
+        let Some(task) = scheduler.get_task(&entry.key) else {
+            continue;
+        };
+        println!("  {}  {} - {}", entry.key, entry.name, entry.description);
+        println!("      Last run: {}", format_last_result(&task));
+        println!("      Next run: {}", format_next_run(&task));
+    }
+    EXIT_OK
+}
+
+/// Start the tunnel and stay in the foreground until it gives up or we are
+/// interrupted, stopping it on the way out.
+fn start_tunnel(config: &Config, key: &str) -> i32 {
+    if config.tunnel(key).is_none() {
+        eprintln!("Unknown tunnel '{key}'");
+        return EXIT_USAGE;
+    }
+    let manager = TunnelManager::new(config.to_tunnel_commands(), config.get_path());
+    let events = manager.events();
+
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || running.store(false, Ordering::SeqCst)) {
+            warn!("Failed to install signal handler: {e}");
+        }
+    }
+
+    if let Err(e) = manager.toggle(key, true) {
+        eprintln!("Tunnel '{key}' not started: {e}");
+        return EXIT_FAILED;
+    }
+    let mut code = EXIT_OK;
+    while running.load(Ordering::SeqCst) {
+        match events.recv_timeout(Duration::from_millis(200)) {
+            Ok(Event::TunnelStarted { .. }) => eprintln!("Tunnel '{key}' is running"),
+            Ok(Event::TunnelFailed { error, .. }) => {
+                eprintln!("Tunnel '{key}' failed: {error}");
+                code = EXIT_FAILED;
+                break;
+            }
+            _ => {}
+        }
+    }
+    manager.cleanup();
+    code
+}
+
+fn stop_tunnel(config: &Config, key: &str) -> i32 {
+    if config.tunnel(key).is_none() {
+        eprintln!("Unknown tunnel '{key}'");
+        return EXIT_USAGE;
+    }
+    // A fresh manager tracks no child, so this falls back to the configured stop command.
+    let manager = TunnelManager::new(config.to_tunnel_commands(), config.get_path());
+    let _ = manager.toggle(key, false);
+    EXIT_OK
+}
+
+/// Run the tunnel for up to the test timeout and print what it wrote to stderr.
+fn test_tunnel(config: &Config, key: &str) -> i32 {
+    if config.tunnel(key).is_none() {
+        eprintln!("Unknown tunnel '{key}'");
+        return EXIT_USAGE;
+    }
+    let manager = TunnelManager::new(config.to_tunnel_commands(), config.get_path());
+    match manager.test(key) {
+        Ok(report) => {
+            if !report.stderr.is_empty() {
+                eprintln!("{}", report.stderr);
+            }
+            let verdict = if report.passed() { "passed" } else { "failed" };
+            println!("Tunnel '{key}' test {verdict}: {}", report.summary());
+            if report.passed() {
+                EXIT_OK
+            } else {
+                EXIT_FAILED
+            }
+        }
+        Err(e) => {
+            eprintln!("Tunnel '{key}' not tested: {e}");
+            EXIT_FAILED
+        }
+    }
+}
+
+fn run_task(config: &Config, paths: &CliPaths, key: &str) -> i32 {
+    let scheduler = load_scheduler(config, paths);
+    if scheduler.get_task(key).is_none() {
+        eprintln!("Unknown scheduled task '{key}'");
+        return EXIT_USAGE;
+    }
+    match scheduler.run_task_now_blocking(key) {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("{e}");
+            EXIT_FAILED
+        }
+    }
+}
+
+/// Report what would run and every problem found; spawns nothing and writes no state.
+fn check(config: &Config) -> i32 {
+    let report = config.dry_run();
+    println!("PATH: {}", report.path);
+    println!("Next runs:");
+    for (key, next_run) in &report.next_runs {
+        let enabled = config
+            .schedules
+            .iter()
+            .any(|(id, task)| id == key && task.enabled);
+        let when = match next_run {
+            _ if !enabled => "Disabled".to_string(),
+            Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+            None => "At startup".to_string(),
+        };
+        println!("  {key}  {when}");
+    }
+    if report.problems.is_empty() {
+        println!("No problems found");
+        return EXIT_OK;
+    }
+    println!("Problems:");
+    for problem in &report.problems {
+        println!("  {problem}");
+    }
+    EXIT_CONFIG
+}
+
+/// A scheduler holding every valid task, with state from the shared state file,
+/// so a run recorded here shows up in the tray app after its next launch.
+fn load_scheduler(config: &Config, paths: &CliPaths) -> TaskScheduler {
+    let scheduler = TaskScheduler::new(config.get_path(), paths);
+    for (key, task) in &config.schedules {
+        if let Err(e) = scheduler.add_task(key.clone(), task) {
+            eprintln!("Skipping scheduled task '{key}': {e}");
+        }
+    }
+    scheduler
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse_args(&args(&[])), Ok(CliCommand::Help));
+        assert_eq!(
+            parse_args(&args(&["list", "--json"])),
+            Ok(CliCommand::List { json: true })
+        );
+        assert_eq!(
+            parse_args(&args(&["run", "backup"])),
+            Ok(CliCommand::Run("backup".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args(&["test", "db"])),
+            Ok(CliCommand::Test("db".to_string()))
+        );
+        assert_eq!(parse_args(&args(&["--check"])), Ok(CliCommand::Check));
+        assert!(parse_args(&args(&["run"])).is_err());
+        assert!(parse_args(&args(&["toggle", "a", "b"])).is_err());
+    }
+}
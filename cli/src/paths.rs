@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use something_bg_core::platform::{AppPaths, CONFIG_PATH_ENV, STATE_PATH_ENV, path_override};
+
+/// The same locations the tray app on this platform uses, so the CLI and the
+/// app share one config and one task state file.
+#[derive(Default)]
+pub struct CliPaths;
+
+impl AppPaths for CliPaths {
+    fn config_path(&self) -> PathBuf {
+        path_override(CONFIG_PATH_ENV, || config_dir().join("config.toml"))
+    }
+
+    fn state_path(&self) -> PathBuf {
+        path_override(STATE_PATH_ENV, || data_dir().join("task_state.toml"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> PathBuf {
+    mac_dir(&["config.toml", "config.json"])
+}
+
+#[cfg(not(target_os = "macos"))]
+fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("something_bg")
+}
+
+#[cfg(target_os = "macos")]
+fn data_dir() -> PathBuf {
+    mac_dir(&["task_state.toml"])
+}
+
+/// Application Support, like the tray app, unless `files` are still only in the
+/// old `~/.config/something_bg` because the app has not moved them yet.
+#[cfg(target_os = "macos")]
+fn mac_dir(files: &[&str]) -> PathBuf {
+    let support = dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("com.vim-zz.something-bg");
+    let legacy = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join("something_bg");
+    let found = |dir: &PathBuf| files.iter().any(|name| dir.join(name).exists());
+    if !found(&support) && found(&legacy) {
+        legacy
+    } else {
+        support
+    }
+}
+
+#[cfg(windows)]
+fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("something_bg")
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("something_bg")
+}
@@ -14,7 +14,7 @@ use std::process::{Command, Stdio};
 use std::sync::Arc;
 use std::thread;
 
-use crate::config::CommandConfig;
+use crate::config::{CommandConfig, command_not_found, expand_env_args};
 
 /// How to handle command output.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -142,7 +142,11 @@ impl CommandRunner {
 
     fn run_silent(&self, entry: &CommandEntry) -> Result<(), String> {
         let result = Command::new(&entry.command)
-            .args(&entry.args)
+            .args(expand_env_args(
+                &entry.args,
+                &self.env_path,
+                &HashMap::new(),
+            ))
             .env("PATH", &self.env_path)
             .stdin(Stdio::null())
             .stdout(Stdio::null())
@@ -160,21 +164,28 @@ impl CommandRunner {
                 debug!("Spawned silent command: {}", entry.name);
             }
             Err(e) => {
-                let msg = format!("Failed to spawn: {}", e);
+                let msg = format!(
+                    "Failed to spawn: {}",
+                    spawn_error(&entry.command, &self.env_path, e)
+                );
                 append_history(&self.history_path, &entry.name, false, &msg);
             }
         }
 
-        result
-            .map(|_| ())
-            .map_err(|e| format!("Failed to spawn '{}': {}", entry.command, e))
+        result.map(|_| ()).map_err(|e| {
+            format!(
+                "Failed to spawn '{}': {}",
+                entry.command,
+                spawn_error(&entry.command, &self.env_path, &e)
+            )
+        })
     }
 
     fn run_notify(&self, entry: &CommandEntry) -> Result<(), String> {
         let cb = self.notify_cb.clone();
         let name = entry.name.clone();
         let command = entry.command.clone();
-        let args = entry.args.clone();
+        let args = expand_env_args(&entry.args, &self.env_path, &HashMap::new());
         let env_path = self.env_path.clone();
         let history_path = self.history_path.clone();
 
@@ -256,9 +267,10 @@ impl CommandRunner {
                     }
                 }
                 Err(e) => {
-                    let msg = format!("Failed to execute: {}", e);
+                    let reason = spawn_error(&command, &env_path, &e);
+                    let msg = format!("Failed to execute: {}", reason);
                     append_history(&history_path, &name, false, &msg);
-                    error!("Failed to run command '{}': {}", name, e);
+                    error!("Failed to run command '{}': {}", name, reason);
                     if let Some(cb) = cb {
                         cb(&NotifyEvent {
                             name: &name,
@@ -277,7 +289,10 @@ impl CommandRunner {
 
     fn run_terminal(&self, entry: &CommandEntry) -> Result<(), String> {
         if let Some(cb) = &self.terminal_cb {
-            cb(&entry.command, &entry.args);
+            cb(
+                &entry.command,
+                &expand_env_args(&entry.args, &self.env_path, &HashMap::new()),
+            );
             append_history(
                 &self.history_path,
                 &entry.name,
@@ -291,6 +306,15 @@ impl CommandRunner {
     }
 }
 
+/// Describe a failed spawn, spelling out a program missing from `path`.
+fn spawn_error(command: &str, path: &str, error: &std::io::Error) -> String {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        command_not_found(command, path)
+    } else {
+        error.to_string()
+    }
+}
+
 /// Append a timestamped entry to the command history log.
 fn append_history(path: &Option<PathBuf>, name: &str, success: bool, output: &str) {
     let Some(path) = path else { return };
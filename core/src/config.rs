@@ -1,18 +1,235 @@
 //! Versioned configuration loading, migration, and runtime models.
 //! Uses injected `AppPaths` so platform shells control where files live.
 
-use log::{debug, info, warn};
+use chrono::{DateTime, FixedOffset, Local};
+use log::{LevelFilter, debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use crate::platform::AppPaths;
-use crate::tunnel::TunnelCommand;
+use crate::platform::{AppPaths, Notifier};
+use crate::scheduler::{ActiveHours, EVERY_PREFIX, ScheduledTask, TaskScheduler, startup_summary};
+use crate::tunnel::{HealthCheck, HealthProbe, ReadyCheck, TunnelCommand};
 
 pub const CURRENT_CONFIG_VERSION: u64 = 2;
 
+/// Seconds a tunnel gets to exit after SIGTERM before it is killed outright.
+pub const DEFAULT_KILL_TIMEOUT_SECS: u64 = 5;
+/// Restarts attempted before a failing tunnel is given up on.
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+/// Initial delay between tunnel restarts; doubles on each consecutive failure.
+pub const DEFAULT_RETRY_BACKOFF_SECS: u64 = 1;
+/// Longest the scheduler sleeps before re-checking, even when no task is due sooner.
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 300;
+/// Runs kept per scheduled task in its history.
+pub const DEFAULT_HISTORY_SIZE: usize = 20;
+/// Largest `jitter_secs` accepted for a scheduled task: one day.
+pub const MAX_JITTER_SECS: u64 = 86_400;
+/// Largest timeout accepted for a tunnel or task: one day. Anything longer is a typo.
+pub const MAX_TIMEOUT_SECS: u64 = 86_400;
+/// How long a tunnel's `ready_check` may take before the attempt counts as failed.
+pub const DEFAULT_READY_TIMEOUT_SECS: u64 = 10;
+/// Time between a running tunnel's `health_check` probes.
+pub const DEFAULT_HEALTH_INTERVAL_SECS: u64 = 30;
+/// How long one `health_check` probe may take before it counts as failed.
+pub const DEFAULT_HEALTH_TIMEOUT_SECS: u64 = 5;
+
+/// Errors returned by the public config API, split so shells can tell a
+/// missing/unreadable file apart from a malformed or invalid one.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Reading or writing the config file failed.
+    Io(std::io::Error),
+    /// The file is not valid TOML (or does not match the expected shape).
+    Parse { line: Option<usize>, msg: String },
+    /// The file parsed but its contents are not a usable configuration.
+    Validation(Vec<String>),
+}
+
+impl ConfigError {
+    fn validation(msg: impl Into<String>) -> Self {
+        Self::Validation(vec![msg.into()])
+    }
+
+    /// Build a parse error, resolving the byte span into a 1-based line number.
+    fn parse(content: &str, error: toml::de::Error) -> Self {
+        let line = error
+            .span()
+            .and_then(|span| content.get(..span.start))
+            .map(|before| before.matches('\n').count() + 1);
+        Self::Parse {
+            line,
+            msg: error.message().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to access config file: {e}"),
+            Self::Parse {
+                line: Some(line),
+                msg,
+            } => write!(f, "Config parse error at line {line}: {msg}"),
+            Self::Parse { line: None, msg } => write!(f, "Config parse error: {msg}"),
+            Self::Validation(errors) => write!(f, "{}", errors.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Parse {
+            line: None,
+            msg: error.message().to_string(),
+        }
+    }
+}
+
+impl From<toml::ser::Error> for ConfigError {
+    fn from(error: toml::ser::Error) -> Self {
+        Self::Io(std::io::Error::other(error))
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            return Self::Io(error.into());
+        }
+        Self::Parse {
+            line: (error.line() > 0).then_some(error.line()),
+            msg: error.to_string(),
+        }
+    }
+}
+
+/// The config file in use: `config.toml`, or a `config.json` beside it when only
+/// the JSON file exists. A fresh install always starts with TOML.
+pub fn config_file(paths: &dyn AppPaths) -> PathBuf {
+    let toml_path = paths.config_path();
+    let json_path = toml_path.with_extension("json");
+    if !toml_path.exists() && json_path.exists() {
+        json_path
+    } else {
+        toml_path
+    }
+}
+
+/// Replace `path` with `contents` so readers see either the old file or the new
+/// one, never a truncated mix: the data goes to a sibling temp file that is then
+/// renamed over `path`. The directory must already exist.
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("state");
+    // The pid keeps two instances, and the counter two threads of this one (e.g.
+    // tunnel threads saving their state at once), from writing the same temp file.
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let temp_path = path.with_file_name(format!(
+        ".{file_name}.{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = fs::File::create(&temp_path).and_then(|mut file| {
+        std::io::Write::write_all(&mut file, contents.as_ref())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Directory beside the config file whose `*.toml` files are merged into it.
+const FRAGMENT_DIR: &str = "conf.d";
+
+/// The `*.toml` files in the `conf.d` directory beside `config_path`, sorted by
+/// file name, each named for messages (e.g. `conf.d/10-db.toml`) with its contents.
+fn read_fragments(config_path: &Path) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+    let Some(dir) = config_path.parent().map(|parent| parent.join(FRAGMENT_DIR)) else {
+        return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        // Skips editor backups and half-written files like `.db.toml.swp`.
+        .filter(|name| name.ends_with(".toml") && !name.starts_with('.'))
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| Ok((format!("{FRAGMENT_DIR}/{name}"), fs::read(dir.join(&name))?)))
+        .collect()
+}
+
+/// The config file's contents followed by every fragment's name and contents, so
+/// [`ConfigMonitor`] notices a fragment being added, edited, or removed.
+fn snapshot(mut contents: Vec<u8>, fragments: &[(String, Vec<u8>)]) -> Vec<u8> {
+    for (name, fragment) in fragments {
+        contents.push(0);
+        contents.extend_from_slice(name.as_bytes());
+        contents.push(0);
+        contents.extend_from_slice(fragment);
+    }
+    contents
+}
+
+/// On-disk syntax of a config file, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    fn of(path: &Path) -> Self {
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::Json
+        } else {
+            Self::Toml
+        }
+    }
+
+    fn serialize(self, document: &V2Document) -> Result<String, ConfigError> {
+        match self {
+            Self::Toml => Ok(toml::to_string_pretty(document)?),
+            Self::Json => serde_json::to_string_pretty(document)
+                .map_err(|e| ConfigError::Io(std::io::Error::other(e))),
+        }
+    }
+}
+
 /// Tracks the exact config contents that were last applied by the app.
 pub struct ConfigMonitor {
     path: PathBuf,
@@ -27,9 +244,10 @@ impl ConfigMonitor {
         }
     }
 
+    /// Whether the config file or any `conf.d` fragment differs from what was applied.
     pub fn has_changed(&self) -> std::io::Result<bool> {
         let current = match fs::read(&self.path) {
-            Ok(contents) => Some(contents),
+            Ok(contents) => Some(snapshot(contents, &read_fragments(&self.path)?)),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
             Err(e) => return Err(e),
         };
@@ -42,7 +260,7 @@ impl ConfigMonitor {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum SectionKind {
     Tunnel,
@@ -50,10 +268,141 @@ pub enum SectionKind {
     ScheduledTask,
 }
 
+/// How the configured `[environment] path` combines with the PATH the app inherited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathMode {
+    /// Use the configured path alone.
+    #[default]
+    Replace,
+    /// Search the configured entries first, then the inherited ones.
+    Prepend,
+    /// Search the inherited entries first, then the configured ones.
+    Append,
+}
+
+impl PathMode {
+    fn is_replace(&self) -> bool {
+        *self == Self::Replace
+    }
+}
+
+/// How much the app logs, from the top-level `log_level`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn is_info(&self) -> bool {
+        *self == Self::Info
+    }
+
+    pub fn filter(self) -> LevelFilter {
+        match self {
+            Self::Off => LevelFilter::Off,
+            Self::Error => LevelFilter::Error,
+            Self::Warn => LevelFilter::Warn,
+            Self::Info => LevelFilter::Info,
+            Self::Debug => LevelFilter::Debug,
+            Self::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Clock used for times in menu labels, from the top-level `time_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    /// `"12h"`: "6:00 AM".
+    #[serde(rename = "12h")]
+    TwelveHour,
+    /// `"24h"`: "06:00".
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+/// Regions (the `US` in `en_US.UTF-8`) where the 12-hour clock is the norm.
+const TWELVE_HOUR_REGIONS: &[&str] = &["US", "CA", "AU", "NZ", "PH", "IN", "PK", "BD", "EG", "SA"];
+
+impl TimeFormat {
+    /// The `chrono` format for a time of day.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Self::TwelveHour => "%-I:%M %p",
+            Self::TwentyFourHour => "%H:%M",
+        }
+    }
+
+    /// The clock of the locale in `LC_ALL`, `LC_TIME`, or `LANG`, whichever is
+    /// set first; 24-hour when none is.
+    pub fn from_locale_env() -> Self {
+        Self::locale_env().unwrap_or(Self::TwentyFourHour)
+    }
+
+    /// The clock of the locale in `LC_ALL`, `LC_TIME`, or `LANG`, or `None` when
+    /// none is set, as for apps launched from the Dock or at login.
+    pub fn locale_env() -> Option<Self> {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .map(|locale| Self::from_locale(&locale))
+    }
+
+    /// The clock for a locale name like `en_US.UTF-8` or `en-US`.
+    pub fn from_locale(locale: &str) -> Self {
+        let name = locale.split(['.', '@']).next().unwrap_or_default();
+        let region = name.split(['_', '-']).nth(1).unwrap_or_default();
+        if TWELVE_HOUR_REGIONS.contains(&region.to_ascii_uppercase().as_str()) {
+            Self::TwelveHour
+        } else {
+            Self::TwentyFourHour
+        }
+    }
+}
+
+/// A section header icon, parsed from the config's `icon` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IconSpec {
+    /// `sf:clock.fill`: an SF Symbol. Only macOS has them; other shells show no icon.
+    Symbol(String),
+    /// `file:~/icons/db.png`: an image file shown in its own colors.
+    File(PathBuf),
+    /// `template:~/icons/db.png`: an image file tinted to match the menu, like SF Symbols.
+    Template(PathBuf),
+}
+
+impl IconSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (scheme, value) = spec.split_once(':').unwrap_or(("", spec));
+        if value.trim().is_empty() && !scheme.is_empty() {
+            return Err(format!(
+                "icon '{spec}' is missing a name or path after '{scheme}:'"
+            ));
+        }
+        match scheme {
+            "sf" => Ok(Self::Symbol(value.to_string())),
+            "file" => Ok(Self::File(PathBuf::from(expand_tilde(value)))),
+            "template" => Ok(Self::Template(PathBuf::from(expand_tilde(value)))),
+            _ => Err(format!(
+                "icon '{spec}' is not supported; use 'sf:symbol.name', 'file:/path/to/icon.png', or 'template:/path/to/icon.png'"
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfigSection {
     pub id: String,
     pub title: Option<String>,
+    /// Header icon as written in the config; see [`IconSpec`].
     pub icon: Option<String>,
     pub kind: SectionKind,
     pub item_ids: Vec<String>,
@@ -66,6 +415,47 @@ pub struct TunnelConfig {
     pub args: Vec<String>,
     pub kill_command: String,
     pub kill_args: Vec<String>,
+    pub kill_timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub retry_backoff_secs: Option<u64>,
+    pub working_dir: Option<String>,
+    pub env: HashMap<String, String>,
+    /// Run `start`/`stop` through the platform shell (`sh -c` / `cmd /C`).
+    pub shell: bool,
+    /// Start this tunnel every time the app launches.
+    pub auto_start: bool,
+    /// Only report the tunnel as running once this address accepts connections.
+    pub ready_check: Option<ReadyCheckConfig>,
+    /// Restart the tunnel when this probe fails while it is running.
+    pub health_check: Option<HealthCheckConfig>,
+    /// Seconds between `health_check` probes while the tunnel runs.
+    pub health_interval_secs: Option<u64>,
+    /// Wait this long before the first attempt when started at launch.
+    pub start_delay_secs: Option<u64>,
+    /// Tunnels that must be running (and ready) before this one starts.
+    pub depends_on: Vec<String>,
+}
+
+/// A tunnel's `ready_check`, e.g. `{ tcp = "localhost:5432", timeout_secs = 10 }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyCheckConfig {
+    pub tcp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A tunnel's `health_check`: either `{ tcp = "localhost:5432" }` or
+/// `{ command = "curl", args = ["-sf", "http://localhost:8080/health"] }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +464,33 @@ pub struct ScheduledTaskConfig {
     pub command: String,
     pub args: Vec<String>,
     pub cron_schedule: String,
+    /// IANA timezone name (e.g. `America/New_York`) for the cron fields.
+    pub timezone: Option<String>,
+    pub env: HashMap<String, String>,
+    /// Run `run` through the platform shell (`sh -c` / `cmd /C`).
+    pub shell: bool,
+    pub wait_for_completion: bool,
+    pub timeout_secs: Option<u64>,
+    /// Delay each run by a random `0..=jitter_secs` seconds, so tasks sharing a
+    /// schedule across machines don't all fire at once.
+    pub jitter_secs: Option<u64>,
+    /// Due runs before `active_from`, after `active_until`, or outside the daily
+    /// `active_hours` (in `timezone`) are skipped.
+    pub active_from: Option<DateTime<FixedOffset>>,
+    pub active_until: Option<DateTime<FixedOffset>>,
+    pub active_hours: Option<ActiveHours>,
+    pub run_on_startup: bool,
+    /// PATH for this task only; the global `[environment] path` applies when unset.
+    pub path: Option<String>,
+    /// Post a notification when a run succeeds, not only when it fails.
+    pub notify_on_complete: bool,
+    /// Scheduled-task ids to run once this task succeeds, in order; implies waiting
+    /// for completion.
+    pub then: Vec<String>,
+    /// Run `then` after failed runs too.
+    pub then_on_failure: bool,
+    /// Run on schedule; disabled tasks stay in the menu and can still be run by hand.
+    pub enabled: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -84,6 +501,17 @@ pub struct CommandConfig {
     pub output: Option<String>,
 }
 
+/// What [`Config::dry_run`] found.
+#[derive(Debug, Clone)]
+pub struct DryRun {
+    /// PATH used by every entry that does not set its own.
+    pub path: String,
+    /// Each valid task's next run in config order; `None` for `@reboot` tasks.
+    pub next_runs: Vec<(String, Option<DateTime<Local>>)>,
+    /// Everything that would fail at spawn or schedule time; empty when all is well.
+    pub problems: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct Config {
     pub sections: Vec<ConfigSection>,
@@ -93,14 +521,126 @@ pub struct Config {
     pub scripts_dir: Option<String>,
     pub scripts_output: Option<String>,
     pub path: Option<String>,
+    /// How `path` combines with the inherited PATH; see `get_path()`.
+    pub path_mode: PathMode,
+    pub poll_interval_secs: Option<u64>,
+    /// Runs kept per scheduled task; see `history_size()`.
+    pub history_size: Option<usize>,
+    /// Re-enable the tunnels that were active when the app last quit.
+    pub auto_restore_tunnels: bool,
+    /// Start a tunnel's `depends_on` tunnels with it, instead of refusing to
+    /// start it while they are off.
+    pub start_tunnel_dependencies: bool,
+    /// Tunnels allowed to be starting at once; `0` means no limit. See
+    /// `TunnelManager::set_max_concurrent_starts`.
+    pub max_concurrent_starts: usize,
+    /// Stop the active tunnels when the app quits; when `false` they are left
+    /// running, untracked. See `TunnelManager::set_kill_on_quit`.
+    pub kill_tunnels_on_quit: bool,
+    /// Post a summary of what was loaded when the app starts.
+    pub startup_notification: bool,
+    /// Base for `{port_base}` / `{port_base+N}` in tunnel arguments; see
+    /// [`expand_port_template`].
+    pub port_base: Option<u16>,
+    /// Most detailed log messages kept; see `apply_log_level()`.
+    pub log_level: LogLevel,
+    /// Clock for times in labels; `None` follows the locale. See `apply_time_format()`.
+    pub time_format: Option<TimeFormat>,
+    /// Wait before bringing up tunnels and tasks at launch; see `startup_delay()`.
+    pub startup_delay_secs: Option<u64>,
     scripts_section: Option<String>,
     discovered_command_ids: HashSet<String>,
+    /// Items marked `hidden = true`, kept only to be saved back: section id, position
+    /// among the section's items, and the item as written.
+    hidden_items: Vec<(String, usize, ItemDocument)>,
+    /// What the `conf.d` fragments added or replaced, left out when saving.
+    fragments: MergedFragments,
+}
+
+/// What `conf.d` fragments changed in the document they were merged into, so a
+/// save writes back only what came from the config file itself.
+#[derive(Debug, Default)]
+struct MergedFragments {
+    /// Sections only fragments declare.
+    sections: HashSet<String>,
+    /// Items only fragments declare.
+    items: HashSet<(SectionKind, String)>,
+    /// Items of the config file that a fragment replaced, as the file has them.
+    replaced: HashMap<(SectionKind, String), ItemDocument>,
+    /// Items declared by more than one fragment; reported by `validate()`.
+    duplicates: Vec<String>,
+}
+
+impl MergedFragments {
+    /// Undo the merge in a document built from the merged config.
+    fn unmerge(&self, document: &mut V2Document) {
+        document
+            .sections
+            .retain(|section| !self.sections.contains(&section.id));
+        for section in &mut document.sections {
+            let kind = section.kind;
+            section
+                .items
+                .retain(|item| !self.items.contains(&(kind, item.id.clone())));
+            for item in &mut section.items {
+                if let Some(original) = self.replaced.get(&(kind, item.id.clone())) {
+                    *item = original.clone();
+                }
+            }
+        }
+    }
+}
+
+/// A `conf.d` fragment: only sections; other settings come from the config file.
+#[derive(Debug, Deserialize)]
+struct FragmentDocument {
+    #[serde(default)]
+    sections: Vec<SectionDocument>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct EnvironmentDocument {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     path: Option<String>,
+    #[serde(default, skip_serializing_if = "PathMode::is_replace")]
+    path_mode: PathMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct SchedulerDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    poll_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history_size: Option<usize>,
+}
+
+impl SchedulerDocument {
+    fn is_empty(&self) -> bool {
+        self.poll_interval_secs.is_none() && self.history_size.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct TunnelsDocument {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    auto_restore: bool,
+    /// Defaults to `true`; only written when turned off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_dependencies: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrent_starts: Option<usize>,
+    /// Defaults to `true`; only written when turned off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kill_on_quit: Option<bool>,
+}
+
+impl TunnelsDocument {
+    fn is_empty(&self) -> bool {
+        !self.auto_restore
+            && self.start_dependencies.is_none()
+            && self.max_concurrent_starts.is_none()
+            && self.kill_on_quit.is_none()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,8 +655,22 @@ struct ScriptsDocument {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct V2Document {
     version: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    startup_notification: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port_base: Option<u16>,
+    #[serde(default, skip_serializing_if = "LogLevel::is_info")]
+    log_level: LogLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_format: Option<TimeFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    startup_delay_secs: Option<u64>,
     #[serde(default)]
     environment: EnvironmentDocument,
+    #[serde(default, skip_serializing_if = "SchedulerDocument::is_empty")]
+    scheduler: SchedulerDocument,
+    #[serde(default, skip_serializing_if = "TunnelsDocument::is_empty")]
+    tunnels: TunnelsDocument,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     scripts: Option<ScriptsDocument>,
     #[serde(default)]
@@ -135,20 +689,94 @@ struct SectionDocument {
     items: Vec<ItemDocument>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct ItemDocument {
     id: String,
     name: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    start: Option<Vec<String>>,
+    start: Option<ActionDocument>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    stop: Option<Vec<String>>,
+    stop: Option<ActionDocument>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    run: Option<Vec<String>>,
+    run: Option<ActionDocument>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     cron: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kill_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_retries: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_backoff_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    working_dir: Option<String>,
+    /// Sorted so saved configs are stable.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    wait_for_completion: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jitter_secs: Option<u64>,
+    /// RFC 3339 timestamps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_until: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_hours: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    run_on_startup: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    shell: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    auto_start: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ready_check: Option<ReadyCheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health_check: Option<HealthCheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_delay_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    notify_on_complete: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    then: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    then_on_failure: bool,
+    /// Only written when `false`; tasks are enabled by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    /// Keep the entry in the file but leave it out of the menu, the tunnel
+    /// manager, and the scheduler, as if it were commented out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+}
+
+/// A `start`/`stop`/`run` value: an argv array, or a single command line that is
+/// split into one with [`split_command_line`]. Always saved back as an array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ActionDocument {
+    Argv(Vec<String>),
+    Line(String),
+}
+
+impl From<Vec<String>> for ActionDocument {
+    fn from(argv: Vec<String>) -> Self {
+        Self::Argv(argv)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -197,14 +825,13 @@ struct LegacyCommandConfig {
 }
 
 impl Config {
-    pub fn load_with(paths: &dyn AppPaths) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_with(paths: &dyn AppPaths) -> Result<Self, ConfigError> {
         Self::load_with_snapshot(paths).map(|(config, _)| config)
     }
 
-    pub fn load_with_snapshot(
-        paths: &dyn AppPaths,
-    ) -> Result<(Self, Vec<u8>), Box<dyn std::error::Error>> {
-        let config_path = paths.config_path();
+    pub fn load_with_snapshot(paths: &dyn AppPaths) -> Result<(Self, Vec<u8>), ConfigError> {
+        let config_path = config_file(paths);
+        let format = ConfigFormat::of(&config_path);
 
         if !config_path.exists() {
             info!(
@@ -216,15 +843,25 @@ impl Config {
 
         debug!("Loading config from {:?}", config_path);
         let original_contents = fs::read(&config_path)?;
-        let content = std::str::from_utf8(&original_contents)?;
-        let value: toml::Value = content.parse()?;
+        let fragments = read_fragments(&config_path)?;
+        let content = std::str::from_utf8(&original_contents).map_err(|e| ConfigError::Parse {
+            line: None,
+            msg: e.to_string(),
+        })?;
+        // JSON is read into the same value tree, so both formats share one pipeline.
+        let value: toml::Value = match format {
+            ConfigFormat::Toml => content
+                .parse()
+                .map_err(|e| ConfigError::parse(content, e))?,
+            ConfigFormat::Json => serde_json::from_str(content)?,
+        };
         let version = declared_version(&value)?;
 
         let (config, applied_contents) = match version {
             1 => {
                 let document = migrate_v1_to_v2(value)?;
-                let config = Self::from_v2_document(document.clone())?;
-                let migrated = toml::to_string_pretty(&document)?.into_bytes();
+                let migrated = format.serialize(&document)?.into_bytes();
+                let config = Self::from_merged_document(document, &fragments)?;
                 persist_migration(&config_path, &original_contents, &migrated, 1)?;
                 info!(
                     "Migrated configuration from v1 to v{}; backup saved next to config",
@@ -233,14 +870,21 @@ impl Config {
                 (config, migrated)
             }
             CURRENT_CONFIG_VERSION => {
-                let document: V2Document = value.try_into()?;
-                (Self::from_v2_document(document)?, original_contents)
+                let document: V2Document = match format {
+                    ConfigFormat::Toml => {
+                        toml::from_str(content).map_err(|e| ConfigError::parse(content, e))?
+                    }
+                    ConfigFormat::Json => serde_json::from_str(content)?,
+                };
+                (
+                    Self::from_merged_document(document, &fragments)?,
+                    original_contents,
+                )
             }
             other => {
-                return Err(format!(
+                return Err(ConfigError::validation(format!(
                     "Unsupported config version {other}; this app supports up to version {CURRENT_CONFIG_VERSION}"
-                )
-                .into());
+                )));
             }
         };
 
@@ -250,16 +894,47 @@ impl Config {
             config.commands.len(),
             config.schedules.len()
         );
-        Ok((config, applied_contents))
+        if !fragments.is_empty() {
+            info!(
+                "Merged {} config fragments from {FRAGMENT_DIR}",
+                fragments.len()
+            );
+        }
+        Ok((config, snapshot(applied_contents, &fragments)))
     }
 
-    pub fn save_with(&self, paths: &dyn AppPaths) -> Result<(), Box<dyn std::error::Error>> {
-        let config_path = paths.config_path();
+    /// [`Config::from_v2_document`] after merging the `conf.d` fragments into `document`.
+    fn from_merged_document(
+        mut document: V2Document,
+        fragments: &[(String, Vec<u8>)],
+    ) -> Result<Self, ConfigError> {
+        let merged = merge_fragments(&mut document, fragments)?;
+        let mut config = Self::from_v2_document(document)?;
+        config.fragments = merged;
+        Ok(config)
+    }
+
+    /// Write the config in the format of the file currently in use (see [`config_file`]).
+    /// The previous file is kept as `<name>.bak`, and the new one is renamed into
+    /// place so an interrupted save never leaves a truncated config behind. What
+    /// came from `conf.d` fragments stays in the fragments.
+    pub fn save_with(&self, paths: &dyn AppPaths) -> Result<(), ConfigError> {
+        let config_path = config_file(paths);
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        let content = toml::to_string_pretty(&self.to_v2_document())?;
-        fs::write(&config_path, content)?;
+        let content = ConfigFormat::of(&config_path).serialize(&self.to_v2_document())?;
+        if config_path.exists() {
+            let file_name = config_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("config.toml");
+            fs::copy(
+                &config_path,
+                config_path.with_file_name(format!("{file_name}.bak")),
+            )?;
+        }
+        write_atomic(&config_path, content)?;
         info!(
             "Saved v{} config to {:?}",
             CURRENT_CONFIG_VERSION, config_path
@@ -267,7 +942,35 @@ impl Config {
         Ok(())
     }
 
+    /// Why each port template in tunnel `id`'s arguments and check addresses
+    /// cannot be filled in.
+    fn port_template_errors(&self, id: &str, tunnel: &TunnelConfig) -> Vec<String> {
+        let health = tunnel.health_check.as_ref();
+        let templated = tunnel
+            .args
+            .iter()
+            .chain(&tunnel.kill_args)
+            .chain(health.into_iter().flat_map(|check| &check.args));
+        let addresses = tunnel
+            .ready_check
+            .as_ref()
+            .map(|check| &check.tcp)
+            .into_iter()
+            .chain(health.and_then(|check| check.tcp.as_ref()));
+        templated
+            .chain(addresses)
+            .filter_map(|value| expand_port_template(value, self.port_base).err())
+            .map(|e| format!("Tunnel '{id}' has an invalid port template: {e}"))
+            .collect()
+    }
+
+    /// The runtime form of every tunnel, with port templates filled in. A tunnel
+    /// with a malformed template keeps it as written and is marked `invalid`, so
+    /// it refuses to start; `validate` reports it as well.
     pub fn to_tunnel_commands(&self) -> HashMap<String, TunnelCommand> {
+        let template = |value: &String| {
+            expand_port_template(value, self.port_base).unwrap_or_else(|_| value.clone())
+        };
         self.tunnels
             .iter()
             .map(|(key, config)| {
@@ -275,42 +978,415 @@ impl Config {
                     key.clone(),
                     TunnelCommand {
                         command: config.command.clone(),
-                        args: config.args.clone(),
+                        args: config.args.iter().map(template).collect(),
                         kill_command: config.kill_command.clone(),
-                        kill_args: config.kill_args.clone(),
+                        kill_args: config.kill_args.iter().map(template).collect(),
+                        kill_timeout: Duration::from_secs(
+                            config
+                                .kill_timeout_secs
+                                .unwrap_or(DEFAULT_KILL_TIMEOUT_SECS),
+                        ),
+                        max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+                        retry_backoff: Duration::from_secs(
+                            config
+                                .retry_backoff_secs
+                                .unwrap_or(DEFAULT_RETRY_BACKOFF_SECS),
+                        ),
+                        working_dir: config
+                            .working_dir
+                            .as_deref()
+                            .map(|dir| PathBuf::from(expand_tilde(dir))),
+                        env: config.env.clone(),
+                        shell: config.shell,
+                        ready_check: config.ready_check.as_ref().map(|check| ReadyCheck {
+                            address: template(&check.tcp),
+                            timeout: Duration::from_secs(
+                                check.timeout_secs.unwrap_or(DEFAULT_READY_TIMEOUT_SECS),
+                            ),
+                        }),
+                        health_check: config.health_check.as_ref().and_then(|check| {
+                            let probe = match (&check.tcp, &check.command) {
+                                (Some(address), _) => HealthProbe::Tcp(template(address)),
+                                (None, Some(command)) => HealthProbe::Command {
+                                    command: command.clone(),
+                                    args: check.args.iter().map(template).collect(),
+                                },
+                                (None, None) => return None,
+                            };
+                            Some(HealthCheck {
+                                probe,
+                                interval: Duration::from_secs(
+                                    config
+                                        .health_interval_secs
+                                        .unwrap_or(DEFAULT_HEALTH_INTERVAL_SECS)
+                                        .max(1),
+                                ),
+                                // `validate` reports a zero timeout, which would fail every probe.
+                                timeout: Duration::from_secs(
+                                    check
+                                        .timeout_secs
+                                        .unwrap_or(DEFAULT_HEALTH_TIMEOUT_SECS)
+                                        .max(1),
+                                ),
+                            })
+                        }),
+                        start_delay: Duration::from_secs(config.start_delay_secs.unwrap_or(0)),
+                        depends_on: config.depends_on.clone(),
+                        start_dependencies: self.start_tunnel_dependencies,
+                        invalid: self.port_template_errors(key, config).into_iter().next(),
                     },
                 )
             })
             .collect()
     }
 
-    pub fn get_path(&self) -> String {
-        self.path
-            .clone()
-            .unwrap_or_else(|| std::env::var("PATH").unwrap_or_default())
-    }
+    /// Check the loaded entries for problems that would only surface later, at
+    /// spawn or schedule time. Every problem is returned, not just the first, so a
+    /// user can fix the whole file in one pass.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        // Duplicate ids never get this far: loading refuses the whole file.
+        for section in &self.sections {
+            match section.icon.as_deref().map(IconSpec::parse) {
+                Some(Err(e)) => errors.push(format!("Section '{}' {e}", section.id)),
+                Some(Ok(IconSpec::File(path) | IconSpec::Template(path))) if !path.is_file() => {
+                    errors.push(format!(
+                        "Section '{}' icon file {} does not exist",
+                        section.id,
+                        path.display()
+                    ));
+                }
+                _ => {}
+            }
+        }
 
-    pub fn tunnel(&self, id: &str) -> Option<&TunnelConfig> {
-        self.tunnels
-            .iter()
-            .find_map(|(key, config)| (key == id).then_some(config))
-    }
+        for (id, tunnel) in &self.tunnels {
+            if tunnel.command.trim().is_empty() {
+                errors.push(format!("Tunnel '{id}' has an empty 'start' command"));
+            }
+            if tunnel.kill_command.trim().is_empty() {
+                errors.push(format!("Tunnel '{id}' has an empty 'stop' command"));
+            }
+            let health = tunnel.health_check.as_ref();
+            errors.extend(self.port_template_errors(id, tunnel));
+            if let Some(check) = &tunnel.ready_check
+                && let Ok(address) = expand_port_template(&check.tcp, self.port_base)
+                && !is_host_port(&address)
+            {
+                errors.push(format!(
+                    "Tunnel '{id}' ready_check address '{}' must be host:port",
+                    check.tcp
+                ));
+            }
+            if let Some(check) = health {
+                match (&check.tcp, &check.command) {
+                    (Some(_), Some(_)) | (None, None) => errors.push(format!(
+                        "Tunnel '{id}' health_check needs exactly one of 'tcp' or 'command'"
+                    )),
+                    (Some(tcp), None) => {
+                        if let Ok(address) = expand_port_template(tcp, self.port_base)
+                            && !is_host_port(&address)
+                        {
+                            errors.push(format!(
+                                "Tunnel '{id}' health_check address '{tcp}' must be host:port"
+                            ));
+                        }
+                    }
+                    (None, Some(command)) if command.trim().is_empty() => {
+                        errors.push(format!("Tunnel '{id}' has an empty health_check command"))
+                    }
+                    (None, Some(_)) => {}
+                }
+            }
+            let too_long = |field: &str| {
+                format!("Tunnel '{id}' {field} must be at most {MAX_TIMEOUT_SECS} (one day)")
+            };
+            if tunnel
+                .ready_check
+                .as_ref()
+                .and_then(|check| check.timeout_secs)
+                .is_some_and(|timeout| timeout > MAX_TIMEOUT_SECS)
+            {
+                errors.push(too_long("ready_check timeout_secs"));
+            }
+            if tunnel
+                .kill_timeout_secs
+                .is_some_and(|timeout| timeout > MAX_TIMEOUT_SECS)
+            {
+                errors.push(too_long("kill_timeout_secs"));
+            }
+            match tunnel.health_interval_secs {
+                Some(0) => errors.push(format!(
+                    "Tunnel '{id}' health_interval_secs must be at least 1"
+                )),
+                Some(interval) if interval > MAX_TIMEOUT_SECS => {
+                    errors.push(too_long("health_interval_secs"))
+                }
+                _ => {}
+            }
+            match health.and_then(|check| check.timeout_secs) {
+                Some(0) => errors.push(format!(
+                    "Tunnel '{id}' health_check timeout_secs must be at least 1"
+                )),
+                Some(timeout) if timeout > MAX_TIMEOUT_SECS => {
+                    errors.push(too_long("health_check timeout_secs"))
+                }
+                _ => {}
+            }
+            for dependency in &tunnel.depends_on {
+                if self.is_hidden(SectionKind::Tunnel, dependency) {
+                    errors.push(format!(
+                        "Tunnel '{id}' depends on hidden tunnel '{dependency}'"
+                    ));
+                } else if self.tunnel(dependency).is_none() {
+                    errors.push(format!(
+                        "Tunnel '{id}' depends on unknown tunnel '{dependency}'"
+                    ));
+                }
+            }
+            let depends_on = |id: &str| self.tunnel(id).map(|tunnel| tunnel.depends_on.as_slice());
+            if let Some(cycle) = find_cycle(id, depends_on) {
+                errors.push(format!(
+                    "Tunnel '{id}' depends on itself: {}",
+                    cycle.join(" -> ")
+                ));
+            }
+        }
 
-    pub fn command(&self, id: &str) -> Option<&CommandConfig> {
-        self.commands
-            .iter()
-            .find_map(|(key, config)| (key == id).then_some(config))
+        for (id, command) in &self.commands {
+            if command.command.trim().is_empty() {
+                errors.push(format!("Command '{id}' has an empty 'run' command"));
+            }
+        }
+
+        for (id, task) in &self.schedules {
+            if task.command.trim().is_empty() {
+                errors.push(format!("Scheduled task '{id}' has an empty 'run' command"));
+            }
+            if let Err(e) =
+                crate::scheduler::validate_schedule(&task.cron_schedule, task.timezone.as_deref())
+            {
+                errors.push(format!("Scheduled task '{id}': {e}"));
+            }
+            let too_long = |field: &str, max: u64| {
+                format!("Scheduled task '{id}' {field} must be at most {max} (one day)")
+            };
+            if task
+                .jitter_secs
+                .is_some_and(|jitter| jitter > MAX_JITTER_SECS)
+            {
+                errors.push(too_long("jitter_secs", MAX_JITTER_SECS));
+            }
+            if task
+                .timeout_secs
+                .is_some_and(|timeout| timeout > MAX_TIMEOUT_SECS)
+            {
+                errors.push(too_long("timeout_secs", MAX_TIMEOUT_SECS));
+            }
+            for next in &task.then {
+                if self.is_hidden(SectionKind::ScheduledTask, next) {
+                    errors.push(format!(
+                        "Scheduled task '{id}' chains to hidden task '{next}'"
+                    ));
+                } else if self.schedule(next).is_none() {
+                    errors.push(format!(
+                        "Scheduled task '{id}' chains to unknown task '{next}'"
+                    ));
+                }
+            }
+            let then = |id: &str| self.schedule(id).map(|task| task.then.as_slice());
+            if let Some(cycle) = find_cycle(id, then) {
+                errors.push(format!(
+                    "Scheduled task '{id}' chains back to itself: {}",
+                    cycle.join(" -> ")
+                ));
+            }
+        }
+
+        errors.extend(self.fragments.duplicates.iter().cloned());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into_iter().map(ConfigError::validation).collect())
+        }
     }
 
-    pub fn schedule(&self, id: &str) -> Option<&ScheduledTaskConfig> {
-        self.schedules
-            .iter()
-            .find_map(|(key, config)| (key == id).then_some(config))
+    /// With `startup_notification = true`, post what was loaded, as a check that
+    /// the config was read as intended. Call once the scheduler holds its tasks.
+    pub fn report_startup(&self, scheduler: &TaskScheduler, notifier: &dyn Notifier) {
+        if self.startup_notification {
+            let summary = startup_summary(self.tunnels.len(), scheduler);
+            notifier.info("Something in the Background is running", &summary);
+        }
     }
 
-    fn from_v2_document(document: V2Document) -> Result<Self, Box<dyn std::error::Error>> {
-        if document.version != CURRENT_CONFIG_VERSION {
-            return Err(format!("Expected config version {CURRENT_CONFIG_VERSION}").into());
+    /// Run [`Config::validate`] and show any problems as a single warning.
+    pub fn report_problems(&self, notifier: &dyn Notifier) {
+        if let Err(errors) = self.validate() {
+            let details: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            let title = match details.len() {
+                1 => "Config has a problem".to_string(),
+                count => format!("Config has {count} problems"),
+            };
+            notifier.warn(&title, &details.join("\n"));
+        }
+    }
+
+    /// Check the config the way it would run, without spawning anything or touching
+    /// saved task state: validation, whether each program is on its PATH, and when
+    /// every task would next run if it had never run before.
+    pub fn dry_run(&self) -> DryRun {
+        let path = self.get_path();
+        let mut problems: Vec<String> = match self.validate() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        };
+
+        let mut check = |what: String, program: &str, shell: bool, path: &str| {
+            if !shell && !program.trim().is_empty() && !program_exists(program, path) {
+                problems.push(format!("{what}: command '{program}' not found on PATH"));
+            }
+        };
+        for (id, tunnel) in &self.tunnels {
+            check(
+                format!("Tunnel '{id}'"),
+                &tunnel.command,
+                tunnel.shell,
+                &path,
+            );
+            check(
+                format!("Tunnel '{id}' stop"),
+                &tunnel.kill_command,
+                tunnel.shell,
+                &path,
+            );
+        }
+        for (id, command) in &self.commands {
+            check(format!("Command '{id}'"), &command.command, false, &path);
+        }
+        for (id, task) in &self.schedules {
+            let task_path = task.path.as_deref().unwrap_or(&path);
+            check(
+                format!("Scheduled task '{id}'"),
+                &task.command,
+                task.shell,
+                task_path,
+            );
+        }
+
+        // Invalid schedules are already among the validation problems.
+        let next_runs = self
+            .schedules
+            .iter()
+            .filter_map(|(id, config)| {
+                let task = ScheduledTask::from_config(config).ok()?;
+                Some((id.clone(), task.upcoming(1).into_iter().next()))
+            })
+            .collect();
+
+        DryRun {
+            path,
+            next_runs,
+            problems,
+        }
+    }
+
+    /// Apply `log_level` to the global logger. `RUST_LOG`, when set, wins, so the
+    /// shells' loggers must let every level through and leave filtering to this.
+    pub fn apply_log_level(&self) {
+        if std::env::var_os("RUST_LOG").is_none() {
+            log::set_max_level(self.log_level.filter());
+        }
+    }
+
+    /// Apply `time_format` to the scheduler's label helpers, falling back to the
+    /// locale's clock when it is not set.
+    pub fn apply_time_format(&self) {
+        self.apply_time_format_with(TimeFormat::from_locale_env);
+    }
+
+    /// `apply_time_format` for shells that know the system clock setting better
+    /// than the locale variables; `system` is only asked when `time_format` is unset.
+    pub fn apply_time_format_with(&self, system: impl FnOnce() -> TimeFormat) {
+        crate::scheduler::set_time_format(self.time_format.unwrap_or_else(system));
+    }
+
+    /// PATH for tunnels, commands, and scheduled tasks: the configured `path`
+    /// combined with the app's own PATH according to `path_mode`.
+    pub fn get_path(&self) -> String {
+        let inherited = std::env::var("PATH").unwrap_or_default();
+        match &self.path {
+            Some(path) => combine_path(path, self.path_mode, &inherited),
+            None => inherited,
+        }
+    }
+
+    /// Interval between scheduler checks; never shorter than one second.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(
+            self.poll_interval_secs
+                .unwrap_or(DEFAULT_POLL_INTERVAL_SECS)
+                .max(1),
+        )
+    }
+
+    /// How long the shells wait after launch before restoring and auto-starting
+    /// tunnels and catching up on missed tasks, so the network can come up first.
+    pub fn startup_delay(&self) -> Duration {
+        Duration::from_secs(self.startup_delay_secs.unwrap_or(0))
+    }
+
+    /// Runs to keep in each scheduled task's history.
+    pub fn history_size(&self) -> usize {
+        self.history_size.unwrap_or(DEFAULT_HISTORY_SIZE)
+    }
+
+    /// Keys of tunnels marked `auto_start`, in menu order.
+    pub fn auto_start_tunnels(&self) -> Vec<String> {
+        self.tunnels
+            .iter()
+            .filter(|(_, config)| config.auto_start)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    pub fn tunnel(&self, id: &str) -> Option<&TunnelConfig> {
+        self.tunnels
+            .iter()
+            .find_map(|(key, config)| (key == id).then_some(config))
+    }
+
+    pub fn command(&self, id: &str) -> Option<&CommandConfig> {
+        self.commands
+            .iter()
+            .find_map(|(key, config)| (key == id).then_some(config))
+    }
+
+    pub fn schedule(&self, id: &str) -> Option<&ScheduledTaskConfig> {
+        self.schedules
+            .iter()
+            .find_map(|(key, config)| (key == id).then_some(config))
+    }
+
+    /// Whether `id` is an entry of `kind` marked `hidden = true`; those are left
+    /// out of `tunnels`, `commands`, `schedules`, and the sections' `item_ids`.
+    pub fn is_hidden(&self, kind: SectionKind, id: &str) -> bool {
+        self.hidden_items.iter().any(|(section_id, _, item)| {
+            item.id == id
+                && self
+                    .sections
+                    .iter()
+                    .any(|section| section.id == *section_id && section.kind == kind)
+        })
+    }
+
+    fn from_v2_document(document: V2Document) -> Result<Self, ConfigError> {
+        if document.version != CURRENT_CONFIG_VERSION {
+            return Err(ConfigError::validation(format!(
+                "Expected config version {CURRENT_CONFIG_VERSION}"
+            )));
         }
 
         let scripts_dir = document.scripts.as_ref().map(|s| s.directory.clone());
@@ -324,8 +1400,22 @@ impl Config {
             scripts_dir,
             scripts_output,
             path: document.environment.path,
+            path_mode: document.environment.path_mode,
+            poll_interval_secs: document.scheduler.poll_interval_secs,
+            history_size: document.scheduler.history_size,
+            auto_restore_tunnels: document.tunnels.auto_restore,
+            start_tunnel_dependencies: document.tunnels.start_dependencies.unwrap_or(true),
+            max_concurrent_starts: document.tunnels.max_concurrent_starts.unwrap_or(0),
+            kill_tunnels_on_quit: document.tunnels.kill_on_quit.unwrap_or(true),
+            startup_notification: document.startup_notification,
+            port_base: document.port_base,
+            log_level: document.log_level,
+            time_format: document.time_format,
+            startup_delay_secs: document.startup_delay_secs,
             scripts_section,
             discovered_command_ids: HashSet::new(),
+            hidden_items: Vec::new(),
+            fragments: MergedFragments::default(),
         };
 
         let mut section_ids = HashSet::new();
@@ -335,19 +1425,37 @@ impl Config {
 
         for section in document.sections {
             if !section_ids.insert(section.id.clone()) {
-                return Err(format!("Duplicate section id '{}'", section.id).into());
+                return Err(ConfigError::validation(format!(
+                    "Duplicate section id '{}'",
+                    section.id
+                )));
             }
 
             let mut item_ids = Vec::new();
-            for item in section.items {
+            for (position, item) in section.items.into_iter().enumerate() {
                 let id = item.id.clone();
+                let (ids, label) = match section.kind {
+                    SectionKind::Tunnel => (&mut tunnel_ids, "tunnel"),
+                    SectionKind::Command => (&mut command_ids, "command"),
+                    SectionKind::ScheduledTask => (&mut schedule_ids, "scheduled-task"),
+                };
+                if !ids.insert(id.clone()) {
+                    return Err(ConfigError::validation(format!(
+                        "Duplicate {label} id '{id}'"
+                    )));
+                }
+                // Hidden entries are not checked either, like commented-out ones.
+                if item.hidden {
+                    config
+                        .hidden_items
+                        .push((section.id.clone(), position, item));
+                    continue;
+                }
                 match section.kind {
                     SectionKind::Tunnel => {
-                        if !tunnel_ids.insert(id.clone()) {
-                            return Err(format!("Duplicate tunnel id '{id}'").into());
-                        }
-                        let (command, args) = split_action(item.start, "start", &id)?;
-                        let (kill_command, kill_args) = split_action(item.stop, "stop", &id)?;
+                        let (command, args) = split_action(item.start, item.shell, "start", &id)?;
+                        let (kill_command, kill_args) =
+                            split_action(item.stop, item.shell, "stop", &id)?;
                         config.tunnels.push((
                             id.clone(),
                             TunnelConfig {
@@ -356,14 +1464,23 @@ impl Config {
                                 args,
                                 kill_command,
                                 kill_args,
+                                kill_timeout_secs: item.kill_timeout_secs,
+                                max_retries: item.max_retries,
+                                retry_backoff_secs: item.retry_backoff_secs,
+                                working_dir: item.working_dir,
+                                env: item.env.into_iter().collect(),
+                                shell: item.shell,
+                                auto_start: item.auto_start,
+                                ready_check: item.ready_check,
+                                health_check: item.health_check,
+                                health_interval_secs: item.health_interval_secs,
+                                start_delay_secs: item.start_delay_secs,
+                                depends_on: item.depends_on,
                             },
                         ));
                     }
                     SectionKind::Command => {
-                        if !command_ids.insert(id.clone()) {
-                            return Err(format!("Duplicate command id '{id}'").into());
-                        }
-                        let (command, args) = split_action(item.run, "run", &id)?;
+                        let (command, args) = split_action(item.run, false, "run", &id)?;
                         config.commands.push((
                             id.clone(),
                             CommandConfig {
@@ -375,13 +1492,53 @@ impl Config {
                         ));
                     }
                     SectionKind::ScheduledTask => {
-                        if !schedule_ids.insert(id.clone()) {
-                            return Err(format!("Duplicate scheduled-task id '{id}'").into());
+                        let (command, args) = split_action(item.run, item.shell, "run", &id)?;
+                        // Interval schedules travel as `@every <duration>` so the
+                        // scheduler and menus handle both forms through one field.
+                        let cron_schedule = match (item.cron, item.interval) {
+                            (Some(cron), None) => cron,
+                            (None, Some(interval)) => format!("{EVERY_PREFIX}{interval}"),
+                            (Some(_), Some(_)) => {
+                                return Err(ConfigError::validation(format!(
+                                    "Scheduled task '{id}' cannot set both 'cron' and 'interval'"
+                                )));
+                            }
+                            (None, None) => {
+                                return Err(ConfigError::validation(format!(
+                                    "Scheduled task '{id}' requires 'cron' or 'interval'"
+                                )));
+                            }
+                        };
+                        if let Some(timezone) = &item.timezone
+                            && timezone.trim().parse::<chrono_tz::Tz>().is_err()
+                        {
+                            return Err(ConfigError::validation(format!(
+                                "Scheduled task '{id}' has unknown timezone '{timezone}'"
+                            )));
                         }
-                        let (command, args) = split_action(item.run, "run", &id)?;
-                        let cron_schedule = item
-                            .cron
-                            .ok_or_else(|| format!("Scheduled task '{id}' requires 'cron'"))?;
+                        let timestamp = |value: Option<String>, field: &str| {
+                            value
+                                .map(|value| {
+                                    DateTime::parse_from_rfc3339(value.trim()).map_err(|e| {
+                                        ConfigError::validation(format!(
+                                            "Scheduled task '{id}' has an invalid '{field}' \
+                                             '{value}': {e}"
+                                        ))
+                                    })
+                                })
+                                .transpose()
+                        };
+                        let active_from = timestamp(item.active_from, "active_from")?;
+                        let active_until = timestamp(item.active_until, "active_until")?;
+                        let active_hours = item
+                            .active_hours
+                            .map(|hours| hours.parse::<ActiveHours>())
+                            .transpose()
+                            .map_err(|e| {
+                                ConfigError::validation(format!(
+                                    "Scheduled task '{id}' has an invalid 'active_hours': {e}"
+                                ))
+                            })?;
                         config.schedules.push((
                             id.clone(),
                             ScheduledTaskConfig {
@@ -389,6 +1546,21 @@ impl Config {
                                 command,
                                 args,
                                 cron_schedule,
+                                timezone: item.timezone,
+                                env: item.env.into_iter().collect(),
+                                shell: item.shell,
+                                wait_for_completion: item.wait_for_completion,
+                                timeout_secs: item.timeout_secs,
+                                jitter_secs: item.jitter_secs,
+                                active_from,
+                                active_until,
+                                active_hours,
+                                run_on_startup: item.run_on_startup,
+                                path: item.path,
+                                notify_on_complete: item.notify_on_complete,
+                                then: item.then,
+                                then_on_failure: item.then_on_failure,
+                                enabled: item.enabled.unwrap_or(true),
                             },
                         ));
                     }
@@ -411,10 +1583,7 @@ impl Config {
         Ok(config)
     }
 
-    fn add_discovered_scripts(
-        &mut self,
-        scripts: &ScriptsDocument,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    fn add_discovered_scripts(&mut self, scripts: &ScriptsDocument) -> Result<(), ConfigError> {
         let discovered = discover_scripts(&scripts.directory, scripts.output.as_deref());
         if discovered.is_empty() {
             return Ok(());
@@ -427,11 +1596,10 @@ impl Config {
             .position(|section| section.id == requested_id)
         {
             if self.sections[index].kind != SectionKind::Command {
-                return Err(format!(
+                return Err(ConfigError::validation(format!(
                     "Scripts section '{}' must have kind = 'command'",
                     requested_id
-                )
-                .into());
+                )));
             }
             index
         } else {
@@ -463,7 +1631,7 @@ impl Config {
             .sections
             .iter()
             .map(|section| {
-                let items = section
+                let mut items: Vec<ItemDocument> = section
                     .item_ids
                     .iter()
                     .filter(|id| !self.discovered_command_ids.contains(*id))
@@ -473,32 +1641,67 @@ impl Config {
                             name: config.name.clone(),
                             start: Some(join_action(&config.command, &config.args)),
                             stop: Some(join_action(&config.kill_command, &config.kill_args)),
-                            run: None,
-                            cron: None,
-                            output: None,
+                            kill_timeout_secs: config.kill_timeout_secs,
+                            max_retries: config.max_retries,
+                            retry_backoff_secs: config.retry_backoff_secs,
+                            working_dir: config.working_dir.clone(),
+                            env: config.env.clone().into_iter().collect(),
+                            shell: config.shell,
+                            auto_start: config.auto_start,
+                            ready_check: config.ready_check.clone(),
+                            health_check: config.health_check.clone(),
+                            health_interval_secs: config.health_interval_secs,
+                            start_delay_secs: config.start_delay_secs,
+                            depends_on: config.depends_on.clone(),
+                            ..Default::default()
                         }),
                         SectionKind::Command => self.command(id).map(|config| ItemDocument {
                             id: id.clone(),
                             name: config.name.clone(),
-                            start: None,
-                            stop: None,
                             run: Some(join_action(&config.command, &config.args)),
-                            cron: None,
                             output: config.output.clone(),
+                            ..Default::default()
                         }),
                         SectionKind::ScheduledTask => {
                             self.schedule(id).map(|config| ItemDocument {
                                 id: id.clone(),
                                 name: config.name.clone(),
-                                start: None,
-                                stop: None,
                                 run: Some(join_action(&config.command, &config.args)),
-                                cron: Some(config.cron_schedule.clone()),
-                                output: None,
+                                cron: (!config.cron_schedule.starts_with(EVERY_PREFIX))
+                                    .then(|| config.cron_schedule.clone()),
+                                interval: config
+                                    .cron_schedule
+                                    .strip_prefix(EVERY_PREFIX)
+                                    .map(str::to_string),
+                                env: config.env.clone().into_iter().collect(),
+                                wait_for_completion: config.wait_for_completion,
+                                timeout_secs: config.timeout_secs,
+                                jitter_secs: config.jitter_secs,
+                                active_from: config.active_from.map(|from| from.to_rfc3339()),
+                                active_until: config.active_until.map(|until| until.to_rfc3339()),
+                                active_hours: config.active_hours.map(|hours| hours.to_string()),
+                                run_on_startup: config.run_on_startup,
+                                timezone: config.timezone.clone(),
+                                shell: config.shell,
+                                path: config.path.clone(),
+                                notify_on_complete: config.notify_on_complete,
+                                then: config.then.clone(),
+                                then_on_failure: config.then_on_failure,
+                                enabled: (!config.enabled).then_some(false),
+                                ..Default::default()
                             })
                         }
                     })
                     .collect();
+                // Positions ascend, so each lands where it was unless items around
+                // it were removed.
+                for (_, position, item) in self
+                    .hidden_items
+                    .iter()
+                    .filter(|(section_id, _, _)| *section_id == section.id)
+                {
+                    items.insert((*position).min(items.len()), item.clone());
+                }
                 SectionDocument {
                     id: section.id.clone(),
                     title: section.title.clone(),
@@ -509,10 +1712,27 @@ impl Config {
             })
             .collect();
 
-        V2Document {
+        let mut document = V2Document {
             version: CURRENT_CONFIG_VERSION,
+            startup_notification: self.startup_notification,
+            port_base: self.port_base,
+            log_level: self.log_level,
+            time_format: self.time_format,
+            startup_delay_secs: self.startup_delay_secs,
             environment: EnvironmentDocument {
                 path: self.path.clone(),
+                path_mode: self.path_mode,
+            },
+            scheduler: SchedulerDocument {
+                poll_interval_secs: self.poll_interval_secs,
+                history_size: self.history_size,
+            },
+            tunnels: TunnelsDocument {
+                auto_restore: self.auto_restore_tunnels,
+                start_dependencies: (!self.start_tunnel_dependencies).then_some(false),
+                max_concurrent_starts: (self.max_concurrent_starts != 0)
+                    .then_some(self.max_concurrent_starts),
+                kill_on_quit: (!self.kill_tunnels_on_quit).then_some(false),
             },
             scripts: self.scripts_dir.as_ref().map(|directory| ScriptsDocument {
                 directory: directory.clone(),
@@ -520,7 +1740,9 @@ impl Config {
                 section: self.scripts_section.clone(),
             }),
             sections,
-        }
+        };
+        self.fragments.unmerge(&mut document);
+        document
     }
 }
 
@@ -528,7 +1750,14 @@ impl Default for Config {
     fn default() -> Self {
         Self::from_v2_document(V2Document {
             version: CURRENT_CONFIG_VERSION,
+            startup_notification: false,
+            port_base: None,
+            log_level: LogLevel::default(),
+            time_format: None,
+            startup_delay_secs: None,
             environment: EnvironmentDocument::default(),
+            scheduler: SchedulerDocument::default(),
+            tunnels: TunnelsDocument::default(),
             scripts: None,
             sections: vec![
                 SectionDocument {
@@ -540,41 +1769,49 @@ impl Default for Config {
                         ItemDocument {
                             id: "example-ssh".to_string(),
                             name: "Example SSH Tunnel".to_string(),
-                            start: Some(vec![
-                                "ssh".to_string(),
-                                "-N".to_string(),
-                                "-L".to_string(),
-                                "5432:localhost:5432".to_string(),
-                                "user@example.com".to_string(),
-                            ]),
-                            stop: Some(vec![
-                                "pkill".to_string(),
-                                "-f".to_string(),
-                                "user@example.com".to_string(),
-                            ]),
-                            run: None,
-                            cron: None,
-                            output: None,
+                            start: Some(
+                                vec![
+                                    "ssh".to_string(),
+                                    "-N".to_string(),
+                                    "-L".to_string(),
+                                    "5432:localhost:5432".to_string(),
+                                    "user@example.com".to_string(),
+                                ]
+                                .into(),
+                            ),
+                            stop: Some(
+                                vec![
+                                    "pkill".to_string(),
+                                    "-f".to_string(),
+                                    "user@example.com".to_string(),
+                                ]
+                                .into(),
+                            ),
+                            ..Default::default()
                         },
                         ItemDocument {
                             id: "k8s-example".to_string(),
                             name: "K8s Port Forward".to_string(),
-                            start: Some(vec![
-                                "kubectl".to_string(),
-                                "port-forward".to_string(),
-                                "svc/my-service".to_string(),
-                                "8080:8080".to_string(),
-                                "-n".to_string(),
-                                "default".to_string(),
-                            ]),
-                            stop: Some(vec![
-                                "pkill".to_string(),
-                                "-f".to_string(),
-                                "svc/my-service".to_string(),
-                            ]),
-                            run: None,
-                            cron: None,
-                            output: None,
+                            start: Some(
+                                vec![
+                                    "kubectl".to_string(),
+                                    "port-forward".to_string(),
+                                    "svc/my-service".to_string(),
+                                    "8080:8080".to_string(),
+                                    "-n".to_string(),
+                                    "default".to_string(),
+                                ]
+                                .into(),
+                            ),
+                            stop: Some(
+                                vec![
+                                    "pkill".to_string(),
+                                    "-f".to_string(),
+                                    "svc/my-service".to_string(),
+                                ]
+                                .into(),
+                            ),
+                            ..Default::default()
                         },
                     ],
                 },
@@ -586,11 +1823,9 @@ impl Default for Config {
                     items: vec![ItemDocument {
                         id: "colima".to_string(),
                         name: "Colima Docker".to_string(),
-                        start: Some(vec!["colima".to_string(), "start".to_string()]),
-                        stop: Some(vec!["colima".to_string(), "stop".to_string()]),
-                        run: None,
-                        cron: None,
-                        output: None,
+                        start: Some(vec!["colima".to_string(), "start".to_string()].into()),
+                        stop: Some(vec!["colima".to_string(), "stop".to_string()].into()),
+                        ..Default::default()
                     }],
                 },
                 SectionDocument {
@@ -601,14 +1836,11 @@ impl Default for Config {
                     items: vec![ItemDocument {
                         id: "daily-backup".to_string(),
                         name: "Daily Backup".to_string(),
-                        start: None,
-                        stop: None,
-                        run: Some(vec![
-                            "echo".to_string(),
-                            "Running daily backup...".to_string(),
-                        ]),
+                        run: Some(
+                            vec!["echo".to_string(), "Running daily backup...".to_string()].into(),
+                        ),
                         cron: Some("0 6 * * *".to_string()),
-                        output: None,
+                        ..Default::default()
                     }],
                 },
             ],
@@ -617,37 +1849,186 @@ impl Default for Config {
     }
 }
 
-fn declared_version(value: &toml::Value) -> Result<u64, Box<dyn std::error::Error>> {
-    let table = value.as_table().ok_or("Root must be a table")?;
+/// Merge the `conf.d` fragments into `document`, in order. A fragment section with
+/// an existing id adds to that section and a new one is appended; an item whose
+/// kind and id already exist replaces the earlier one where it stands.
+fn merge_fragments(
+    document: &mut V2Document,
+    fragments: &[(String, Vec<u8>)],
+) -> Result<MergedFragments, ConfigError> {
+    const CONFIG_FILE: &str = "the config file";
+    let mut merged = MergedFragments::default();
+    // Where each item was last declared, to tell overrides from duplicates.
+    let mut origins: HashMap<(SectionKind, String), &str> = document
+        .sections
+        .iter()
+        .flat_map(|section| {
+            section
+                .items
+                .iter()
+                .map(|item| ((section.kind, item.id.clone()), CONFIG_FILE))
+        })
+        .collect();
+
+    for (name, contents) in fragments {
+        for section in parse_fragment(name, contents)?.sections {
+            let SectionDocument {
+                id,
+                title,
+                icon,
+                kind,
+                items,
+            } = section;
+            let index = match document
+                .sections
+                .iter()
+                .position(|existing| existing.id == id)
+            {
+                Some(index) if document.sections[index].kind != kind => {
+                    return Err(ConfigError::validation(format!(
+                        "Section '{id}' in {name} has a different kind than the section it adds to"
+                    )));
+                }
+                Some(index) => index,
+                None => {
+                    merged.sections.insert(id.clone());
+                    document.sections.push(SectionDocument {
+                        id,
+                        title,
+                        icon,
+                        kind,
+                        items: Vec::new(),
+                    });
+                    document.sections.len() - 1
+                }
+            };
+
+            for item in items {
+                let key = (kind, item.id.clone());
+                let label = match kind {
+                    SectionKind::Tunnel => "Tunnel",
+                    SectionKind::Command => "Command",
+                    SectionKind::ScheduledTask => "Scheduled task",
+                };
+                let Some(origin) = origins.insert(key.clone(), name) else {
+                    merged.items.insert(key);
+                    document.sections[index].items.push(item);
+                    continue;
+                };
+                if origin == name {
+                    return Err(ConfigError::validation(format!(
+                        "{label} '{}' is declared twice in {name}",
+                        item.id
+                    )));
+                }
+                if origin == CONFIG_FILE {
+                    info!(
+                        "{label} '{}' from {name} replaces the one in the config file",
+                        item.id
+                    );
+                } else {
+                    warn!(
+                        "{label} '{}' is declared in both {origin} and {name}",
+                        item.id
+                    );
+                    merged.duplicates.push(format!(
+                        "{label} '{}' is declared in both {origin} and {name}; the one in {name} is used",
+                        item.id
+                    ));
+                }
+                let slot = document
+                    .sections
+                    .iter_mut()
+                    .filter(|section| section.kind == kind)
+                    .flat_map(|section| section.items.iter_mut())
+                    .find(|existing| existing.id == item.id)
+                    .expect("every declared item is in the document");
+                let previous = std::mem::replace(slot, item);
+                if origin == CONFIG_FILE {
+                    merged.replaced.insert(key, previous);
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Parse one fragment; only its `[[sections]]` are read.
+fn parse_fragment(name: &str, contents: &[u8]) -> Result<FragmentDocument, ConfigError> {
+    let content = std::str::from_utf8(contents).map_err(|e| ConfigError::Parse {
+        line: None,
+        msg: format!("{e} (in {name})"),
+    })?;
+    let in_fragment = |error: ConfigError| match error {
+        ConfigError::Parse { line, msg } => ConfigError::Parse {
+            line,
+            msg: format!("{msg} (in {name})"),
+        },
+        other => other,
+    };
+    let fragment: FragmentDocument =
+        toml::from_str(content).map_err(|e| in_fragment(ConfigError::parse(content, e)))?;
+    if let Ok(table) = content.parse::<toml::Table>() {
+        for key in table
+            .keys()
+            .filter(|key| !matches!(key.as_str(), "sections" | "version"))
+        {
+            warn!("Ignoring '{key}' in {name}; fragments only add [[sections]]");
+        }
+    }
+    Ok(fragment)
+}
+
+fn declared_version(value: &toml::Value) -> Result<u64, ConfigError> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| ConfigError::validation("Root must be a table"))?;
     match table.get("version") {
         None => Ok(1),
         Some(value) => value
             .as_integer()
             .and_then(|version| u64::try_from(version).ok())
-            .ok_or_else(|| "Config 'version' must be a positive integer".into()),
+            .ok_or_else(|| ConfigError::validation("Config 'version' must be a positive integer")),
     }
 }
 
+/// Split an item's action into its program and arguments. A command line is split
+/// into words, unless `shell` is set: then it is the script, handed over whole.
 fn split_action(
-    action: Option<Vec<String>>,
+    action: Option<ActionDocument>,
+    shell: bool,
     field: &str,
     item_id: &str,
-) -> Result<(String, Vec<String>), Box<dyn std::error::Error>> {
-    let mut action = action.ok_or_else(|| format!("Item '{item_id}' requires '{field}'"))?;
+) -> Result<(String, Vec<String>), ConfigError> {
+    let action = action
+        .ok_or_else(|| ConfigError::validation(format!("Item '{item_id}' requires '{field}'")))?;
+    let mut action = match action {
+        ActionDocument::Argv(argv) => argv,
+        ActionDocument::Line(line) if shell => vec![line],
+        ActionDocument::Line(line) => split_command_line(&line).map_err(|e| {
+            ConfigError::validation(format!("Item '{item_id}' has an invalid '{field}': {e}"))
+        })?,
+    };
     if action.is_empty() {
-        return Err(format!("Item '{item_id}' has an empty '{field}' command").into());
+        return Err(ConfigError::validation(format!(
+            "Item '{item_id}' has an empty '{field}' command"
+        )));
     }
     let command = action.remove(0);
     if command.is_empty() {
-        return Err(format!("Item '{item_id}' has an empty executable in '{field}'").into());
+        return Err(ConfigError::validation(format!(
+            "Item '{item_id}' has an empty executable in '{field}'"
+        )));
     }
     Ok((command, action))
 }
 
-fn join_action(command: &str, args: &[String]) -> Vec<String> {
-    std::iter::once(command.to_string())
-        .chain(args.iter().cloned())
-        .collect()
+fn join_action(command: &str, args: &[String]) -> ActionDocument {
+    ActionDocument::Argv(
+        std::iter::once(command.to_string())
+            .chain(args.iter().cloned())
+            .collect(),
+    )
 }
 
 fn persist_migration(
@@ -655,7 +2036,7 @@ fn persist_migration(
     original: &[u8],
     migrated: &[u8],
     from_version: u64,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), ConfigError> {
     let file_name = config_path
         .file_name()
         .and_then(|name| name.to_str())
@@ -664,14 +2045,16 @@ fn persist_migration(
     if !backup_path.exists() {
         fs::write(&backup_path, original)?;
     }
-    fs::write(config_path, migrated)?;
+    write_atomic(config_path, migrated)?;
     Ok(())
 }
 
 /// Permanent v1 adapter. Do not remove when newer schemas are introduced; future
 /// migrations should chain this document through each subsequent version.
-fn migrate_v1_to_v2(value: toml::Value) -> Result<V2Document, Box<dyn std::error::Error>> {
-    let table = value.as_table().ok_or("Root must be a table")?;
+fn migrate_v1_to_v2(value: toml::Value) -> Result<V2Document, ConfigError> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| ConfigError::validation("Root must be a table"))?;
     let path = optional_string(table, "path");
     let scripts_dir = optional_string(table, "scripts_dir");
     let scripts_output = optional_string(table, "scripts_output");
@@ -705,7 +2088,17 @@ fn migrate_v1_to_v2(value: toml::Value) -> Result<V2Document, Box<dyn std::error
 
     Ok(V2Document {
         version: CURRENT_CONFIG_VERSION,
-        environment: EnvironmentDocument { path },
+        startup_notification: false,
+        port_base: None,
+        log_level: LogLevel::default(),
+        time_format: None,
+        startup_delay_secs: None,
+        environment: EnvironmentDocument {
+            path,
+            ..Default::default()
+        },
+        scheduler: SchedulerDocument::default(),
+        tunnels: TunnelsDocument::default(),
         scripts,
         sections,
     })
@@ -718,10 +2111,7 @@ fn optional_string(table: &toml::Table, key: &str) -> Option<String> {
         .map(str::to_string)
 }
 
-fn legacy_entries<T>(
-    root: &toml::Table,
-    key: &str,
-) -> Result<Vec<(String, T)>, Box<dyn std::error::Error>>
+fn legacy_entries<T>(root: &toml::Table, key: &str) -> Result<Vec<(String, T)>, ConfigError>
 where
     T: for<'de> Deserialize<'de>,
 {
@@ -730,7 +2120,7 @@ where
     };
     let table = value
         .as_table()
-        .ok_or_else(|| format!("Legacy '{key}' must be a table"))?;
+        .ok_or_else(|| ConfigError::validation(format!("Legacy '{key}' must be a table")))?;
     table
         .iter()
         .map(|(id, value)| Ok((id.clone(), value.clone().try_into()?)))
@@ -747,9 +2137,7 @@ fn migrate_legacy_tunnels(
             name: item.name,
             start: Some(join_action(&item.command, &item.args)),
             stop: Some(join_action(&item.kill_command, &item.kill_args)),
-            run: None,
-            cron: None,
-            output: None,
+            ..Default::default()
         }
     })
 }
@@ -766,11 +2154,9 @@ fn migrate_legacy_commands(
         |id, item| ItemDocument {
             id,
             name: item.name,
-            start: None,
-            stop: None,
             run: Some(join_action(&item.command, &item.args)),
-            cron: None,
             output: item.output,
+            ..Default::default()
         },
     )
 }
@@ -787,11 +2173,9 @@ fn migrate_legacy_schedules(
         |id, item| ItemDocument {
             id,
             name: item.name,
-            start: None,
-            stop: None,
             run: Some(join_action(&item.command, &item.args)),
             cron: Some(item.cron_schedule),
-            output: None,
+            ..Default::default()
         },
     )
 }
@@ -913,6 +2297,371 @@ fn unique_section_id(base: &str, used: &mut HashSet<String>) -> String {
     unreachable!()
 }
 
+/// The first chain of `edges` (a tunnel's `depends_on`, a task's `then`) that
+/// leads from `id` back to itself.
+fn find_cycle<'a>(
+    id: &str,
+    edges: impl Fn(&str) -> Option<&'a [String]> + Copy,
+) -> Option<Vec<String>> {
+    fn visit<'a>(
+        edges: impl Fn(&str) -> Option<&'a [String]> + Copy,
+        target: &str,
+        chain: &mut Vec<String>,
+    ) -> bool {
+        let Some(next) = chain.last().and_then(|last| edges(last)) else {
+            return false;
+        };
+        for item in next {
+            if item == target {
+                chain.push(item.clone());
+                return true;
+            }
+            if chain.contains(item) {
+                continue;
+            }
+            chain.push(item.clone());
+            if visit(edges, target, chain) {
+                return true;
+            }
+            chain.pop();
+        }
+        false
+    }
+
+    let mut chain = vec![id.to_string()];
+    visit(edges, id, &mut chain).then_some(chain)
+}
+
+/// Message for a spawn that failed because `command` is not in any of `path`'s
+/// directories, naming the PATH so the user can tell whether to fix it or install the tool.
+pub fn command_not_found(command: &str, path: &str) -> String {
+    format!("Command '{command}' not found on PATH ({path})")
+}
+
+/// Whether `program` can be started: a bare name must be an executable file in one
+/// of `path`'s directories, an absolute path must exist. Relative paths depend on
+/// the working directory at spawn time, so they are not checked.
+pub(crate) fn program_exists(program: &str, path: &str) -> bool {
+    let program = Path::new(program.trim());
+    if program.is_absolute() {
+        return is_executable(program);
+    }
+    if program.components().count() > 1 {
+        return true;
+    }
+    std::env::split_paths(path).any(|dir| {
+        EXECUTABLE_EXTENSIONS.iter().any(|extension| {
+            let mut name = program.as_os_str().to_owned();
+            name.push(extension);
+            is_executable(&dir.join(name))
+        })
+    })
+}
+
+/// Suffixes tried after a bare program name when searching PATH.
+#[cfg(windows)]
+const EXECUTABLE_EXTENSIONS: &[&str] = &["", ".exe", ".com", ".bat", ".cmd"];
+#[cfg(not(windows))]
+const EXECUTABLE_EXTENSIONS: &[&str] = &[""];
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Combine a configured PATH with an inherited one, dropping empty and repeated
+/// entries so the earlier position of a directory wins.
+pub fn combine_path(configured: &str, mode: PathMode, inherited: &str) -> String {
+    let (first, second) = match mode {
+        PathMode::Replace => return configured.to_string(),
+        PathMode::Prepend => (configured, inherited),
+        PathMode::Append => (inherited, configured),
+    };
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    let mut entries: Vec<&str> = Vec::new();
+    for entry in first.split(separator).chain(second.split(separator)) {
+        if !entry.is_empty() && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries.join(&separator.to_string())
+}
+
+/// Expand `${VAR}` and `$VAR` in command arguments against the environment the
+/// command runs with: its `env` table, then the configured `PATH`, then the app's
+/// own environment. `$$` produces a literal `$`; unknown variables are left as written.
+pub fn expand_env_args(args: &[String], path: &str, env: &HashMap<String, String>) -> Vec<String> {
+    let lookup = |name: &str| match env.get(name) {
+        Some(value) => Some(value.clone()),
+        None if name == "PATH" => Some(path.to_string()),
+        None => std::env::var(name).ok(),
+    };
+    args.iter()
+        .map(|arg| expand_env_vars(arg, lookup))
+        .collect()
+}
+
+/// Substitute `{port_base}`, `{port_base+N}`, and `{port_base-N}` in `value` with
+/// the top-level `port_base` plus or minus `N`, so profiles can shift all their
+/// forwarded ports at once. Other braces, such as `${VAR}` or `{}`, are left alone.
+/// Fails on a malformed expression, an unset `port_base`, or an out-of-range port.
+pub fn expand_port_template(value: &str, port_base: Option<u16>) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(open) = rest.find('{') {
+        let env_reference = rest[..open].ends_with('$');
+        expanded.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if env_reference || !rest[1..].trim_start().starts_with(PORT_BASE) {
+            expanded.push('{');
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(close) = rest.find('}') else {
+            return Err(format!("'{rest}' is missing its closing '}}'"));
+        };
+        expanded.push_str(&port_from_template(rest[1..close].trim(), port_base)?.to_string());
+        rest = &rest[close + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Name of the variable in port templates.
+const PORT_BASE: &str = "port_base";
+
+/// Evaluate the inside of a `{port_base...}` template.
+fn port_from_template(expression: &str, port_base: Option<u16>) -> Result<u16, String> {
+    let malformed =
+        || format!("'{{{expression}}}' must be {{port_base}}, {{port_base+N}}, or {{port_base-N}}");
+    let offset = expression[PORT_BASE.len()..].trim();
+    let offset = match offset.chars().next() {
+        None => 0,
+        Some(sign @ ('+' | '-')) => {
+            let amount: u16 = offset[1..].trim().parse().map_err(|_| malformed())?;
+            if sign == '+' {
+                i64::from(amount)
+            } else {
+                -i64::from(amount)
+            }
+        }
+        Some(_) => return Err(malformed()),
+    };
+    let base =
+        port_base.ok_or_else(|| format!("'{{{expression}}}' needs a top-level port_base"))?;
+    let port = i64::from(base) + offset;
+    u16::try_from(port)
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| format!("'{{{expression}}}' is {port}, which is not a valid port"))
+}
+
+/// Whether `address` looks like `host:port`; resolving it is left to the probe.
+fn is_host_port(address: &str) -> bool {
+    address
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+/// Build the process for `command args...`. With `shell`, the command is handed to
+/// `sh -c` (`cmd /C` on Windows) as shell syntax, so pipes and redirections work,
+/// while each argument is quoted so it still arrives as a single literal word.
+pub fn command_for(command: &str, args: &[String], shell: bool) -> Command {
+    if !shell {
+        let mut process = Command::new(command);
+        process.args(args);
+        return process;
+    }
+    let script = shell_script(command, args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut process = Command::new("cmd");
+        // cmd.exe does its own parsing; Rust's quoting would add backslash escapes.
+        process.arg("/C").raw_arg(script);
+        process
+    }
+    #[cfg(not(windows))]
+    {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(script);
+        process
+    }
+}
+
+fn shell_script(command: &str, args: &[String]) -> String {
+    std::iter::once(command.to_string())
+        .chain(args.iter().map(|arg| shell_quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What [`command_for`] runs for `command args...`, after `${VAR}` expansion, as one
+/// line that reproduces it when pasted into a terminal: the working directory,
+/// PATH and `env` assignments, then the quoted program and arguments.
+pub fn effective_command_line(
+    command: &str,
+    args: &[String],
+    shell: bool,
+    path: &str,
+    env: &HashMap<String, String>,
+    working_dir: Option<&Path>,
+) -> String {
+    let args = expand_env_args(args, path, env);
+    let mut line = String::new();
+    if let Some(dir) = working_dir {
+        let dir = shell_quote(&dir.to_string_lossy());
+        line.push_str(&if cfg!(windows) {
+            format!("cd /d {dir} && ")
+        } else {
+            format!("cd {dir} && ")
+        });
+    }
+    // Sorted for a stable string; an explicit PATH in `env` wins, as when spawning.
+    let mut variables: Vec<(&str, &str)> = env
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    variables.sort();
+    if !env.contains_key("PATH") {
+        variables.insert(0, ("PATH", path));
+    }
+    for (name, value) in variables {
+        line.push_str(&if cfg!(windows) {
+            format!("set \"{name}={value}\" && ")
+        } else {
+            format!("{name}={} ", shell_quote(value))
+        });
+    }
+    if !shell {
+        line.push_str(&shell_script(&shell_quote(command), &args));
+    } else if cfg!(windows) {
+        line.push_str(&format!("cmd /C {}", shell_script(command, &args)));
+    } else {
+        line.push_str(&format!(
+            "sh -c {}",
+            shell_quote(&shell_script(command, &args))
+        ));
+    }
+    line
+}
+
+/// Split a command line into argv the way a POSIX shell splits words, so
+/// `ssh -N -L 5432:localhost:5432 'my host'` gives five arguments. Single quotes
+/// keep everything literal; inside double quotes a backslash escapes only `"`,
+/// `\\`, `$` and `` ` ``; elsewhere it escapes any character. Nothing is expanded:
+/// `$VAR` is left for [`expand_env_args`], and pipes or globs are plain text.
+pub fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    // `None` between words, so `''` still yields an empty argument.
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err("trailing backslash".to_string()),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quote `arg` for the platform shell unless it is made only of safe characters.
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        arg.to_string()
+    } else if cfg!(windows) {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+fn expand_env_vars(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        rest = &rest[dollar..];
+        if let Some(escaped) = rest.strip_prefix("$$") {
+            expanded.push('$');
+            rest = escaped;
+            continue;
+        }
+        // Length of the whole `${NAME}` / `$NAME` reference, and the name itself.
+        let (len, name) = match rest.strip_prefix("${").and_then(|braced| braced.find('}')) {
+            Some(end) => (end + 3, &rest[2..end + 2]),
+            None => {
+                let end = rest[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .map_or(rest.len(), |end| end + 1);
+                (end, &rest[1..end])
+            }
+        };
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            expanded.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+        match lookup(name) {
+            Some(resolved) => expanded.push_str(&resolved),
+            None => {
+                warn!("Environment variable '{name}' is not set; leaving it unexpanded");
+                expanded.push_str(&rest[..len]);
+            }
+        }
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
 fn expand_tilde(path: &str) -> String {
     if (path == "~" || path.starts_with("~/"))
         && let Some(home) = dirs::home_dir()
@@ -1015,47 +2764,191 @@ mod tests {
     }
 
     #[test]
-    fn loads_v2_sections_in_declared_order() {
-        let document = r#"
-version = 2
+    fn atomic_writes_replace_the_file_and_leave_no_temp_behind() {
+        let paths = test_paths("atomic");
+        let path = paths.state_path();
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(&paths.directory).unwrap().count(), 1);
+
+        // Threads writing at once each land a whole file.
+        let writers: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, n.to_string().repeat(4096)))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.len(), 4096);
+        assert!(
+            contents
+                .chars()
+                .all(|c| c == contents.chars().next().unwrap())
+        );
+        assert_eq!(fs::read_dir(&paths.directory).unwrap().count(), 1);
 
-[environment]
-path = "/custom/bin"
+        fs::remove_dir_all(paths.directory).unwrap();
+    }
 
-[[sections]]
-id = "utilities"
-title = "UTILITIES"
-kind = "command"
+    #[test]
+    fn saving_keeps_the_previous_config_as_a_backup() {
+        let paths = test_paths("save-backup");
+        let config_path = paths.config_path();
+        Config::default().save_with(&paths).unwrap();
+        assert!(!config_path.with_file_name("config.toml.bak").exists());
 
-[[sections.items]]
-id = "hello"
-name = "Hello"
-run = ["echo", "hello"]
-output = "notify"
+        fs::write(&config_path, "version = 2\n# hand edited\n").unwrap();
+        Config::default().save_with(&paths).unwrap();
+        assert_eq!(
+            fs::read_to_string(config_path.with_file_name("config.toml.bak")).unwrap(),
+            "version = 2\n# hand edited\n"
+        );
+        assert!(Config::load_with(&paths).is_ok());
+
+        fs::remove_dir_all(paths.directory).unwrap();
+    }
+
+    #[test]
+    fn merges_conf_d_fragments_and_saves_only_the_config_file() {
+        let paths = test_paths("fragments");
+        let base = r#"
+version = 2
 
 [[sections]]
 id = "connections"
-title = "CONNECTIONS"
 kind = "tunnel"
 
 [[sections.items]]
-id = "database"
+id = "db"
 name = "Database"
-start = ["ssh", "-N", "database"]
-stop = ["pkill", "-f", "database"]
+start = ["ssh", "-N", "db"]
+stop = ["pkill", "-f", "db"]
 "#;
-        let value: toml::Value = document.parse().unwrap();
-        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        fs::write(paths.config_path(), base).unwrap();
+        let fragments = paths.directory.join("conf.d");
+        fs::create_dir_all(&fragments).unwrap();
+        fs::write(
+            fragments.join("20-api.toml"),
+            r#"
+[[sections]]
+id = "work"
+title = "Work"
+kind = "tunnel"
 
-        assert_eq!(config.sections[0].id, "utilities");
-        assert_eq!(config.sections[1].id, "connections");
-        assert_eq!(config.commands[0].0, "hello");
-        assert_eq!(config.tunnels[0].0, "database");
-        assert_eq!(config.get_path(), "/custom/bin");
-    }
+[[sections.items]]
+id = "api"
+name = "API (team)"
+start = ["ssh", "-N", "api-team"]
+stop = ["pkill", "-f", "api-team"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            fragments.join("10-db.toml"),
+            r#"
+[[sections]]
+id = "connections"
+kind = "tunnel"
 
-    #[test]
-    fn migrates_unversioned_v1_and_preserves_backup() {
+[[sections.items]]
+id = "db"
+name = "Database (replica)"
+start = ["ssh", "-N", "db-replica"]
+stop = ["pkill", "-f", "db-replica"]
+
+[[sections.items]]
+id = "api"
+name = "API"
+start = ["ssh", "-N", "api"]
+stop = ["pkill", "-f", "api"]
+"#,
+        )
+        .unwrap();
+        fs::write(fragments.join("notes.txt"), "not a fragment").unwrap();
+
+        let (config, snapshot) = Config::load_with_snapshot(&paths).unwrap();
+        assert_eq!(config.tunnel("db").unwrap().name, "Database (replica)");
+        // 20-api.toml comes later, so its `api` wins and stays where 10-db.toml put it.
+        assert_eq!(config.tunnel("api").unwrap().name, "API (team)");
+        assert_eq!(config.sections[0].item_ids, ["db", "api"]);
+        assert_eq!(config.sections[1].id, "work");
+        assert!(config.sections[1].item_ids.is_empty());
+        let problems: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                "Tunnel 'api' is declared in both conf.d/10-db.toml and conf.d/20-api.toml; \
+              the one in conf.d/20-api.toml is used"
+            ]
+        );
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("name = \"Database\""));
+        assert!(!saved.contains("replica") && !saved.contains("api") && !saved.contains("work"));
+
+        let monitor = ConfigMonitor::new(paths.config_path(), Some(snapshot));
+        assert!(!monitor.has_changed().unwrap());
+        fs::remove_file(fragments.join("20-api.toml")).unwrap();
+        assert!(monitor.has_changed().unwrap());
+
+        fs::write(fragments.join("30-bad.toml"), "[[sections]]\nid = \n").unwrap();
+        let error = Config::load_with(&paths).unwrap_err().to_string();
+        assert!(error.contains("(in conf.d/30-bad.toml)"), "{error}");
+
+        fs::remove_dir_all(paths.directory).unwrap();
+    }
+
+    #[test]
+    fn loads_v2_sections_in_declared_order() {
+        let document = r#"
+version = 2
+
+[environment]
+path = "/custom/bin"
+
+[[sections]]
+id = "utilities"
+title = "UTILITIES"
+kind = "command"
+
+[[sections.items]]
+id = "hello"
+name = "Hello"
+run = ["echo", "hello"]
+output = "notify"
+
+[[sections]]
+id = "connections"
+title = "CONNECTIONS"
+kind = "tunnel"
+
+[[sections.items]]
+id = "database"
+name = "Database"
+start = ["ssh", "-N", "database"]
+stop = ["pkill", "-f", "database"]
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+
+        assert_eq!(config.sections[0].id, "utilities");
+        assert_eq!(config.sections[1].id, "connections");
+        assert_eq!(config.commands[0].0, "hello");
+        assert_eq!(config.tunnels[0].0, "database");
+        assert_eq!(config.get_path(), "/custom/bin");
+    }
+
+    #[test]
+    fn migrates_unversioned_v1_and_preserves_backup() {
         let paths = test_paths("migration");
         let legacy = r#"
 path = "/legacy/bin"
@@ -1122,8 +3015,11 @@ group_header = "TOOLS"
         assert_eq!(migrated.version, CURRENT_CONFIG_VERSION);
         assert_eq!(migrated.sections[0].id, "tools");
         assert_eq!(
-            migrated.sections[0].items[0].run.as_ref().unwrap()[0],
-            "echo"
+            migrated.sections[0].items[0].run,
+            Some(ActionDocument::Argv(vec![
+                "echo".to_string(),
+                "hello".to_string()
+            ]))
         );
     }
 
@@ -1140,4 +3036,950 @@ group_header = "TOOLS"
 
         fs::remove_dir_all(paths.directory).unwrap();
     }
+
+    #[test]
+    fn item_env_round_trips_and_reaches_tunnel_commands() {
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "connections"
+kind = "tunnel"
+
+[[sections.items]]
+id = "staging"
+name = "Staging"
+start = ["kubectl", "port-forward", "svc/api", "8080:80"]
+stop = ["pkill", "-f", "svc/api"]
+env = { KUBECONFIG = "/tmp/staging.yaml", AWS_PROFILE = "staging" }
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        let command = &config.to_tunnel_commands()["staging"];
+        assert_eq!(command.env["KUBECONFIG"], "/tmp/staging.yaml");
+        assert_eq!(command.env["AWS_PROFILE"], "staging");
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        let reloaded = Config::from_v2_document(toml::from_str(&saved).unwrap()).unwrap();
+        assert_eq!(reloaded.tunnels[0].1.env, config.tunnels[0].1.env);
+    }
+
+    #[test]
+    fn reports_task_chains_to_unknown_tasks_and_cycles() {
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "backup"
+name = "Backup"
+run = "backup"
+cron = "0 6 * * *"
+then = ["upload"]
+
+[[sections.items]]
+id = "upload"
+name = "Upload"
+run = "upload"
+cron = "0 7 * * *"
+then = ["backup", "notify"]
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "Scheduled task 'backup' chains back to itself: backup -> upload -> backup",
+                "Scheduled task 'upload' chains to unknown task 'notify'",
+                "Scheduled task 'upload' chains back to itself: upload -> backup -> upload",
+            ]
+        );
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("then = [\"upload\"]"), "{saved}");
+    }
+
+    #[test]
+    fn hidden_items_are_left_out_but_saved_back() {
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "connections"
+kind = "tunnel"
+
+[[sections.items]]
+id = "old"
+name = "Old"
+start = ["ssh", "-N", "old"]
+stop = ["pkill", "-f", "old"]
+hidden = true
+
+[[sections.items]]
+id = "db"
+name = "Database"
+start = ["ssh", "-N", "db"]
+stop = ["pkill", "-f", "db"]
+depends_on = ["old"]
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "cleanup"
+name = "Cleanup"
+run = "rm -rf /tmp/cache"
+cron = "not a cron"
+hidden = true
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.sections[0].item_ids, ["db"]);
+        assert!(config.sections[1].item_ids.is_empty());
+        assert!(config.tunnel("old").is_none());
+        assert!(config.schedules.is_empty());
+        assert!(!config.to_tunnel_commands().contains_key("old"));
+        assert!(config.is_hidden(SectionKind::Tunnel, "old"));
+        assert!(!config.is_hidden(SectionKind::ScheduledTask, "old"));
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(errors, ["Tunnel 'db' depends on hidden tunnel 'old'"]);
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        let reloaded: V2Document = toml::from_str(&saved).unwrap();
+        let ids: Vec<&str> = reloaded.sections[0]
+            .items
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect();
+        assert_eq!(ids, ["old", "db"]);
+        assert!(reloaded.sections[0].items[0].hidden);
+        assert_eq!(
+            reloaded.sections[1].items[0].cron.as_deref(),
+            Some("not a cron")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dry_run_reports_missing_programs_and_next_runs() {
+        let document = r#"
+version = 2
+
+[environment]
+path = "/usr/bin:/bin"
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "present"
+name = "Present"
+run = ["sh", "-c", "true"]
+cron = "0 3 * * *"
+
+[[sections.items]]
+id = "missing"
+name = "Missing"
+run = ["something-bg-no-such-program"]
+cron = "@reboot"
+
+[[sections.items]]
+id = "broken"
+name = "Broken"
+run = ["/bin/sh"]
+cron = "0 25 * * *"
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+
+        let report = config.dry_run();
+        assert_eq!(report.path, "/usr/bin:/bin");
+        let keys: Vec<&str> = report
+            .next_runs
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(keys, ["present", "missing"]);
+        assert!(report.next_runs[0].1.is_some());
+        assert!(report.next_runs[1].1.is_none());
+        assert_eq!(report.problems.len(), 2, "{:?}", report.problems);
+        assert!(report.problems[0].starts_with("Scheduled task 'broken': "));
+        assert_eq!(
+            report.problems[1],
+            "Scheduled task 'missing': command 'something-bg-no-such-program' not found on PATH"
+        );
+    }
+
+    #[test]
+    fn reads_the_log_level() {
+        let parse =
+            |document: &str| -> Result<V2Document, toml::de::Error> { toml::from_str(document) };
+        let config =
+            Config::from_v2_document(parse("version = 2\nlog_level = \"warn\"\n").unwrap())
+                .unwrap();
+        assert_eq!(config.log_level, LogLevel::Warn);
+        assert_eq!(config.log_level.filter(), LevelFilter::Warn);
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("log_level = \"warn\""));
+
+        assert_eq!(Config::default().log_level.filter(), LevelFilter::Info);
+        assert!(parse("version = 2\nlog_level = \"loud\"\n").is_err());
+    }
+
+    #[test]
+    fn reads_startup_and_tunnel_start_delays() {
+        let document = r#"
+version = 2
+startup_delay_secs = 20
+
+[[sections]]
+id = "tunnels"
+kind = "tunnel"
+
+[[sections.items]]
+id = "db"
+name = "Database"
+start = ["ssh", "-N", "-L", "5432:db:5432", "bastion"]
+stop = ["pkill", "-f", "5432:db"]
+start_delay_secs = 5
+
+[[sections.items]]
+id = "web"
+name = "Web"
+start = ["ssh", "-N", "-L", "8080:web:80", "bastion"]
+stop = ["pkill", "-f", "8080:web"]
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.startup_delay(), Duration::from_secs(20));
+        assert_eq!(Config::default().startup_delay(), Duration::ZERO);
+        let commands = config.to_tunnel_commands();
+        assert_eq!(commands["db"].start_delay, Duration::from_secs(5));
+        assert_eq!(commands["web"].start_delay, Duration::ZERO);
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("startup_delay_secs = 20"), "{saved}");
+        assert!(saved.contains("start_delay_secs = 5"), "{saved}");
+    }
+
+    #[test]
+    fn reads_the_time_format_and_guesses_it_from_the_locale() {
+        let parse =
+            |document: &str| -> Result<V2Document, toml::de::Error> { toml::from_str(document) };
+        let config =
+            Config::from_v2_document(parse("version = 2\ntime_format = \"12h\"\n").unwrap())
+                .unwrap();
+        assert_eq!(config.time_format, Some(TimeFormat::TwelveHour));
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("time_format = \"12h\""));
+        assert_eq!(Config::default().time_format, None);
+        assert!(parse("version = 2\ntime_format = \"13h\"\n").is_err());
+
+        assert_eq!(
+            TimeFormat::from_locale("en_US.UTF-8"),
+            TimeFormat::TwelveHour
+        );
+        assert_eq!(TimeFormat::from_locale("en-AU"), TimeFormat::TwelveHour);
+        assert_eq!(
+            TimeFormat::from_locale("en_GB.UTF-8"),
+            TimeFormat::TwentyFourHour
+        );
+        assert_eq!(
+            TimeFormat::from_locale("de_DE@euro"),
+            TimeFormat::TwentyFourHour
+        );
+        assert_eq!(TimeFormat::from_locale("C"), TimeFormat::TwentyFourHour);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_mode_combines_with_the_inherited_path() {
+        let inherited = "/usr/bin:/bin:/opt/homebrew/bin";
+        let homebrew = "/opt/homebrew/bin";
+        assert_eq!(
+            combine_path(homebrew, PathMode::Replace, inherited),
+            "/opt/homebrew/bin"
+        );
+        assert_eq!(
+            combine_path(homebrew, PathMode::Prepend, inherited),
+            "/opt/homebrew/bin:/usr/bin:/bin"
+        );
+        assert_eq!(
+            combine_path("/usr/local/bin:", PathMode::Append, inherited),
+            "/usr/bin:/bin:/opt/homebrew/bin:/usr/local/bin"
+        );
+
+        let value: toml::Value =
+            "version = 2\n[environment]\npath = \"/opt/homebrew/bin\"\npath_mode = \"prepend\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.path_mode, PathMode::Prepend);
+        assert!(config.get_path().starts_with("/opt/homebrew/bin"));
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("path_mode = \"prepend\""));
+        assert!(
+            !toml::to_string(&Config::default().to_v2_document())
+                .unwrap()
+                .contains("path_mode")
+        );
+    }
+
+    #[test]
+    fn scheduler_poll_interval_defaults_and_clamps() {
+        assert_eq!(
+            Config::default().poll_interval(),
+            Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS)
+        );
+
+        let value: toml::Value = "version = 2\n[scheduler]\npoll_interval_secs = 0\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.poll_interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn tunnel_auto_restore_is_opt_in_and_round_trips() {
+        assert!(!Config::default().auto_restore_tunnels);
+        assert!(Config::default().kill_tunnels_on_quit);
+
+        let value: toml::Value = "version = 2\n[tunnels]\nauto_restore = true\nmax_concurrent_starts = 2\nkill_on_quit = false\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert!(config.auto_restore_tunnels);
+        assert_eq!(config.max_concurrent_starts, 2);
+        assert!(!config.kill_tunnels_on_quit);
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(
+            saved.contains(
+                "[tunnels]\nauto_restore = true\nmax_concurrent_starts = 2\nkill_on_quit = false"
+            ),
+            "{saved}"
+        );
+        assert!(
+            !toml::to_string(&Config::default().to_v2_document())
+                .unwrap()
+                .contains("[tunnels]")
+        );
+    }
+
+    #[test]
+    fn interval_tasks_round_trip_as_interval() {
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "sync"
+name = "Sync"
+run = ["rsync", "-a", "src", "dst"]
+interval = "15m"
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.schedules[0].1.cron_schedule, "@every 15m");
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("interval = \"15m\""));
+        assert!(!saved.contains("cron ="));
+    }
+
+    #[test]
+    fn task_path_overrides_round_trip() {
+        let document = r#"
+version = 2
+
+[environment]
+path = "/opt/homebrew/bin:/usr/bin:/bin"
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "minimal"
+name = "Minimal"
+run = ["backup.sh"]
+cron = "0 3 * * *"
+path = "/usr/bin:/bin"
+
+[[sections.items]]
+id = "inherits"
+name = "Inherits"
+run = ["sync.sh"]
+cron = "0 4 * * *"
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert_eq!(config.schedules[0].1.path.as_deref(), Some("/usr/bin:/bin"));
+        assert_eq!(config.schedules[1].1.path, None);
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        let reloaded = Config::from_v2_document(toml::from_str(&saved).unwrap()).unwrap();
+        assert_eq!(reloaded.schedules[0].1.path, config.schedules[0].1.path);
+        assert_eq!(reloaded.schedules[1].1.path, None);
+    }
+
+    #[test]
+    fn expands_environment_variables_in_args() {
+        let lookup = |name: &str| match name {
+            "SSH_HOST" => Some("db.example.com".to_string()),
+            "LOCAL_PORT" => Some("15432".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_env_vars("${LOCAL_PORT}:localhost:5432", lookup),
+            "15432:localhost:5432"
+        );
+        assert_eq!(expand_env_vars("me@$SSH_HOST", lookup), "me@db.example.com");
+        assert_eq!(
+            expand_env_vars("$MISSING/${ALSO_MISSING}", lookup),
+            "$MISSING/${ALSO_MISSING}"
+        );
+        assert_eq!(
+            expand_env_vars("$${SSH_HOST} costs $5 $", lookup),
+            "${SSH_HOST} costs $5 $"
+        );
+        assert_eq!(expand_env_vars("${UNTERMINATED", lookup), "${UNTERMINATED");
+
+        let env = HashMap::from([("PROFILE".to_string(), "staging".to_string())]);
+        assert_eq!(
+            expand_env_args(
+                &["$PROFILE".to_string(), "$PATH".to_string()],
+                "/opt/bin",
+                &env
+            ),
+            ["staging", "/opt/bin"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_commands_keep_arguments_literal() {
+        let args = vec![
+            "it's here".to_string(),
+            "$HOME".to_string(),
+            "plain".to_string(),
+        ];
+        let command = command_for("echo", &args, true);
+        assert_eq!(command.get_program(), "sh");
+        let script: Vec<_> = command.get_args().collect();
+        assert_eq!(script[1], "echo 'it'\\''s here' '$HOME' plain");
+
+        let output = command_for("printf '%s\\n'", &args, true).output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "it's here\n$HOME\nplain\n"
+        );
+        let output = command_for("echo one two | wc -w", &[], true)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "2");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn effective_command_line_shows_what_is_spawned() {
+        let env = HashMap::from([
+            ("LOCAL_PORT".to_string(), "5432".to_string()),
+            ("AWS_PROFILE".to_string(), "dev team".to_string()),
+        ]);
+        let args = vec![
+            "-L".to_string(),
+            "${LOCAL_PORT}:db:5432".to_string(),
+            "my host".to_string(),
+        ];
+        assert_eq!(
+            effective_command_line(
+                "ssh",
+                &args,
+                false,
+                "/usr/bin:/bin",
+                &env,
+                Some(Path::new("/tmp/work dir"))
+            ),
+            "cd '/tmp/work dir' && PATH=/usr/bin:/bin AWS_PROFILE='dev team' LOCAL_PORT=5432 ssh -L 5432:db:5432 'my host'"
+        );
+        assert_eq!(
+            effective_command_line(
+                "pg_dump db | gzip",
+                &[],
+                true,
+                "/bin",
+                &HashMap::new(),
+                None
+            ),
+            "PATH=/bin sh -c 'pg_dump db | gzip'"
+        );
+    }
+
+    #[test]
+    fn fills_in_port_templates_and_rejects_bad_ones() {
+        let expand = |value: &str| expand_port_template(value, Some(15000));
+        assert_eq!(
+            expand("{port_base}:localhost:5432").unwrap(),
+            "15000:localhost:5432"
+        );
+        assert_eq!(
+            expand("{port_base+1}:db:5432,{ port_base - 10 }").unwrap(),
+            "15001:db:5432,14990"
+        );
+        assert_eq!(expand("${HOME} {} {x}").unwrap(), "${HOME} {} {x}");
+        assert!(expand("{port_base*2}").is_err());
+        assert!(expand("{port_base+}").is_err());
+        assert!(expand("{port_base+1").is_err());
+        assert!(expand("{port_base+60000}").is_err());
+        assert!(expand_port_template("{port_base}", None).is_err());
+
+        let document = r#"
+version = 2
+port_base = 20000
+
+[[sections]]
+id = "connections"
+kind = "tunnel"
+
+[[sections.items]]
+id = "db"
+name = "Database"
+start = ["ssh", "-N", "-L", "{port_base+2}:localhost:5432", "bastion"]
+stop = ["pkill", "-f", "{port_base+2}:localhost"]
+ready_check = { tcp = "localhost:{port_base+2}" }
+
+[[sections.items]]
+id = "typo"
+name = "Typo"
+start = ["ssh", "-L", "{port_base+two}:localhost:80", "web"]
+stop = ["pkill", "ssh"]
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        let db = &config.to_tunnel_commands()["db"];
+        assert_eq!(db.args[2], "20002:localhost:5432");
+        assert_eq!(db.kill_args[1], "20002:localhost");
+        assert_eq!(db.ready_check.as_ref().unwrap().address, "localhost:20002");
+        // The saved config keeps the template, not the port it stood for.
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("port_base = 20000"), "{saved}");
+        assert!(
+            saved.contains("\"{port_base+2}:localhost:5432\""),
+            "{saved}"
+        );
+
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("Tunnel 'typo' has an invalid port template"));
+
+        // The malformed template is never spawned as written.
+        let commands = config.to_tunnel_commands();
+        assert_eq!(db.invalid, None);
+        assert_eq!(commands["typo"].invalid.as_ref(), Some(&errors[0]));
+        let manager = crate::tunnel::TunnelManager::new(commands, String::new());
+        assert!(matches!(
+            manager.start("typo"),
+            Err(crate::tunnel::TunnelError::Invalid { .. })
+        ));
+        assert!(!manager.has_active_tunnels());
+    }
+
+    #[test]
+    fn splits_command_lines_like_a_shell() {
+        assert_eq!(
+            split_command_line(r#"ssh -N  -L 5432:localhost:5432 'my host' "a \"b\"" c\ d ''"#)
+                .unwrap(),
+            [
+                "ssh",
+                "-N",
+                "-L",
+                "5432:localhost:5432",
+                "my host",
+                "a \"b\"",
+                "c d",
+                ""
+            ]
+        );
+        assert_eq!(split_command_line("echo $HOME | wc").unwrap()[1], "$HOME");
+        assert!(split_command_line("echo 'open").is_err());
+        assert!(split_command_line("echo \\").is_err());
+
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "tunnels"
+kind = "tunnel"
+
+[[sections.items]]
+id = "db"
+name = "Database"
+start = "ssh -N -L 5432:localhost:5432 'db host'"
+stop = ["pkill", "-f", "db host"]
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "backup"
+name = "Backup"
+run = "pg_dump mydb | gzip > backup.sql.gz"
+shell = true
+cron = "0 6 * * *"
+"#;
+        let config = Config::from_v2_document(toml::from_str(document).unwrap()).unwrap();
+        let tunnel = config.tunnel("db").unwrap();
+        assert_eq!(tunnel.command, "ssh");
+        assert_eq!(tunnel.args, ["-N", "-L", "5432:localhost:5432", "db host"]);
+        assert_eq!(
+            config.schedules[0].1.command,
+            "pg_dump mydb | gzip > backup.sql.gz"
+        );
+        assert!(config.schedules[0].1.args.is_empty());
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains(r#"start = ["ssh", "-N", "-L", "5432:localhost:5432", "db host"]"#));
+
+        let broken = document.replace("'db host'\"", "'db host\"");
+        let error = Config::from_v2_document(toml::from_str(&broken).unwrap()).unwrap_err();
+        assert!(
+            error.to_string().contains("'db' has an invalid 'start'"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn validate_reports_every_problem_at_once() {
+        assert!(Config::default().validate().is_ok());
+
+        let document = r#"
+version = 2
+
+[[sections]]
+id = "tasks"
+icon = "clock"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "backup"
+name = "Backup"
+run = ["/usr/local/bin/backup.sh"]
+cron = "0 25 * * *"
+
+[[sections.items]]
+id = "sync"
+name = "Sync"
+run = ["rsync", "-a", "src", "dst"]
+cron = "*/15 * * * *"
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let mut config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        config.schedules[1].1.command = String::new();
+        config.schedules[1].1.jitter_secs = Some(u64::MAX);
+        config.schedules[1].1.timeout_secs = Some(u64::MAX);
+
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert!(errors[0].contains("'sf:symbol.name', 'file:/path/to/icon.png'"));
+        assert!(errors[1].starts_with("Scheduled task 'backup'"));
+        assert!(errors[2].contains("'sync' has an empty 'run' command"));
+        assert!(errors[3].contains("'sync' jitter_secs must be at most 86400"));
+        assert!(errors[4].contains("'sync' timeout_secs must be at most 86400"));
+    }
+
+    #[test]
+    fn validates_tunnel_dependencies() {
+        let document = r#"
+version = 2
+
+[tunnels]
+start_dependencies = false
+
+[[sections]]
+id = "tunnels"
+kind = "tunnel"
+
+[[sections.items]]
+id = "bastion"
+name = "Bastion"
+start = ["ssh", "-N", "bastion"]
+stop = ["pkill", "-f", "bastion"]
+depends_on = ["forward"]
+
+[[sections.items]]
+id = "forward"
+name = "Forward"
+start = ["ssh", "-N", "-L", "5432:db:5432", "bastion"]
+stop = ["pkill", "-f", "5432:db"]
+depends_on = ["bastion", "vpn"]
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        assert!(!config.start_tunnel_dependencies);
+        assert!(!config.to_tunnel_commands()["forward"].start_dependencies);
+
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "Tunnel 'bastion' depends on itself: bastion -> forward -> bastion",
+                "Tunnel 'forward' depends on unknown tunnel 'vpn'",
+                "Tunnel 'forward' depends on itself: forward -> bastion -> forward",
+            ]
+        );
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("start_dependencies = false"));
+        assert!(saved.contains("depends_on = [\"bastion\", \"vpn\"]"));
+    }
+
+    #[test]
+    fn reads_and_validates_tunnel_health_checks() {
+        let document = r#"
+version = 2
+port_base = 20000
+
+[[sections]]
+id = "tunnels"
+kind = "tunnel"
+
+[[sections.items]]
+id = "db"
+name = "Database"
+start = ["ssh", "-N", "-L", "{port_base+2}:localhost:5432", "bastion"]
+stop = ["pkill", "-f", "{port_base+2}:localhost"]
+health_check = { tcp = "localhost:{port_base+2}" }
+health_interval_secs = 60
+
+[[sections.items]]
+id = "web"
+name = "Web"
+start = ["ssh", "-N", "-L", "8080:web:80", "bastion"]
+stop = ["pkill", "-f", "8080:web"]
+health_check = { command = "curl", args = ["-sf", "http://localhost:8080/"], timeout_secs = 3 }
+
+[[sections.items]]
+id = "both"
+name = "Both"
+start = ["ssh", "-N", "bastion"]
+stop = ["pkill", "-f", "bastion"]
+health_check = { tcp = "localhost", command = "true", timeout_secs = 0 }
+health_interval_secs = 0
+kill_timeout_secs = 9223372036854775807
+ready_check = { tcp = "localhost:22", timeout_secs = 9223372036854775807 }
+
+[[sections.items]]
+id = "slow"
+name = "Slow"
+start = ["ssh", "-N", "bastion"]
+stop = ["pkill", "-f", "bastion"]
+health_check = { tcp = "localhost:22", timeout_secs = 9223372036854775807 }
+health_interval_secs = 9223372036854775807
+"#;
+        let value: toml::Value = document.parse().unwrap();
+        let config = Config::from_v2_document(value.try_into().unwrap()).unwrap();
+        let commands = config.to_tunnel_commands();
+        assert_eq!(
+            commands["db"].health_check,
+            Some(HealthCheck {
+                probe: HealthProbe::Tcp("localhost:20002".to_string()),
+                interval: Duration::from_secs(60),
+                timeout: Duration::from_secs(DEFAULT_HEALTH_TIMEOUT_SECS),
+            })
+        );
+        assert_eq!(
+            commands["web"].health_check,
+            Some(HealthCheck {
+                probe: HealthProbe::Command {
+                    command: "curl".to_string(),
+                    args: vec!["-sf".to_string(), "http://localhost:8080/".to_string()],
+                },
+                interval: Duration::from_secs(DEFAULT_HEALTH_INTERVAL_SECS),
+                timeout: Duration::from_secs(3),
+            })
+        );
+
+        let errors: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "Tunnel 'both' health_check needs exactly one of 'tcp' or 'command'",
+                "Tunnel 'both' ready_check timeout_secs must be at most 86400 (one day)",
+                "Tunnel 'both' kill_timeout_secs must be at most 86400 (one day)",
+                "Tunnel 'both' health_interval_secs must be at least 1",
+                "Tunnel 'both' health_check timeout_secs must be at least 1",
+                "Tunnel 'slow' health_interval_secs must be at most 86400 (one day)",
+                "Tunnel 'slow' health_check timeout_secs must be at most 86400 (one day)",
+            ]
+        );
+
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(
+            saved.contains("tcp = \"localhost:{port_base+2}\""),
+            "{saved}"
+        );
+        assert!(saved.contains("health_interval_secs = 60"), "{saved}");
+    }
+
+    #[test]
+    fn parses_icon_specs() {
+        assert_eq!(
+            IconSpec::parse("sf:clock.fill"),
+            Ok(IconSpec::Symbol("clock.fill".to_string()))
+        );
+        assert_eq!(
+            IconSpec::parse("file:/opt/icons/db.png"),
+            Ok(IconSpec::File(PathBuf::from("/opt/icons/db.png")))
+        );
+        assert_eq!(
+            IconSpec::parse("template:icons/db.png"),
+            Ok(IconSpec::Template(PathBuf::from("icons/db.png")))
+        );
+        assert!(IconSpec::parse("sf:").is_err());
+        assert!(IconSpec::parse("clock.fill").is_err());
+        assert!(IconSpec::parse("https://example.com/icon.png").is_err());
+    }
+
+    #[test]
+    fn validates_scheduled_task_timezones() {
+        let document = |timezone: &str| {
+            format!(
+                r#"
+version = 2
+
+[[sections]]
+id = "tasks"
+kind = "scheduled-task"
+
+[[sections.items]]
+id = "standup"
+name = "Standup"
+run = ["open", "https://meet.example.com"]
+cron = "0 9 * * 1-5"
+timezone = "{timezone}"
+"#
+            )
+        };
+        let parse = |timezone| {
+            let value: toml::Value = document(timezone).parse().unwrap();
+            Config::from_v2_document(value.try_into().unwrap())
+        };
+
+        let config = parse("America/New_York").unwrap();
+        assert_eq!(
+            config.schedules[0].1.timezone.as_deref(),
+            Some("America/New_York")
+        );
+        let saved = toml::to_string(&config.to_v2_document()).unwrap();
+        assert!(saved.contains("timezone = \"America/New_York\""));
+
+        assert!(matches!(
+            parse("Mars/Olympus_Mons"),
+            Err(ConfigError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn loads_and_saves_json_configs() {
+        let paths = test_paths("json");
+        let json_path = paths.config_path().with_extension("json");
+        fs::write(
+            &json_path,
+            r#"{
+  "version": 2,
+  "environment": { "path": "/usr/bin:/bin" },
+  "sections": [
+    {
+      "id": "tasks",
+      "kind": "scheduled-task",
+      "items": [
+        { "id": "sync", "name": "Sync", "run": ["rsync", "-a", "src", "dst"], "interval": "15m" },
+        { "id": "backup", "name": "Backup", "run": ["backup.sh"], "cron": "0 6 * * *" }
+      ]
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config_file(&paths), json_path);
+        let config = Config::load_with(&paths).unwrap();
+        let ids: Vec<&str> = config.schedules.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["sync", "backup"]);
+        assert_eq!(config.path.as_deref(), Some("/usr/bin:/bin"));
+
+        config.save_with(&paths).unwrap();
+        assert!(!paths.config_path().exists());
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(saved["sections"][0]["items"][1]["cron"], "0 6 * * *");
+
+        fs::write(&json_path, "{\n  \"version\": 2,\n  oops\n}").unwrap();
+        assert!(matches!(
+            Config::load_with(&paths),
+            Err(ConfigError::Parse { line: Some(3), .. })
+        ));
+
+        fs::remove_dir_all(paths.directory).unwrap();
+    }
+
+    #[test]
+    fn reports_parse_errors_with_line_numbers() {
+        let paths = test_paths("parse-error");
+        fs::write(paths.config_path(), "version = 2\n\n[environment\n").unwrap();
+
+        let error = Config::load_with_snapshot(&paths).unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { line: Some(3), .. }));
+
+        fs::write(
+            paths.config_path(),
+            "version = 2\n[[sections]]\nid = \"x\"\n",
+        )
+        .unwrap();
+        let error = Config::load_with_snapshot(&paths).unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }));
+
+        fs::remove_dir_all(paths.directory).unwrap();
+    }
 }
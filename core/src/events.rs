@@ -0,0 +1,82 @@
+//! Structured lifecycle events for programmatic observers (scripts, status
+//! bars, tests) that embed the core instead of watching the tray.
+//!
+//! Every subscriber gets its own unbounded channel, so emitting never blocks the
+//! tunnel or scheduler threads. Dropping a receiver unsubscribes it.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The tunnel process is up and, if it has a `ready_check`, accepting connections.
+    TunnelStarted {
+        key: String,
+    },
+    /// The tunnel was switched off or stopped at shutdown.
+    TunnelStopped {
+        key: String,
+    },
+    /// The tunnel could not start or gave up after its retries.
+    TunnelFailed {
+        key: String,
+        error: String,
+    },
+    TaskStarted {
+        key: String,
+    },
+    /// `exit_code` is `None` when the run was not waited on, timed out, or never
+    /// started; `error` is set whenever the run counts as failed.
+    TaskFinished {
+        key: String,
+        exit_code: Option<i32>,
+        error: Option<String>,
+    },
+}
+
+/// Fan-out to all live subscribers. Clones share the same subscriber list.
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<Event>>>>,
+}
+
+impl EventBus {
+    pub(crate) fn subscribe(&self) -> Receiver<Event> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub(crate) fn emit(&self, event: Event) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_subscriber_sees_events_until_it_drops() {
+        let bus = EventBus::default();
+        let first = bus.subscribe();
+        let second = bus.subscribe();
+
+        bus.emit(Event::TaskStarted { key: "a".into() });
+        drop(second);
+        bus.emit(Event::TaskStarted { key: "b".into() });
+
+        let seen: Vec<Event> = first.try_iter().collect();
+        assert_eq!(
+            seen,
+            vec![
+                Event::TaskStarted { key: "a".into() },
+                Event::TaskStarted { key: "b".into() },
+            ]
+        );
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,92 @@
+//! Single-instance guard, so a second copy of the app never runs the same
+//! scheduled tasks, fights over tunnel ports, or overwrites the state files.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use log::{info, warn};
+
+/// Exclusive lock on the lock file, held for as long as this value lives.
+///
+/// The lock is an OS file lock (`flock` / `LockFileEx`), which the OS drops when
+/// its holder exits, so a file left behind by a crashed instance is reclaimed
+/// by the next one instead of blocking it.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Take the lock at `path`, creating the file and its directory if needed.
+    /// Returns `Ok(None)` when another running instance holds it.
+    pub fn acquire(path: &Path) -> Result<Option<Self>, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| format!("Failed to open lock file {}: {e}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut holder = String::new();
+                let _ = file.read_to_string(&mut holder);
+                warn!(
+                    "Another instance holds {} (pid {})",
+                    path.display(),
+                    holder.trim()
+                );
+                return Ok(None);
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(format!("Failed to lock {}: {e}", path.display()));
+            }
+        }
+
+        // Only for diagnostics; the lock itself is what keeps other instances out.
+        let pid = std::process::id();
+        if let Err(e) = file
+            .set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| write!(file, "{pid}"))
+        {
+            warn!("Failed to record pid in {}: {e}", path.display());
+        }
+        info!("Acquired instance lock {}", path.display());
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_one_holder_at_a_time() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let directory = std::env::temp_dir().join(format!(
+            "something-bg-instance-{}-{unique}",
+            std::process::id()
+        ));
+        let path = directory.join("something_bg.lock");
+
+        let first = InstanceLock::acquire(&path).unwrap();
+        assert!(first.is_some());
+        assert!(InstanceLock::acquire(&path).unwrap().is_none());
+
+        // Dropping the holder (or the process dying) frees the existing file.
+        drop(first);
+        assert!(InstanceLock::acquire(&path).unwrap().is_some());
+        let _ = fs::remove_dir_all(directory);
+    }
+}
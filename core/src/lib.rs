@@ -1,19 +1,30 @@
 pub mod command;
 pub mod config;
+pub mod events;
+pub mod instance;
+pub mod logging;
 pub mod scheduler;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tunnel;
 
 /// Interfaces that platform shells can implement to adapt the core library
 /// without pulling in platform-specific dependencies.
 pub mod platform {
-    /// Trait for tray/menu UI surfaces.
-    pub trait TrayUi {
-        type MenuId;
+    /// Trait for tray/menu UI surfaces. The core calls it from worker threads when
+    /// tunnel or task state changes, so implementations forward to their UI thread.
+    pub trait TrayUi: Send + Sync {
+        /// Task labels (last/next run, result) may be stale.
         fn refresh(&self);
+        /// Whether any tunnel is active, for the tray icon and "Disconnect All".
         fn set_active(&self, any_active: bool);
-        fn set_menu_item_state(&self, id: &Self::MenuId, active: bool);
+        /// Check or uncheck the tunnel item whose config key is `key`.
+        fn set_menu_item_state(&self, key: &str, active: bool);
     }
 
+    /// Tray handle shared with the tunnel and scheduler threads.
+    pub type SharedTrayUi = std::sync::Arc<dyn TrayUi>;
+
     /// Trait for dispatching user-visible notifications.
     pub trait Notifier {
         fn info(&self, title: &str, body: &str);
@@ -21,29 +32,152 @@ pub mod platform {
         fn error(&self, title: &str, body: &str);
     }
 
-    /// Trait for logging sinks beyond the default logger.
-    pub trait LoggerSink {
+    /// Notifier handle shared with the tunnel and scheduler threads.
+    pub type SharedNotifier = std::sync::Arc<dyn Notifier + Send + Sync>;
+
+    /// Trait for logging sinks beyond the default logger; see `logging::install`.
+    pub trait LoggerSink: Send + Sync {
         fn init(&self);
+        /// Record one message that passed the level filter.
+        fn write(&self, record: &log::Record);
+        fn flush(&self) {}
+    }
+
+    /// Registering the app to start when the user logs in, for the tray's
+    /// "Start at Login" item. Each shell uses its platform's own mechanism.
+    pub trait Autostart: Send + Sync {
+        /// Whether the app is registered to start at login.
+        fn is_enabled(&self) -> bool;
+        /// Register or unregister the app.
+        fn set_enabled(&self, enabled: bool) -> Result<(), String>;
+    }
+
+    /// Environment variable naming the config file to use instead of the platform default.
+    pub const CONFIG_PATH_ENV: &str = "SOMETHING_BG_CONFIG";
+    /// Environment variable naming the task state file to use instead of the platform
+    /// default. The tunnel state, log, and instance lock live beside it, named after
+    /// it, so two instances can run side by side only when their state paths differ.
+    pub const STATE_PATH_ENV: &str = "SOMETHING_BG_STATE";
+
+    /// The path in environment variable `var` when it is set and non-empty,
+    /// otherwise `default()`. `AppPaths` impls use it so the env var always wins.
+    pub fn path_override(
+        var: &str,
+        default: impl FnOnce() -> std::path::PathBuf,
+    ) -> std::path::PathBuf {
+        match std::env::var_os(var) {
+            Some(path) if !path.is_empty() => std::path::PathBuf::from(path),
+            _ => default(),
+        }
+    }
+
+    /// The file next to task state file `state_path` that ends in `suffix`. The
+    /// default `task_state.toml` keeps the fixed `default_name`; any other state file
+    /// lends its stem, so profiles sharing a folder still get files of their own.
+    pub fn beside_state(
+        state_path: &std::path::Path,
+        default_name: &str,
+        suffix: &str,
+    ) -> std::path::PathBuf {
+        match state_path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if stem != "task_state" => {
+                state_path.with_file_name(format!("{stem}{suffix}"))
+            }
+            _ => state_path.with_file_name(default_name),
+        }
     }
 
     /// Trait for platform-correct config/cache paths.
     pub trait AppPaths: Send + Sync {
         fn config_path(&self) -> std::path::PathBuf;
         fn state_path(&self) -> std::path::PathBuf;
+
+        /// Where the active tunnel set is kept for `[tunnels] auto_restore`.
+        fn tunnel_state_path(&self) -> std::path::PathBuf {
+            beside_state(&self.state_path(), "tunnel_state.toml", ".tunnels.toml")
+        }
+
+        /// Log file written by `logging::RotatingFileSink`, rotated beside it.
+        fn log_path(&self) -> std::path::PathBuf {
+            beside_state(&self.state_path(), "something_bg.log", ".log")
+        }
+
+        /// Lock file that keeps a second copy of the app from starting.
+        fn lock_path(&self) -> std::path::PathBuf {
+            beside_state(&self.state_path(), "something_bg.lock", ".lock")
+        }
     }
 
-    /// Trait for spawning and stopping processes; allows platform-specific policies.
-    pub trait ProcessSpawner {
+    /// Trait for spawning and stopping processes; allows platform-specific policies
+    /// such as process groups, job objects, or detaching.
+    pub trait ProcessSpawner: Send + Sync {
+        /// Start a command whose program, args, environment, and stdio are already set.
         fn spawn(
             &self,
-            program: &str,
-            args: &[String],
-            path: &str,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-        fn kill(
+            command: &mut std::process::Command,
+        ) -> std::io::Result<std::process::Child>;
+
+        /// Forcefully stop a child returned by `spawn`.
+        fn kill(&self, child: &mut std::process::Child) -> std::io::Result<()> {
+            child.kill()
+        }
+    }
+
+    /// Spawner handle shared with the tunnel and scheduler threads.
+    pub type SharedSpawner = std::sync::Arc<dyn ProcessSpawner>;
+
+    /// Default spawner: starts commands with `std::process`. On Unix each child
+    /// leads its own process group and is killed together with everything it
+    /// started, so helpers forked by `ssh` or `kubectl` never keep a port bound.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct StdProcessSpawner;
+
+    impl ProcessSpawner for StdProcessSpawner {
+        fn spawn(
             &self,
-            program: &str,
-            args: &[String],
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+            command: &mut std::process::Command,
+        ) -> std::io::Result<std::process::Child> {
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                command.process_group(0);
+            }
+            command.spawn()
+        }
+
+        fn kill(&self, child: &mut std::process::Child) -> std::io::Result<()> {
+            #[cfg(unix)]
+            // SAFETY: the child leads the process group `spawn` created for it.
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+            }
+            child.kill()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::platform::beside_state;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn files_beside_the_state_are_named_after_it() {
+        let default = Path::new("/data/task_state.toml");
+        assert_eq!(
+            beside_state(default, "something_bg.lock", ".lock"),
+            PathBuf::from("/data/something_bg.lock")
+        );
+
+        let work = Path::new("/data/work.toml");
+        let home = Path::new("/data/home.toml");
+        assert_eq!(
+            beside_state(work, "something_bg.lock", ".lock"),
+            PathBuf::from("/data/work.lock")
+        );
+        assert_eq!(
+            beside_state(home, "tunnel_state.toml", ".tunnels.toml"),
+            PathBuf::from("/data/home.tunnels.toml")
+        );
     }
 }
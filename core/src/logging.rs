@@ -0,0 +1,202 @@
+//! Log file support: a size-rotated file sink, and a logger that feeds sinks
+//! alongside the platform logger, so users have a plain file to attach to a
+//! bug report even where the OS log is hard to extract.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::platform::LoggerSink;
+
+/// Size at which the log file is rotated.
+pub const DEFAULT_LOG_MAX_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept next to the live one (`<name>.1` is the newest).
+pub const DEFAULT_LOG_FILES: usize = 3;
+
+/// Appends records to a file, rotating it to `<name>.1`, `<name>.2`, ... once
+/// it reaches `max_bytes`. The file is opened on the first record.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    /// The open file and its current size.
+    file: Mutex<Option<(File, u64)>>,
+}
+
+impl RotatingFileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_bytes: DEFAULT_LOG_MAX_BYTES,
+            keep: DEFAULT_LOG_FILES,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Rotate at `max_bytes`, keeping `keep` old files (0 keeps none).
+    pub fn set_rotation(&mut self, max_bytes: u64, keep: usize) {
+        self.max_bytes = max_bytes.max(1);
+        self.keep = keep;
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(&self) -> std::io::Result<(File, u64)> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
+    /// Shift `<name>.N` up by one, dropping the oldest, and move the live file to `<name>.1`.
+    fn rotate(&self) -> std::io::Result<()> {
+        let numbered = |n: usize| {
+            let mut name = self.path.as_os_str().to_owned();
+            name.push(format!(".{n}"));
+            PathBuf::from(name)
+        };
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let _ = fs::remove_file(numbered(self.keep));
+        for n in (1..self.keep).rev() {
+            let from = numbered(n);
+            if from.exists() {
+                fs::rename(&from, numbered(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, numbered(1))
+    }
+}
+
+impl LoggerSink for RotatingFileSink {
+    fn init(&self) {}
+
+    fn write(&self, record: &Record) {
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let mut file = self.file.lock().unwrap();
+        if file
+            .as_ref()
+            .is_some_and(|(_, size)| size + line.len() as u64 > self.max_bytes)
+        {
+            *file = None;
+            // A failed rotation keeps appending to the current file.
+            let _ = self.rotate();
+        }
+        if file.is_none() {
+            // Nowhere to report a failure to: logging it would recurse.
+            *file = self.open().ok();
+        }
+        if let Some((handle, size)) = file.as_mut()
+            && handle.write_all(line.as_bytes()).is_ok()
+        {
+            *size += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        if let Some((handle, _)) = self.file.lock().unwrap().as_mut() {
+            let _ = handle.flush();
+        }
+    }
+}
+
+/// Passes every record to the platform logger and to each sink.
+pub struct TeeLogger {
+    primary: Box<dyn Log>,
+    sinks: Vec<Box<dyn LoggerSink>>,
+}
+
+impl Log for TeeLogger {
+    /// Sinks follow the primary logger's filter, so `RUST_LOG` narrows both.
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && self.primary.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.primary.log(record);
+        for sink in &self.sinks {
+            sink.write(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.primary.flush();
+        for sink in &self.sinks {
+            sink.flush();
+        }
+    }
+}
+
+/// Install `primary` (e.g. `env_logger` or `oslog`, built but not initialized)
+/// as the global logger together with `sinks`. Every level passes until
+/// [`Config::apply_log_level`](crate::config::Config::apply_log_level) sets the filter.
+pub fn install(
+    primary: Box<dyn Log>,
+    sinks: Vec<Box<dyn LoggerSink>>,
+) -> Result<(), SetLoggerError> {
+    for sink in &sinks {
+        sink.init();
+    }
+    log::set_boxed_logger(Box::new(TeeLogger { primary, sinks }))?;
+    log::set_max_level(LevelFilter::Trace);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    #[test]
+    fn rotates_and_keeps_a_bounded_number_of_files() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let directory =
+            std::env::temp_dir().join(format!("something-bg-log-{}-{unique}", std::process::id()));
+        let path = directory.join("something_bg.log");
+        let mut sink = RotatingFileSink::new(path.clone());
+        sink.set_rotation(200, 2);
+
+        for n in 0..20 {
+            sink.write(
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("test")
+                    .args(format_args!("line {n} of the rotation test"))
+                    .build(),
+            );
+        }
+        sink.flush();
+
+        let live = fs::read_to_string(&path).unwrap();
+        assert!(live.contains("INFO  test: line 19 of the rotation test"));
+        assert!(live.len() <= 200);
+        assert!(directory.join("something_bg.log.1").exists());
+        assert!(directory.join("something_bg.log.2").exists());
+        assert!(!directory.join("something_bg.log.3").exists());
+        let _ = fs::remove_dir_all(directory);
+    }
+}
@@ -3,27 +3,389 @@
 // Cron-based task scheduler for Something in the Background.
 // Handles scheduling and execution of periodic tasks based on cron expressions.
 
-use chrono::{DateTime, Datelike, Local};
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+#[cfg(feature = "human-time")]
+use chrono::{Datelike, NaiveDate};
+use chrono_tz::Tz;
 use croner::Cron;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus, Stdio};
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::config::{
+    DEFAULT_HISTORY_SIZE, DEFAULT_POLL_INTERVAL_SECS, ScheduledTaskConfig, TimeFormat, command_for,
+    command_not_found, effective_command_line, expand_env_args, write_atomic,
+};
+use crate::events::{Event, EventBus};
+use crate::platform::{
+    AppPaths, ProcessSpawner, SharedNotifier, SharedSpawner, SharedTrayUi, StdProcessSpawner,
+};
+
+/// Schedule prefix for fixed intervals, e.g. `@every 15m` (from `interval = "15m"`).
+pub const EVERY_PREFIX: &str = "@every ";
+
+/// Errors returned by the public scheduler API, split so shells can tell an
+/// unknown task or a bad schedule apart from a run that failed.
+#[derive(Debug)]
+pub enum SchedulerError {
+    /// No task is loaded under this key.
+    TaskNotFound(String),
+    /// A run of the task is still in progress, so no second one was started.
+    AlreadyRunning(String),
+    /// The schedule or its timezone could not be parsed.
+    CronParse(String),
+    /// The task's program is not on its PATH.
+    CommandNotFound {
+        task: String,
+        command: String,
+        path: String,
+    },
+    /// The task's command could not be started.
+    Spawn {
+        task: String,
+        source: std::io::Error,
+    },
+    /// Waiting for the task's command failed.
+    Io {
+        task: String,
+        source: std::io::Error,
+    },
+    /// The task ran past its `timeout` and was killed.
+    TimedOut { task: String, timeout: Duration },
+    /// The task's command exited unsuccessfully (-1 when killed by a signal).
+    Failed { task: String, code: i32 },
+    /// The run was killed because the task was removed while it ran.
+    Stopped(String),
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TaskNotFound(key) => write!(f, "Task '{key}' not found"),
+            Self::AlreadyRunning(key) => write!(f, "Task '{key}' is already running"),
+            Self::CronParse(msg) => write!(f, "{msg}"),
+            Self::CommandNotFound {
+                task,
+                command,
+                path,
+            } => write!(
+                f,
+                "Failed to execute task '{task}': {}",
+                command_not_found(command, path)
+            ),
+            Self::Spawn { task, source } => write!(f, "Failed to execute task '{task}': {source}"),
+            Self::Io { task, source } => write!(f, "Failed to wait for task '{task}': {source}"),
+            Self::TimedOut { task, timeout } => {
+                write!(
+                    f,
+                    "Task '{task}' timed out after {timeout:?} and was killed"
+                )
+            }
+            Self::Failed { task, code } => write!(f, "Task '{task}' exited with code {code}"),
+            Self::Stopped(task) => write!(f, "Task '{task}' was stopped"),
+        }
+    }
+}
+
+impl std::error::Error for SchedulerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Spawn { source, .. } | Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// How a task's runs are spaced.
+#[derive(Clone, Debug)]
+enum Schedule {
+    /// Matched against local time, or the task's `timezone` when one is set.
+    Cron(Box<Cron>, Option<Tz>),
+    /// A fixed delay after the previous run.
+    Every(Duration),
+    /// `@reboot`: once each time the scheduler starts.
+    Reboot,
+}
+
+/// Expand crontab `@` macros into the equivalent five-field pattern.
+fn expand_macro(expression: &str) -> Option<&'static str> {
+    match expression {
+        "@hourly" => Some("0 * * * *"),
+        "@daily" | "@midnight" => Some("0 0 * * *"),
+        "@weekly" => Some("0 0 * * 0"),
+        "@monthly" => Some("0 0 1 * *"),
+        "@yearly" | "@annually" => Some("0 0 1 1 *"),
+        _ => None,
+    }
+}
+
+impl Schedule {
+    fn parse(expression: &str) -> Result<Self, SchedulerError> {
+        let expression = expression.trim();
+        if expression == "@reboot" {
+            return Ok(Self::Reboot);
+        }
+        if let Some(spec) = expression.strip_prefix(EVERY_PREFIX) {
+            let interval = humantime::parse_duration(spec.trim()).map_err(|e| {
+                SchedulerError::CronParse(format!(
+                    "Failed to parse interval '{}': {}",
+                    spec.trim(),
+                    e
+                ))
+            })?;
+            if interval.is_zero() {
+                return Err(SchedulerError::CronParse(format!(
+                    "Interval '{}' must be greater than zero",
+                    spec.trim()
+                )));
+            }
+            return Ok(Self::Every(interval));
+        }
+        Cron::from_str(expand_macro(expression).unwrap_or(expression))
+            .map(|cron| Self::Cron(Box::new(cron), None))
+            .map_err(|e| {
+                SchedulerError::CronParse(format!(
+                    "Failed to parse cron schedule '{}': {}",
+                    expression, e
+                ))
+            })
+    }
+
+    /// Interpret cron fields in the IANA zone `timezone` instead of local time.
+    /// Intervals and `@reboot` don't depend on the wall clock, so they ignore it.
+    fn in_timezone(self, timezone: Option<&str>) -> Result<Self, SchedulerError> {
+        let Some(name) = timezone else {
+            return Ok(self);
+        };
+        let tz = name
+            .trim()
+            .parse::<Tz>()
+            .map_err(|_| SchedulerError::CronParse(format!("Unknown timezone '{}'", name)))?;
+        Ok(match self {
+            Self::Cron(cron, _) => Self::Cron(cron, Some(tz)),
+            other => other,
+        })
+    }
+
+    /// Next occurrence after `from`; `None` for schedules that only run at startup.
+    /// Cron fields are matched against wall-clock time in the schedule's zone.
+    fn next_after(&self, from: &DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+        match self {
+            Self::Cron(cron, Some(tz)) => cron
+                .find_next_occurrence(&from.with_timezone(tz), false)
+                .map(|next| Some(next.with_timezone(&Utc)))
+                .map_err(|e| e.to_string()),
+            Self::Cron(cron, None) => cron
+                .find_next_occurrence(&from.with_timezone(&Local), false)
+                .map(|next| Some(next.with_timezone(&Utc)))
+                .map_err(|e| e.to_string()),
+            Self::Every(interval) => chrono::Duration::from_std(*interval)
+                .ok()
+                .and_then(|interval| from.checked_add_signed(interval))
+                .map(Some)
+                .ok_or_else(|| "interval is too large".to_string()),
+            Self::Reboot => Ok(None),
+        }
+    }
+}
+
+/// Check that a task's schedule and timezone parse, without creating the task.
+pub(crate) fn validate_schedule(
+    expression: &str,
+    timezone: Option<&str>,
+) -> Result<(), SchedulerError> {
+    Schedule::parse(expression)?
+        .in_timezone(timezone)
+        .map(|_| ())
+}
 
-use crate::config::ScheduledTaskConfig;
-use crate::platform::AppPaths;
+/// A daily `HH:MM-HH:MM` window for `active_hours`. It wraps past midnight when
+/// the end is earlier than the start, e.g. `22:00-06:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveHours {
+    pub start: NaiveTime,
+    /// Exclusive.
+    pub end: NaiveTime,
+}
+
+impl ActiveHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for ActiveHours {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{value}' must look like \"09:00-17:00\"");
+        let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+        let time =
+            |part: &str| NaiveTime::parse_from_str(part.trim(), "%H:%M").map_err(|_| invalid());
+        let hours = Self {
+            start: time(start)?,
+            end: time(end)?,
+        };
+        if hours.start == hours.end {
+            return Err(format!("'{value}' is an empty window"));
+        }
+        Ok(hours)
+    }
+}
+
+impl fmt::Display for ActiveHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Searches for an in-window run give up after this many out-of-window occurrences,
+/// so a cron that never fires inside `active_hours` cannot spin forever.
+const MAX_WINDOW_SKIPS: usize = 1000;
+
+/// When a task may run: `active_from`/`active_until` and `active_hours`, the latter
+/// read in the task's timezone. A due run outside it is skipped, not delayed.
+#[derive(Clone, Debug, Default)]
+struct ActiveWindow {
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    hours: Option<ActiveHours>,
+    timezone: Option<Tz>,
+}
+
+impl ActiveWindow {
+    fn contains(&self, time: &DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| *time >= from)
+            && self.until.is_none_or(|until| *time <= until)
+            && self
+                .hours
+                .is_none_or(|hours| hours.contains(self.local_time(time)))
+    }
+
+    fn local_time(&self, time: &DateTime<Utc>) -> NaiveTime {
+        match self.timezone {
+            Some(tz) => time.with_timezone(&tz).time(),
+            None => time.with_timezone(&Local).time(),
+        }
+    }
+
+    /// The earliest moment at or after `time` inside the window; `None` once it has ended.
+    fn next_open(&self, time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = self.from.map_or(*time, |from| from.max(*time));
+        if let Some(hours) = self.hours
+            && !hours.contains(self.local_time(&time))
+        {
+            let date = match self.timezone {
+                Some(tz) => time.with_timezone(&tz).date_naive(),
+                None => time.with_timezone(&Local).date_naive(),
+            };
+            // Today's start if it is still ahead, otherwise tomorrow's.
+            time = [date, date.succ_opt()?]
+                .into_iter()
+                .filter_map(|date| self.to_utc(date.and_time(hours.start)))
+                .find(|start| *start > time)?;
+        }
+        self.until.is_none_or(|until| time <= until).then_some(time)
+    }
+
+    /// A wall-clock time in the window's zone; an hour later if DST skips it.
+    fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let resolve = |local: NaiveDateTime| match self.timezone {
+            Some(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.to_utc()),
+            None => Local
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.to_utc()),
+        };
+        resolve(local).or_else(|| resolve(local + chrono::Duration::hours(1)))
+    }
+}
+
+/// The first occurrence of `schedule` after `from` that falls inside `window`.
+fn next_in_window(
+    schedule: &Schedule,
+    window: &ActiveWindow,
+    from: &DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let mut from = *from;
+    for _ in 0..MAX_WINDOW_SKIPS {
+        let Some(next) = schedule.next_after(&from)? else {
+            return Ok(None);
+        };
+        if window.contains(&next) {
+            return Ok(Some(next));
+        }
+        let Some(open) = window.next_open(&next) else {
+            return Ok(None);
+        };
+        // Occurrences are searched strictly after `from`; one landing exactly on the
+        // window's opening still counts.
+        from = (open - chrono::Duration::seconds(1)).max(next);
+    }
+    Err(format!(
+        "no run within the active window in the next {MAX_WINDOW_SKIPS} occurrences"
+    ))
+}
 
 /// Structure for persisting scheduled task state
 #[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct TaskState {
-    last_run: Option<DateTime<Local>>,
-    next_run: Option<DateTime<Local>>,
+    last_run: Option<DateTime<Utc>>,
+    next_run: Option<DateTime<Utc>>,
+    #[serde(default)]
+    last_exit_code: Option<i32>,
+    #[serde(default)]
+    last_timed_out: bool,
+    /// Set from the tray; overrides the config's `enabled` until toggled back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    /// Oldest first; trimmed to the configured history size on save.
+    #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+    history: VecDeque<RunRecord>,
+    /// The `cron_schedule` and `timezone` that `next_run` was computed for; missing
+    /// from state saved by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+}
+
+/// One finished run of a scheduled task, as kept in its history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// When the run started.
+    pub timestamp: DateTime<Utc>,
+    /// Only known for runs that were waited on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Wall time of the run; `None` for fire-and-forget runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
 }
 
 /// Load persisted task states from disk
@@ -40,7 +402,8 @@ fn load_task_states(path: &PathBuf) -> HashMap<String, TaskState> {
             }
             Err(e) => {
                 warn!("Failed to parse task state file: {}", e);
-                HashMap::new()
+                back_up_corrupt_state(path);
+                recover_task_states(&contents)
             }
         },
         Err(e) => {
@@ -50,8 +413,56 @@ fn load_task_states(path: &PathBuf) -> HashMap<String, TaskState> {
     }
 }
 
+/// Copy an unparseable state file to `<name>.corrupt`, since the next save replaces it.
+fn back_up_corrupt_state(path: &Path) {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".corrupt");
+    match fs::copy(path, &backup) {
+        Ok(_) => warn!("Kept a copy of the unreadable task state as {:?}", backup),
+        Err(e) => warn!("Failed to back up the unreadable task state: {}", e),
+    }
+}
+
+/// Salvage what can be read from a state file that does not parse as a whole,
+/// e.g. after a partial write: each task's `[key]` table (with its history) is
+/// parsed on its own, and only the broken ones are dropped.
+fn recover_task_states(contents: &str) -> HashMap<String, TaskState> {
+    let mut chunks: Vec<String> = vec![String::new()];
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') && !trimmed.starts_with("[[") {
+            chunks.push(String::new());
+        }
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
+
+    let mut states = HashMap::new();
+    let mut lost = Vec::new();
+    for chunk in chunks.iter().filter(|chunk| !chunk.trim().is_empty()) {
+        let Ok(table) = chunk.parse::<toml::Table>() else {
+            lost.push(chunk.lines().next().unwrap_or_default().trim().to_string());
+            continue;
+        };
+        for (key, value) in table {
+            match value.try_into::<TaskState>() {
+                Ok(state) => {
+                    states.insert(key, state);
+                }
+                Err(_) => lost.push(key),
+            }
+        }
+    }
+    if !lost.is_empty() {
+        warn!("Dropped unreadable task state for: {}", lost.join(", "));
+    }
+    info!("Recovered task state for {} task(s)", states.len());
+    states
+}
+
 /// Save task states to disk
-fn save_task_states(path: &PathBuf, states: &HashMap<String, TaskState>) {
+fn save_task_states(path: &Path, states: &HashMap<String, TaskState>) {
     // Ensure the directory exists
     if let Some(parent) = path.parent()
         && let Err(e) = fs::create_dir_all(parent)
@@ -62,7 +473,7 @@ fn save_task_states(path: &PathBuf, states: &HashMap<String, TaskState>) {
 
     match toml::to_string_pretty(&states) {
         Ok(toml_content) => {
-            if let Err(e) = fs::write(path, toml_content) {
+            if let Err(e) = write_atomic(path, toml_content) {
                 error!("Failed to write task state file: {}", e);
             } else {
                 debug!("Saved task states to {}", path.display());
@@ -81,43 +492,125 @@ pub struct ScheduledTask {
     pub command: String,
     pub args: Vec<String>,
     pub cron_schedule: String,
-    pub last_run: Option<DateTime<Local>>,
-    pub next_run: Option<DateTime<Local>>,
-    cron: Option<Cron>,
+    /// IANA zone the cron fields are read in; `None` means local time.
+    pub timezone: Option<String>,
+    /// Extra environment variables, applied after PATH so they can override it.
+    pub env: HashMap<String, String>,
+    /// Run the command through the platform shell.
+    pub shell: bool,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: Option<DateTime<Utc>>,
+    /// Exit code of the last run; only recorded for tasks that wait for completion.
+    pub last_exit_code: Option<i32>,
+    /// Wait for the command to exit so its status can be recorded.
+    pub wait_for_completion: bool,
+    /// Kill the command if it runs longer than this; implies waiting for completion.
+    pub timeout: Option<Duration>,
+    /// Whether the last run was killed for exceeding `timeout`.
+    pub last_timed_out: bool,
+    /// Upper bound of the random delay added to each computed `next_run`.
+    pub jitter: Option<Duration>,
+    /// Also run once when the scheduler starts, on top of the regular schedule.
+    pub run_on_startup: bool,
+    /// PATH for this task, overriding the one passed to `execute`.
+    pub path: Option<String>,
+    /// Notify on success too; implies waiting for completion.
+    pub notify_on_complete: bool,
+    /// Tasks run after this one succeeds; implies waiting for completion.
+    pub then: Vec<String>,
+    /// Run `then` after a failed run too.
+    pub then_on_failure: bool,
+    /// Whether the task runs on schedule; see `TaskScheduler::set_enabled`.
+    pub enabled: bool,
+    /// Recent runs, oldest first.
+    pub history: VecDeque<RunRecord>,
+    schedule: Schedule,
+    window: ActiveWindow,
+    /// The config's `enabled`, so only a differing choice is persisted.
+    config_enabled: bool,
+    /// Set by `TaskScheduler::remove_task` and `TaskScheduler::stop`; shared with
+    /// the copy a run executes on.
+    stop: Arc<AtomicBool>,
 }
 
 impl ScheduledTask {
+    /// A task for `config` as if it had never run, e.g. to preview its schedule
+    /// without a scheduler or saved state.
+    pub fn from_config(config: &ScheduledTaskConfig) -> Result<Self, SchedulerError> {
+        Self::new(config, None)
+    }
+
     /// Create a new ScheduledTask from configuration and persisted state
-    fn new(config: &ScheduledTaskConfig, state: Option<&TaskState>) -> Result<Self, String> {
+    fn new(
+        config: &ScheduledTaskConfig,
+        state: Option<&TaskState>,
+    ) -> Result<Self, SchedulerError> {
         info!(
             "Creating task '{}' with schedule '{}'",
             config.name, config.cron_schedule
         );
 
-        let cron = Cron::from_str(&config.cron_schedule).map_err(|e| {
-            format!(
-                "Failed to parse cron schedule '{}': {}",
-                config.cron_schedule, e
-            )
-        })?;
+        let schedule =
+            Schedule::parse(&config.cron_schedule)?.in_timezone(config.timezone.as_deref())?;
+
+        let now = Utc::now();
+        let jitter = config.jitter_secs.map(Duration::from_secs);
+        let window = ActiveWindow {
+            from: config.active_from.map(|from| from.to_utc()),
+            until: config.active_until.map(|until| until.to_utc()),
+            hours: config.active_hours,
+            timezone: config
+                .timezone
+                .as_deref()
+                .and_then(|tz| tz.trim().parse().ok()),
+        };
+        if let Schedule::Cron(cron, _) = &schedule
+            && let Some(warning) = schedule_warning(cron, &now)
+        {
+            warn!("Task '{}': {}", config.name, warning);
+        }
 
-        let now = Local::now();
+        // A saved next_run is stale once the schedule it was computed for is edited
+        let schedule_changed = state.is_some_and(|state| {
+            state.schedule.as_ref().is_some_and(|saved| {
+                *saved != config.cron_schedule || state.timezone != config.timezone
+            })
+        });
+        if schedule_changed {
+            info!(
+                "Task '{}': schedule changed since next_run was saved, ignoring it",
+                config.name
+            );
+        }
 
         // Load or calculate next_run
-        let next_run = if let Some(state) = state {
-            if let Some(saved_next_run) = state.next_run {
+        let next_run = if matches!(schedule, Schedule::Reboot) {
+            // Scheduled by `TaskScheduler::start`, never from saved state
+            None
+        } else if let Some(state) = state {
+            if let Some(saved_next_run) = state
+                .next_run
+                .filter(|next_run| !schedule_changed && window.contains(next_run))
+            {
                 info!(
                     "Task '{}': loaded next_run from file: {}",
                     config.name, saved_next_run
                 );
                 Some(saved_next_run)
+            } else if let (Schedule::Every(_), Some(last_run)) = (&schedule, state.last_run) {
+                // Intervals count from the previous run, not from now
+                info!(
+                    "Task '{}': no saved next_run, calculating from last run",
+                    config.name
+                );
+                Self::calculate_next_run(&schedule, &window, jitter, &last_run, &config.name)
             } else {
                 // State exists but no next_run - calculate it
                 info!(
                     "Task '{}': no saved next_run, calculating from now",
                     config.name
                 );
-                Self::calculate_next_run(&cron, &now, &config.name)
+                Self::calculate_next_run(&schedule, &window, jitter, &now, &config.name)
             }
         } else {
             // No state at all - first time
@@ -125,10 +618,12 @@ impl ScheduledTask {
                 "Task '{}': first time, calculating next_run from now",
                 config.name
             );
-            Self::calculate_next_run(&cron, &now, &config.name)
+            Self::calculate_next_run(&schedule, &window, jitter, &now, &config.name)
         };
 
         let last_run = state.and_then(|s| s.last_run);
+        let last_exit_code = state.and_then(|s| s.last_exit_code);
+        let last_timed_out = state.is_some_and(|s| s.last_timed_out);
 
         info!(
             "Task '{}': initialized with last_run={:?}, next_run={:?}",
@@ -140,23 +635,48 @@ impl ScheduledTask {
             command: config.command.clone(),
             args: config.args.clone(),
             cron_schedule: config.cron_schedule.clone(),
+            timezone: config.timezone.clone(),
+            env: config.env.clone(),
+            shell: config.shell,
             last_run,
             next_run,
-            cron: Some(cron),
+            last_exit_code,
+            wait_for_completion: config.wait_for_completion
+                || config.notify_on_complete
+                || !config.then.is_empty(),
+            timeout: config.timeout_secs.map(Duration::from_secs),
+            last_timed_out,
+            jitter,
+            run_on_startup: config.run_on_startup,
+            path: config.path.clone(),
+            notify_on_complete: config.notify_on_complete,
+            then: config.then.clone(),
+            then_on_failure: config.then_on_failure,
+            enabled: state.and_then(|s| s.enabled).unwrap_or(config.enabled),
+            history: state.map(|s| s.history.clone()).unwrap_or_default(),
+            schedule,
+            window,
+            config_enabled: config.enabled,
+            stop: Arc::new(AtomicBool::new(false)),
         })
     }
 
-    /// Calculate next occurrence from a given time
+    /// Calculate next occurrence from a given time, delayed by a fresh random
+    /// offset of up to `jitter`
     fn calculate_next_run(
-        cron: &Cron,
-        from_time: &DateTime<Local>,
+        schedule: &Schedule,
+        window: &ActiveWindow,
+        jitter: Option<Duration>,
+        from_time: &DateTime<Utc>,
         task_name: &str,
-    ) -> Option<DateTime<Local>> {
-        match cron.find_next_occurrence(from_time, false) {
-            Ok(next) => {
+    ) -> Option<DateTime<Utc>> {
+        match next_in_window(schedule, window, from_time) {
+            Ok(Some(next)) => {
+                let next = add_jitter(next, jitter);
                 info!("Task '{}': calculated next_run = {}", task_name, next);
                 Some(next)
             }
+            Ok(None) => None,
             Err(e) => {
                 error!("Task '{}': failed to calculate next_run: {}", task_name, e);
                 None
@@ -166,85 +686,318 @@ impl ScheduledTask {
 
     /// Get a human-readable description of the cron schedule
     pub fn get_schedule_description(&self) -> String {
-        if let Some(ref cron) = self.cron {
-            cron.pattern.to_string()
-        } else {
-            self.cron_schedule.clone()
+        match &self.schedule {
+            Schedule::Cron(cron, _) => cron.pattern.to_string(),
+            Schedule::Every(_) | Schedule::Reboot => self.cron_schedule.clone(),
         }
     }
 
-    /// Check if the task should run now
-    pub fn should_run(&self, now: &DateTime<Local>) -> bool {
-        if let Some(next_run) = &self.next_run {
-            now >= next_run
-        } else {
-            false
+    /// The next `n` times the task is due, starting with `next_run`, in local
+    /// time. Shorter when the schedule runs out, and empty for `@reboot` tasks.
+    /// Only `next_run` includes jitter; later runs get theirs once scheduled.
+    /// Runs outside the task's active window are left out.
+    pub fn upcoming(&self, n: usize) -> Vec<DateTime<Local>> {
+        let mut upcoming = Vec::new();
+        let mut next = self.next_run;
+        while let Some(time) = next
+            && upcoming.len() < n
+        {
+            upcoming.push(time.with_timezone(&Local));
+            next = next_in_window(&self.schedule, &self.window, &time)
+                .ok()
+                .flatten();
+        }
+        upcoming
+    }
+
+    /// How long from `now` until `next_run`; zero once it is due, and `None` while
+    /// the task is disabled or has nothing scheduled.
+    pub fn time_until_next(&self, now: &DateTime<Utc>) -> Option<Duration> {
+        let next_run = self.next_run.filter(|_| self.enabled)?;
+        Some((next_run - *now).to_std().unwrap_or_default())
+    }
+
+    /// Whether the task should run as soon as the scheduler starts.
+    fn runs_at_startup(&self) -> bool {
+        self.run_on_startup || matches!(self.schedule, Schedule::Reboot)
+    }
+
+    /// Check if the task should run now; never true while it is disabled or
+    /// outside its active window.
+    pub fn should_run(&self, now: &DateTime<Utc>) -> bool {
+        match &self.next_run {
+            Some(next_run) => self.enabled && now >= next_run && self.window.contains(now),
+            None => false,
+        }
+    }
+
+    /// A run that came due outside the active window is dropped: move `next_run` on
+    /// to the next in-window occurrence. Returns whether it did.
+    fn skip_outside_window(&mut self, now: &DateTime<Utc>) -> bool {
+        let due = self.next_run.is_some_and(|next_run| *now >= next_run);
+        if !self.enabled || !due || self.window.contains(now) {
+            return false;
         }
+        self.next_run =
+            Self::calculate_next_run(&self.schedule, &self.window, self.jitter, now, &self.name);
+        info!(
+            "Task '{}' came due outside its active window; skipped to {:?}",
+            self.name, self.next_run
+        );
+        true
     }
 
     /// Update next run time after execution
     pub fn update_next_run(&mut self) {
-        if let Some(ref cron) = self.cron {
-            let now = Local::now();
-            self.last_run = Some(now);
-            match cron.find_next_occurrence(&now, false) {
-                Ok(next) => {
-                    self.next_run = Some(next);
-                    debug!(
-                        "Task '{}': updated next_run to {} after execution at {}",
-                        self.name, next, now
-                    );
-                }
-                Err(e) => {
-                    error!(
-                        "Task '{}': failed to calculate next_run after execution at {}: {}",
-                        self.name, now, e
-                    );
-                    self.next_run = None;
-                }
+        let now = Utc::now();
+        self.last_run = Some(now);
+        match next_in_window(&self.schedule, &self.window, &now) {
+            Ok(Some(next)) => {
+                let next = add_jitter(next, self.jitter);
+                self.next_run = Some(next);
+                debug!(
+                    "Task '{}': updated next_run to {} after execution at {}",
+                    self.name, next, now
+                );
+            }
+            Ok(None) => self.next_run = None,
+            Err(e) => {
+                error!(
+                    "Task '{}': failed to calculate next_run after execution at {}: {}",
+                    self.name, now, e
+                );
+                self.next_run = None;
             }
         }
     }
 
-    /// Execute the scheduled task
-    pub fn execute(&mut self, path: &str) -> Result<(), String> {
+    /// The command a run would execute, with `path` as PATH unless the task has its
+    /// own; see [`effective_command_line`].
+    pub fn effective_command_line(&self, path: &str) -> String {
+        let path = self.path.as_deref().unwrap_or(path);
+        effective_command_line(&self.command, &self.args, self.shell, path, &self.env, None)
+    }
+
+    /// Execute the scheduled task with `path` as PATH, unless the task has its own
+    pub fn execute(
+        &mut self,
+        path: &str,
+        spawner: &dyn ProcessSpawner,
+    ) -> Result<(), SchedulerError> {
         info!(
             "Executing scheduled task '{}': {} {:?}",
             self.name, self.command, self.args
         );
+        let path = self.path.as_deref().unwrap_or(path);
 
-        let result = Command::new(&self.command)
-            .args(&self.args)
+        let args = expand_env_args(&self.args, path, &self.env);
+        let mut command = command_for(&self.command, &args, self.shell);
+        command
             .env("PATH", path)
+            .envs(&self.env)
             .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn();
-
-        match result {
-            Ok(_) => {
-                self.update_next_run();
-                info!(
-                    "Successfully executed task '{}'. Next run: {:?}",
-                    self.name, self.next_run
-                );
-                Ok(())
-            }
+            .stderr(Stdio::null());
+        let mut child = match spawner.spawn(&mut command) {
+            Ok(child) => child,
             Err(e) => {
-                let err_msg = format!("Failed to execute task '{}': {}", self.name, e);
-                error!("{}", err_msg);
-                Err(err_msg)
+                let err = if e.kind() == std::io::ErrorKind::NotFound && !self.shell {
+                    SchedulerError::CommandNotFound {
+                        task: self.name.clone(),
+                        command: self.command.clone(),
+                        path: path.to_string(),
+                    }
+                } else {
+                    SchedulerError::Spawn {
+                        task: self.name.clone(),
+                        source: e,
+                    }
+                };
+                error!("{}", err);
+                self.last_exit_code = None;
+                return Err(err);
+            }
+        };
+
+        self.update_next_run();
+        if !self.wait_for_completion && self.timeout.is_none() {
+            info!(
+                "Successfully executed task '{}'. Next run: {:?}",
+                self.name, self.next_run
+            );
+            return Ok(());
+        }
+
+        let waited = wait_for_exit(&mut child, self.timeout, spawner, &self.stop);
+        let status = waited.map_err(|e| {
+            let err = SchedulerError::Io {
+                task: self.name.clone(),
+                source: e,
+            };
+            error!("{}", err);
+            err
+        })?;
+        let stopped = self.stop.load(Ordering::SeqCst);
+        self.last_timed_out = status.is_none() && !stopped;
+        let Some(status) = status else {
+            self.last_exit_code = None;
+            if stopped {
+                info!("Task '{}' was stopped while running; killed it", self.name);
+                return Err(SchedulerError::Stopped(self.name.clone()));
             }
+            let err = SchedulerError::TimedOut {
+                task: self.name.clone(),
+                timeout: self.timeout.unwrap_or_default(),
+            };
+            error!("{}", err);
+            return Err(err);
+        };
+        // A process killed by a signal has no exit code; record it as -1.
+        let code = status.code().unwrap_or(-1);
+        self.last_exit_code = Some(code);
+        if status.success() {
+            info!(
+                "Task '{}' completed successfully. Next run: {:?}",
+                self.name, self.next_run
+            );
+            Ok(())
+        } else {
+            let err = SchedulerError::Failed {
+                task: self.name.clone(),
+                code,
+            };
+            error!("{}", err);
+            Err(err)
+        }
+    }
+
+    /// Copy the outcome of a run executed on a clone of this task. The next run
+    /// is only taken over if the schedule was not changed by a reload meanwhile.
+    fn record_run(&mut self, ran: &ScheduledTask) {
+        self.last_run = ran.last_run;
+        self.last_exit_code = ran.last_exit_code;
+        self.last_timed_out = ran.last_timed_out;
+        self.history = ran.history.clone();
+        if self.cron_schedule == ran.cron_schedule && self.timezone == ran.timezone {
+            self.next_run = ran.next_run;
+        }
+    }
+
+    fn state(&self) -> TaskState {
+        TaskState {
+            last_run: self.last_run,
+            next_run: self.next_run,
+            last_exit_code: self.last_exit_code,
+            last_timed_out: self.last_timed_out,
+            enabled: (self.enabled != self.config_enabled).then_some(self.enabled),
+            history: self.history.clone(),
+            schedule: Some(self.cron_schedule.clone()),
+            timezone: self.timezone.clone(),
+        }
+    }
+
+    /// Drop the oldest runs beyond `limit`.
+    fn prune_history(&mut self, limit: usize) {
+        let excess = self.history.len().saturating_sub(limit);
+        self.history.drain(..excess);
+    }
+}
+
+/// Wait for `child`, killing it (and on Unix its process group) once `timeout`
+/// passes or `stop` is set. Returns `None` when the child had to be killed. A
+/// timeout too long to represent as a deadline counts as none.
+fn wait_for_exit(
+    child: &mut Child,
+    timeout: Option<Duration>,
+    spawner: &dyn ProcessSpawner,
+    stop: &AtomicBool,
+) -> std::io::Result<Option<ExitStatus>> {
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    while deadline.is_none_or(|deadline| Instant::now() < deadline) && !stop.load(Ordering::SeqCst)
+    {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let poll = Duration::from_millis(100);
+        thread::sleep(deadline.map_or(poll, |deadline| {
+            poll.min(deadline.saturating_duration_since(Instant::now()))
+        }));
+    }
+
+    // The spawner takes down whatever the task started along with it.
+    let _ = spawner.kill(child);
+    child.wait()?;
+    Ok(None)
+}
+
+/// How long `TaskScheduler::stop` waits for a running task before killing it.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Schedules whose first occurrence is further out than this are almost
+/// always typos (e.g. `0 0 30 2 *`), so they get flagged at load time.
+const MAX_REASONABLE_FIRST_RUN_DAYS: i64 = 366;
+
+/// Describe why a parsed cron may never fire, if it looks suspicious.
+fn schedule_warning(cron: &Cron, now: &DateTime<Utc>) -> Option<String> {
+    let now = now.with_timezone(&Local);
+    match cron.find_next_occurrence(&now, false) {
+        Ok(next) if next - now > chrono::Duration::days(MAX_REASONABLE_FIRST_RUN_DAYS) => {
+            Some(format!(
+                "schedule '{}' does not fire until {}; check for a mistyped field",
+                cron.pattern, next
+            ))
         }
+        Ok(_) => None,
+        Err(e) => Some(format!(
+            "schedule '{}' may never fire ({}); check for a mistyped field",
+            cron.pattern, e
+        )),
     }
 }
 
+/// `time` pushed back by a random whole number of seconds in `[0, jitter]`.
+fn add_jitter(time: DateTime<Utc>, jitter: Option<Duration>) -> DateTime<Utc> {
+    use std::hash::{BuildHasher, Hasher};
+
+    let Some(jitter) = jitter.filter(|jitter| !jitter.is_zero()) else {
+        return time;
+    };
+    // Every `RandomState` is freshly keyed, which is random enough to spread runs out
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    let offset = random % jitter.as_secs().saturating_add(1);
+    i64::try_from(offset)
+        .ok()
+        .and_then(chrono::TimeDelta::try_seconds)
+        .and_then(|offset| time.checked_add_signed(offset))
+        .unwrap_or(time)
+}
+
 /// Manages all scheduled tasks and handles their execution
 pub struct TaskScheduler {
     tasks: Arc<Mutex<HashMap<String, ScheduledTask>>>,
+    /// Tasks `add_task` rejected, with the reason, so shells can show them as broken
+    /// instead of leaving them out.
+    failed: Arc<Mutex<HashMap<String, String>>>,
+    /// Keys of tasks currently executing, so a long run is never started twice.
+    in_flight: Arc<Mutex<HashSet<String>>>,
     path: Arc<Mutex<String>>,
     running: Arc<Mutex<bool>>,
+    /// Wakes the loop early (paired with `running`) when tasks change or it is stopped.
+    wake: Arc<Condvar>,
+    /// The loop started by `start`, joined by `stop`.
+    thread: Mutex<Option<JoinHandle<()>>>,
     states: Arc<Mutex<HashMap<String, TaskState>>>,
     state_file: PathBuf,
+    /// Held from snapshot to write, so concurrent saves (the loop and `save_states`)
+    /// land in the order their snapshots were taken.
+    saving: Arc<Mutex<()>>,
+    notifier: Option<SharedNotifier>,
+    tray: Option<SharedTrayUi>,
+    spawner: SharedSpawner,
+    poll_interval: Arc<Mutex<Duration>>,
+    history_size: Arc<Mutex<usize>>,
+    events: EventBus,
 }
 
 impl TaskScheduler {
@@ -254,49 +1007,121 @@ impl TaskScheduler {
         let states = load_task_states(&state_file);
         Self {
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            failed: Arc::new(Mutex::new(HashMap::new())),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
             path: Arc::new(Mutex::new(path)),
             running: Arc::new(Mutex::new(false)),
+            wake: Arc::new(Condvar::new()),
+            thread: Mutex::new(None),
             states: Arc::new(Mutex::new(states)),
             state_file,
+            saving: Arc::new(Mutex::new(())),
+            notifier: None,
+            tray: None,
+            spawner: Arc::new(StdProcessSpawner),
+            poll_interval: Arc::new(Mutex::new(Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS))),
+            history_size: Arc::new(Mutex::new(DEFAULT_HISTORY_SIZE)),
+            events: EventBus::default(),
         }
     }
 
-    /// Add a scheduled task
-    pub fn add_task(&self, key: String, config: &ScheduledTaskConfig) -> Result<(), String> {
+    /// Change the longest the running loop sleeps between checks. The loop normally
+    /// sleeps until the next task is due; this bound catches wall-clock jumps.
+    pub fn set_poll_interval(&self, interval: Duration) {
+        *self.poll_interval.lock().unwrap() = interval;
+        self.wake_loop();
+    }
+
+    /// Change how many runs each task keeps in its history; applied on the next save.
+    pub fn set_history_size(&self, size: usize) {
+        *self.history_size.lock().unwrap() = size;
+    }
+
+    /// Make the running loop re-evaluate its sleep. Taking the `running` lock first
+    /// ensures the notification cannot slip in between its computing and waiting.
+    fn wake_loop(&self) {
+        let _running = self.running.lock().unwrap();
+        self.wake.notify_all();
+    }
+
+    /// Start task commands through `spawner` instead of `std::process` directly.
+    pub fn set_spawner(&mut self, spawner: SharedSpawner) {
+        self.spawner = spawner;
+    }
+
+    /// Notify the user when a task fails to start; without one, failures are only logged.
+    pub fn set_notifier(&mut self, notifier: SharedNotifier) {
+        self.notifier = Some(notifier);
+    }
+
+    /// Ask `tray` to refresh task labels whenever a run finishes.
+    pub fn set_tray_ui(&mut self, tray: SharedTrayUi) {
+        self.tray = Some(tray);
+    }
+
+    /// Subscribe to task start/finish events. Each call returns an independent receiver.
+    pub fn events(&self) -> Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Add a scheduled task. A task whose schedule cannot be parsed is not added,
+    /// but is kept in [`TaskScheduler::failed_tasks`] with the error.
+    pub fn add_task(
+        &self,
+        key: String,
+        config: &ScheduledTaskConfig,
+    ) -> Result<(), SchedulerError> {
         // Check if we have a persisted state for this task
         let states = self.states.lock().unwrap();
         let state = states.get(&key);
 
-        let task = ScheduledTask::new(config, state)?;
+        let task = match ScheduledTask::new(config, state) {
+            Ok(task) => task,
+            Err(e) => {
+                self.failed.lock().unwrap().insert(key, e.to_string());
+                return Err(e);
+            }
+        };
         drop(states);
 
+        self.failed.lock().unwrap().remove(&key);
         let mut tasks = self.tasks.lock().unwrap();
         tasks.insert(key, task);
+        drop(tasks);
+        self.wake_loop();
         Ok(())
     }
 
     /// Atomically replace all scheduled tasks and the PATH used to execute them.
-    pub fn reconfigure(
-        &self,
-        path: String,
-        configs: &[(String, ScheduledTaskConfig)],
-    ) -> Result<(), String> {
+    /// As with `add_task`, a task whose schedule cannot be parsed is left out and
+    /// kept in [`TaskScheduler::failed_tasks`]; the others are still applied.
+    pub fn reconfigure(&self, path: String, configs: &[(String, ScheduledTaskConfig)]) {
         let mut tasks = self.tasks.lock().unwrap();
         let persisted_states = self.states.lock().unwrap();
         let mut new_tasks = HashMap::new();
+        let mut failed = HashMap::new();
 
         for (key, config) in configs {
             let current_state = tasks.get(key).map(|existing| TaskState {
-                last_run: existing.last_run,
-                next_run: if existing.cron_schedule == config.cron_schedule {
+                next_run: if existing.cron_schedule == config.cron_schedule
+                    && existing.timezone == config.timezone
+                {
                     existing.next_run
                 } else {
                     None
                 },
+                ..existing.state()
             });
             let state = current_state.as_ref().or_else(|| persisted_states.get(key));
-            let task = ScheduledTask::new(config, state)?;
-            new_tasks.insert(key.clone(), task);
+            match ScheduledTask::new(config, state) {
+                Ok(task) => {
+                    new_tasks.insert(key.clone(), task);
+                }
+                Err(e) => {
+                    error!("Failed to add scheduled task '{key}': {e}");
+                    failed.insert(key.clone(), e.to_string());
+                }
+            }
         }
         drop(persisted_states);
 
@@ -305,26 +1130,16 @@ impl TaskScheduler {
         *current_path = path;
         drop(current_path);
         drop(tasks);
+        *self.failed.lock().unwrap() = failed;
         self.save_states();
-        Ok(())
+        self.wake_loop();
     }
 
     /// Save the current task states to disk
     pub fn save_states(&self) {
-        let tasks = self.tasks.lock().unwrap();
-        let mut states_map = HashMap::new();
-
-        for (key, task) in tasks.iter() {
-            states_map.insert(
-                key.clone(),
-                TaskState {
-                    last_run: task.last_run,
-                    next_run: task.next_run,
-                },
-            );
-        }
-
-        drop(tasks);
+        let _saving = self.saving.lock().unwrap();
+        let history_size = *self.history_size.lock().unwrap();
+        let states_map = collect_states(&mut self.tasks.lock().unwrap(), history_size);
 
         // Update the states in memory
         let mut states = self.states.lock().unwrap();
@@ -335,18 +1150,105 @@ impl TaskScheduler {
         save_task_states(&self.state_file, &states_map);
     }
 
+    /// Remove task `key`, killing a run of it that is still waiting on its command.
+    /// Its saved state goes too, so adding the key again starts fresh.
+    pub fn remove_task(&self, key: &str) -> Result<(), SchedulerError> {
+        if self.failed.lock().unwrap().remove(key).is_some() {
+            return Ok(());
+        }
+        let task = self
+            .tasks
+            .lock()
+            .unwrap()
+            .remove(key)
+            .ok_or_else(|| SchedulerError::TaskNotFound(key.to_string()))?;
+        task.stop.store(true, Ordering::SeqCst);
+        info!("Removed task '{}'", task.name);
+
+        self.save_states();
+        self.refresh_tray();
+        self.wake_loop();
+        Ok(())
+    }
+
     /// Get a copy of a specific task's state
     pub fn get_task(&self, key: &str) -> Option<ScheduledTask> {
         let tasks = self.tasks.lock().unwrap();
         tasks.get(key).cloned()
     }
 
+    /// Time left until task `key` is due, for countdowns; see
+    /// [`ScheduledTask::time_until_next`]. `None` for unknown tasks too.
+    pub fn time_until_next(&self, key: &str) -> Option<Duration> {
+        let tasks = self.tasks.lock().unwrap();
+        tasks.get(key)?.time_until_next(&Utc::now())
+    }
+
+    /// What task `key` runs, for pasting into a terminal; `None` for unknown tasks.
+    pub fn effective_command_line(&self, key: &str) -> Option<String> {
+        let path = self.path.lock().unwrap().clone();
+        let tasks = self.tasks.lock().unwrap();
+        Some(tasks.get(key)?.effective_command_line(&path))
+    }
+
+    /// Recent runs of task `key`, newest first; empty for unknown tasks.
+    pub fn get_history(&self, key: &str) -> Vec<RunRecord> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|task| task.history.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Get all tasks
     pub fn get_all_tasks(&self) -> HashMap<String, ScheduledTask> {
         let tasks = self.tasks.lock().unwrap();
         tasks.clone()
     }
 
+    /// Tasks that could not be added, e.g. because of an invalid `cron`, with why.
+    /// They never run; shells show them disabled so they are not mistaken for
+    /// scheduled ones.
+    pub fn failed_tasks(&self) -> HashMap<String, String> {
+        self.failed.lock().unwrap().clone()
+    }
+
+    /// Pause or resume task `key`'s schedule without touching the config. The
+    /// choice is saved with the task state; a task resumed after its next run
+    /// went by is rescheduled from now instead of running at once.
+    pub fn set_enabled(&self, key: &str, enabled: bool) -> Result<(), SchedulerError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let task = tasks
+            .get_mut(key)
+            .ok_or_else(|| SchedulerError::TaskNotFound(key.to_string()))?;
+        if task.enabled == enabled {
+            return Ok(());
+        }
+        task.enabled = enabled;
+        let now = Utc::now();
+        if enabled && task.next_run.is_some_and(|next_run| next_run <= now) {
+            task.next_run = ScheduledTask::calculate_next_run(
+                &task.schedule,
+                &task.window,
+                task.jitter,
+                &now,
+                &task.name,
+            );
+        }
+        info!(
+            "Task '{}' {}",
+            task.name,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        drop(tasks);
+
+        self.save_states();
+        self.refresh_tray();
+        self.wake_loop();
+        Ok(())
+    }
+
     /// Start the scheduler background thread
     pub fn start(&self) {
         let mut running = self.running.lock().unwrap();
@@ -357,89 +1259,210 @@ impl TaskScheduler {
         *running = true;
         drop(running);
 
+        // Marking startup tasks due folds them into the first loop pass, so a task
+        // that was also missed while the app was closed still runs only once.
+        let now = Utc::now();
+        for task in self.tasks.lock().unwrap().values_mut() {
+            if task.runs_at_startup() {
+                info!("Task '{}' runs at startup", task.name);
+                task.next_run = Some(now);
+            }
+        }
+
         let tasks = Arc::clone(&self.tasks);
+        let in_flight = Arc::clone(&self.in_flight);
         let path = Arc::clone(&self.path);
         let running = Arc::clone(&self.running);
+        let wake = Arc::clone(&self.wake);
         let states = Arc::clone(&self.states);
         let state_file = self.state_file.clone();
-
-        thread::spawn(move || {
+        let saving = Arc::clone(&self.saving);
+        let notifier = self.notifier.clone();
+        let tray = self.tray.clone();
+        let spawner = self.spawner.clone();
+        let events = self.events.clone();
+        let poll_interval = Arc::clone(&self.poll_interval);
+        let history_size = Arc::clone(&self.history_size);
+
+        let handle = thread::spawn(move || {
             info!("Task scheduler started");
 
             while *running.lock().unwrap() {
-                let now = Local::now();
-                let mut tasks_guard = tasks.lock().unwrap();
-                let mut states_changed = false;
-
-                for (key, task) in tasks_guard.iter_mut() {
-                    if task.should_run(&now) {
-                        debug!("Task '{}' is due to run", key);
-                        let path = path.lock().unwrap().clone();
-                        if let Err(e) = task.execute(&path) {
-                            error!("Task '{}' execution failed: {}", key, e);
-                        } else {
-                            states_changed = true;
-                        }
+                let now = Utc::now();
+                let mut skipped = false;
+                let due: Vec<(String, String)> = {
+                    let mut tasks = tasks.lock().unwrap();
+                    for task in tasks.values_mut() {
+                        skipped |= task.skip_outside_window(&now);
                     }
+                    let in_flight = in_flight.lock().unwrap();
+                    tasks
+                        .iter()
+                        .filter(|(key, task)| task.should_run(&now) && !in_flight.contains(*key))
+                        .map(|(key, task)| (key.clone(), task.name.clone()))
+                        .collect()
+                };
+                // Failed runs still advance next_run and may record an exit code.
+                let states_changed = !due.is_empty() || skipped;
+
+                for (key, name) in due {
+                    debug!("Task '{}' is due to run", key);
+                    let path = path.lock().unwrap().clone();
+                    let Some(result) = run_unlocked(
+                        &tasks,
+                        &in_flight,
+                        &key,
+                        &path,
+                        spawner.as_ref(),
+                        &events,
+                        Some(&now),
+                        false,
+                    ) else {
+                        continue;
+                    };
+                    if let Err(e) = &result {
+                        error!("Task '{}' execution failed: {}", key, e);
+                    }
+                    report_outcome(notifier.as_ref(), &tasks, &key, &name, &result);
+                    run_chain(
+                        &tasks,
+                        &in_flight,
+                        &key,
+                        result.is_ok(),
+                        &path,
+                        spawner.as_ref(),
+                        &events,
+                        notifier.as_ref(),
+                    );
                 }
 
-                drop(tasks_guard);
-
                 // Save states if any task was executed
                 if states_changed {
-                    let tasks = tasks.lock().unwrap();
-                    let mut states_map = HashMap::new();
-
-                    for (key, task) in tasks.iter() {
-                        states_map.insert(
-                            key.clone(),
-                            TaskState {
-                                last_run: task.last_run,
-                                next_run: task.next_run,
-                            },
-                        );
-                    }
-
-                    drop(tasks);
+                    let saving = saving.lock().unwrap();
+                    let history_size = *history_size.lock().unwrap();
+                    let states_map = collect_states(&mut tasks.lock().unwrap(), history_size);
 
                     let mut states_guard = states.lock().unwrap();
                     *states_guard = states_map.clone();
                     drop(states_guard);
 
                     save_task_states(&state_file, &states_map);
+                    drop(saving);
+                    if let Some(tray) = &tray {
+                        tray.refresh();
+                    }
                 }
 
-                // Check every 30 seconds
-                thread::sleep(Duration::from_secs(30));
+                // Sleep until the earliest task is due, bounded by the poll interval.
+                let running_guard = running.lock().unwrap();
+                if !*running_guard {
+                    break;
+                }
+                let cap = *poll_interval.lock().unwrap();
+                let delay = time_until_next_due(&tasks.lock().unwrap(), &Utc::now())
+                    .map_or(cap, |until| until.min(cap));
+                debug!("Scheduler sleeping for {delay:?}");
+                let _ = wake.wait_timeout(running_guard, delay).unwrap();
             }
 
             info!("Task scheduler stopped");
         });
+        *self.thread.lock().unwrap() = Some(handle);
     }
 
-    /// Stop the scheduler
+    /// Stop the scheduler and wait for its thread to exit, so a scheduler started
+    /// in its place never runs alongside it. A task the loop is running at the
+    /// time gets `STOP_GRACE_PERIOD` to finish and is then killed, so quitting
+    /// never hangs on a long `wait_for_completion` run.
     pub fn stop(&self) {
+        self.stop_within(STOP_GRACE_PERIOD);
+    }
+
+    fn stop_within(&self, grace: Duration) {
         let mut running = self.running.lock().unwrap();
         *running = false;
+        self.wake.notify_all();
+        drop(running);
         info!("Stopping task scheduler");
-    }
 
-    /// Manually trigger a task to run now
-    pub fn run_task_now(&self, key: &str) -> Result<(), String> {
-        let mut tasks = self.tasks.lock().unwrap();
-        let path = self.path.lock().unwrap().clone();
-        let result = if let Some(task) = tasks.get_mut(key) {
-            task.execute(&path)
-        } else {
-            Err(format!("Task '{}' not found", key))
+        let Some(handle) = self.thread.lock().unwrap().take() else {
+            return;
         };
-
-        drop(tasks);
-
-        // Save states after manual execution
-        if result.is_ok() {
-            self.save_states();
+        let deadline = Instant::now() + grace;
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
         }
+        let killed = !handle.is_finished();
+        if killed {
+            warn!("A scheduled task is still running after {grace:?}; killing it");
+            self.set_stop_flags(true);
+        }
+        if handle.join().is_err() {
+            error!("Task scheduler thread panicked");
+        }
+        if killed {
+            // The runs are over; a restarted loop must be able to run the tasks again.
+            self.set_stop_flags(false);
+        }
+    }
+
+    fn set_stop_flags(&self, stop: bool) {
+        for task in self.tasks.lock().unwrap().values() {
+            task.stop.store(stop, Ordering::SeqCst);
+        }
+    }
+
+    /// Manually trigger a task to run now
+    pub fn run_task_now(&self, key: &str) -> Result<(), SchedulerError> {
+        self.run_now(key, false)
+    }
+
+    /// Manually trigger a task and wait for it to exit, so a non-zero exit code
+    /// surfaces as an error even for fire-and-forget tasks. Blocks the caller
+    /// for the length of the run; shells should call it off the UI thread.
+    pub fn run_task_now_blocking(&self, key: &str) -> Result<(), SchedulerError> {
+        self.run_now(key, true)
+    }
+
+    fn run_now(&self, key: &str, wait: bool) -> Result<(), SchedulerError> {
+        if !self.tasks.lock().unwrap().contains_key(key) {
+            return Err(SchedulerError::TaskNotFound(key.to_string()));
+        }
+        let path = self.path.lock().unwrap().clone();
+        let result = run_unlocked(
+            &self.tasks,
+            &self.in_flight,
+            key,
+            &path,
+            self.spawner.as_ref(),
+            &self.events,
+            None,
+            wait,
+        )
+        .unwrap_or_else(|| Err(SchedulerError::AlreadyRunning(key.to_string())));
+
+        // Save states after manual execution, including failed runs' exit codes
+        let name = self
+            .tasks
+            .lock()
+            .unwrap()
+            .get(key)
+            .map_or_else(|| key.to_string(), |task| task.name.clone());
+        report_outcome(self.notifier.as_ref(), &self.tasks, key, &name, &result);
+        if !matches!(result, Err(SchedulerError::AlreadyRunning(_))) {
+            run_chain(
+                &self.tasks,
+                &self.in_flight,
+                key,
+                result.is_ok(),
+                &path,
+                self.spawner.as_ref(),
+                &self.events,
+                self.notifier.as_ref(),
+            );
+        }
+        self.save_states();
+        self.refresh_tray();
 
         result
     }
@@ -447,16 +1470,20 @@ impl TaskScheduler {
     /// Check for and run any missed scheduled tasks
     /// This is useful after the system wakes from sleep
     pub fn check_and_run_missed_tasks(&self) {
-        let now = Local::now();
+        let now = Utc::now();
         let mut tasks = self.tasks.lock().unwrap();
-        let mut any_task_run = false;
+        let mut missed = Vec::new();
+        let mut skipped = false;
+        for task in tasks.values_mut() {
+            skipped |= task.skip_outside_window(&now);
+        }
 
         info!(
             "Checking for missed scheduled tasks (current time: {})",
             now
         );
 
-        for (key, task) in tasks.iter_mut() {
+        for (key, task) in tasks.iter() {
             info!(
                 "Task '{}': schedule={}, next_run={:?}, last_run={:?}",
                 key, task.cron_schedule, task.next_run, task.last_run
@@ -466,7 +1493,9 @@ impl TaskScheduler {
             // 1. It has a next_run time scheduled
             // 2. That next_run time is in the past (we're past when it should have run)
             // 3. Either it has never run, or the last run was before the scheduled next_run
-            if let Some(next_run) = task.next_run {
+            if !task.enabled {
+                info!("Task '{}' is disabled", key);
+            } else if let Some(next_run) = task.next_run {
                 let is_overdue = now >= next_run;
                 let was_not_run_yet = task.last_run.is_none() || task.last_run.unwrap() < next_run;
 
@@ -484,12 +1513,7 @@ impl TaskScheduler {
                         key, next_run
                     );
 
-                    let path = self.path.lock().unwrap().clone();
-                    if let Err(e) = task.execute(&path) {
-                        error!("Failed to run missed task '{}': {}", key, e);
-                    } else {
-                        any_task_run = true;
-                    }
+                    missed.push((key.clone(), task.name.clone()));
                 }
             } else {
                 info!("Task '{}' has no next_run scheduled", key);
@@ -498,11 +1522,231 @@ impl TaskScheduler {
 
         drop(tasks);
 
-        // Save states if any task was run
-        if any_task_run {
+        for (key, name) in &missed {
+            let path = self.path.lock().unwrap().clone();
+            let result = run_unlocked(
+                &self.tasks,
+                &self.in_flight,
+                key,
+                &path,
+                self.spawner.as_ref(),
+                &self.events,
+                Some(&now),
+                false,
+            );
+            let Some(result) = result else {
+                continue;
+            };
+            if let Err(e) = &result {
+                error!("Failed to run missed task '{}': {}", key, e);
+            }
+            report_outcome(self.notifier.as_ref(), &self.tasks, key, name, &result);
+            run_chain(
+                &self.tasks,
+                &self.in_flight,
+                key,
+                result.is_ok(),
+                &path,
+                self.spawner.as_ref(),
+                &self.events,
+                self.notifier.as_ref(),
+            );
+        }
+
+        // Save states if any task was run or skipped
+        if !missed.is_empty() || skipped {
             self.save_states();
+            self.refresh_tray();
+        }
+    }
+
+    fn refresh_tray(&self) {
+        if let Some(tray) = &self.tray {
+            tray.refresh();
+        }
+    }
+}
+
+/// Execute the task `key` on a copy, so the tasks lock is not held while it runs,
+/// then record the outcome on the live task. Returns `None` without running
+/// anything if the task is unknown, already running, or (with `due_at`) no longer due.
+/// `wait` waits for the command to exit even if the task is fire-and-forget.
+#[allow(clippy::too_many_arguments)]
+fn run_unlocked(
+    tasks: &Mutex<HashMap<String, ScheduledTask>>,
+    in_flight: &Mutex<HashSet<String>>,
+    key: &str,
+    path: &str,
+    spawner: &dyn ProcessSpawner,
+    events: &EventBus,
+    due_at: Option<&DateTime<Utc>>,
+    wait: bool,
+) -> Option<Result<(), SchedulerError>> {
+    if !in_flight.lock().unwrap().insert(key.to_string()) {
+        debug!("Task '{}' is already running; skipping", key);
+        return None;
+    }
+    // Re-check under the lock: another caller may have just run a due task.
+    let task = tasks
+        .lock()
+        .unwrap()
+        .get(key)
+        .filter(|task| due_at.is_none_or(|now| task.should_run(now)))
+        .cloned();
+    let Some(mut task) = task else {
+        in_flight.lock().unwrap().remove(key);
+        return None;
+    };
+    task.wait_for_completion |= wait;
+
+    events.emit(Event::TaskStarted {
+        key: key.to_string(),
+    });
+    let (timestamp, started) = (Utc::now(), Instant::now());
+    let result = task.execute(path, spawner);
+    let waited = task.wait_for_completion || task.timeout.is_some();
+    let exit_code = task.last_exit_code.filter(|_| waited);
+    task.history.push_back(RunRecord {
+        timestamp,
+        exit_code,
+        duration_ms: waited.then(|| started.elapsed().as_millis() as u64),
+        success: result.is_ok(),
+        timed_out: task.last_timed_out,
+    });
+    events.emit(Event::TaskFinished {
+        key: key.to_string(),
+        exit_code,
+        error: result.as_ref().err().map(ToString::to_string),
+    });
+
+    if let Some(current) = tasks.lock().unwrap().get_mut(key) {
+        current.record_run(&task);
+    }
+    in_flight.lock().unwrap().remove(key);
+    Some(result)
+}
+
+/// After a run of task `key` ended (`succeeded` or not), run the tasks in its `then`
+/// one after another, and theirs in turn. Each task runs at most once per chain, so
+/// a cycle the config check missed ends instead of looping.
+#[allow(clippy::too_many_arguments)]
+fn run_chain(
+    tasks: &Mutex<HashMap<String, ScheduledTask>>,
+    in_flight: &Mutex<HashSet<String>>,
+    key: &str,
+    succeeded: bool,
+    path: &str,
+    spawner: &dyn ProcessSpawner,
+    events: &EventBus,
+    notifier: Option<&SharedNotifier>,
+) {
+    let mut seen = HashSet::from([key.to_string()]);
+    let mut pending = VecDeque::from([(key.to_string(), succeeded)]);
+    while let Some((from, succeeded)) = pending.pop_front() {
+        let then = match tasks.lock().unwrap().get(&from) {
+            Some(task) if succeeded || task.then_on_failure => task.then.clone(),
+            _ => continue,
+        };
+        for next in then {
+            if !seen.insert(next.clone()) {
+                warn!(
+                    "Task '{}' chains back to '{}'; not running it again",
+                    from, next
+                );
+                continue;
+            }
+            info!("Task '{}' finished; running '{}' next", from, next);
+            let Some(result) =
+                run_unlocked(tasks, in_flight, &next, path, spawner, events, None, false)
+            else {
+                warn!(
+                    "Task '{}' is unknown or already running; chain stops there",
+                    next
+                );
+                continue;
+            };
+            if let Err(e) = &result {
+                error!("Chained task '{}' failed: {}", next, e);
+            }
+            let name = tasks
+                .lock()
+                .unwrap()
+                .get(&next)
+                .map_or_else(|| next.clone(), |task| task.name.clone());
+            report_outcome(notifier, tasks, &next, &name, &result);
+            pending.push_back((next, result.is_ok()));
+        }
+    }
+}
+
+/// Trim each task's history to `history_size` and snapshot the states to persist.
+fn collect_states(
+    tasks: &mut HashMap<String, ScheduledTask>,
+    history_size: usize,
+) -> HashMap<String, TaskState> {
+    tasks
+        .iter_mut()
+        .map(|(key, task)| {
+            task.prune_history(history_size);
+            (key.clone(), task.state())
+        })
+        .collect()
+}
+
+/// Time left until the earliest scheduled `next_run`; zero if one is already due.
+fn time_until_next_due(
+    tasks: &HashMap<String, ScheduledTask>,
+    now: &DateTime<Utc>,
+) -> Option<Duration> {
+    tasks
+        .values()
+        .filter(|task| task.enabled)
+        .filter_map(|task| task.next_run)
+        .min()
+        .map(|next| (next - *now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Surface how a run of task `key` ended, if the shell provided a notifier:
+/// failures always, successes only for tasks with `notify_on_complete`.
+fn report_outcome(
+    notifier: Option<&SharedNotifier>,
+    tasks: &Mutex<HashMap<String, ScheduledTask>>,
+    key: &str,
+    name: &str,
+    result: &Result<(), SchedulerError>,
+) {
+    let Some(notifier) = notifier else {
+        return;
+    };
+    if let Err(error) = result {
+        if matches!(error, SchedulerError::Stopped(_)) {
+            // Removed on purpose; nothing for the user to fix.
+            return;
         }
+        notifier.error(
+            &format!("Scheduled task '{name}' failed"),
+            &error.to_string(),
+        );
+        return;
     }
+
+    let (title, body) = {
+        let tasks = tasks.lock().unwrap();
+        let Some(task) = tasks.get(key).filter(|task| task.notify_on_complete) else {
+            return;
+        };
+        let title = match task.last_exit_code {
+            Some(code) => format!("{} finished (exit {code})", task.name),
+            None => format!("{} finished", task.name),
+        };
+        let body = task
+            .history
+            .back()
+            .map(format_run_record)
+            .unwrap_or_default();
+        (title, body)
+    };
+    notifier.info(&title, &body);
 }
 
 impl Drop for TaskScheduler {
@@ -511,56 +1755,479 @@ impl Drop for TaskScheduler {
     }
 }
 
-/// Convert a cron pattern to a human-readable description
-pub fn cron_to_human_readable(cron_pattern: &str) -> String {
+/// Convert a cron pattern to a human-readable description, naming the
+/// task's timezone when it has one (e.g. "Every day at 9:00 (America/New_York)")
+pub fn cron_to_human_readable(cron_pattern: &str, timezone: Option<&str>) -> String {
+    let description = describe_cron(cron_pattern);
+    let follows_clock = !matches!(
+        Schedule::parse(cron_pattern),
+        Ok(Schedule::Every(_) | Schedule::Reboot)
+    );
+    match timezone {
+        Some(tz) if follows_clock => format!("{description} ({tz})"),
+        _ => description,
+    }
+}
+
+fn describe_cron(cron_pattern: &str) -> String {
+    match cron_pattern.trim() {
+        "@reboot" => return "At startup".to_string(),
+        "@hourly" => return "Every hour".to_string(),
+        "@daily" | "@midnight" => return "Every day at midnight".to_string(),
+        "@weekly" => return "Every Sunday at midnight".to_string(),
+        "@monthly" => return "Monthly on the 1st at midnight".to_string(),
+        "@yearly" | "@annually" => return "Every January 1st at midnight".to_string(),
+        _ => {}
+    }
+    if let Some(spec) = cron_pattern.strip_prefix(EVERY_PREFIX) {
+        return match humantime::parse_duration(spec.trim()) {
+            Ok(interval) => describe_interval(interval),
+            Err(_) => cron_pattern.to_string(),
+        };
+    }
+
     match Cron::from_str(cron_pattern) {
-        Ok(cron) => {
-            // Try to create a more user-friendly description
-            let pattern = &cron.pattern;
+        // Fall back to the normalized pattern for anything the renderer does not cover.
+        Ok(cron) => describe_cron_fields(cron_pattern).unwrap_or_else(|| cron.pattern.to_string()),
+        Err(_) => cron_pattern.to_string(),
+    }
+}
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// One field of a five-field cron expression, as far as the describer needs it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CronField {
+    /// `*` or `?`.
+    Any,
+    /// `*/n`.
+    Step(u32),
+    /// `a-b`.
+    Range(u32, u32),
+    /// A single value, a list, or a stepped range, expanded and sorted.
+    Values(Vec<u32>),
+}
+
+impl CronField {
+    /// Parse a field whose values may also be written as names (`MON`, `JAN`);
+    /// `first` is the value of `names[0]`. Returns `None` for syntax the
+    /// describer does not handle, such as `L` or `#`.
+    fn parse(field: &str, names: &[&str], first: u32) -> Option<Self> {
+        let value = |text: &str| {
+            text.parse::<u32>().ok().or_else(|| {
+                names
+                    .iter()
+                    .position(|name| name[..3].eq_ignore_ascii_case(text))
+                    .map(|index| index as u32 + first)
+            })
+        };
+
+        match field {
+            "*" | "?" => return Some(Self::Any),
+            _ => {}
+        }
+        if let Some(step) = field.strip_prefix("*/") {
+            return step.parse().ok().filter(|step| *step > 0).map(Self::Step);
+        }
+        if let Some((start, end)) = field.split_once('-')
+            && !field.contains([',', '/'])
+        {
+            let (start, end) = (value(start)?, value(end)?);
+            return (start <= end).then_some(Self::Range(start, end));
+        }
 
-            // Simple pattern matching for common cases
-            if cron_pattern == "0 * * * *" {
-                return "Every hour".to_string();
+        let mut values = Vec::new();
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<usize>().ok().filter(|s| *s > 0)?),
+                None => (part, 1),
+            };
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                None if step == 1 => (value(range)?, value(range)?),
+                None => return None,
+            };
+            if start > end {
+                return None;
             }
-            if cron_pattern == "0 0 * * *" {
-                return "Every day at midnight".to_string();
+            values.extend((start..=end).step_by(step));
+        }
+        values.sort_unstable();
+        values.dedup();
+        Some(Self::Values(values))
+    }
+
+    /// The values this field matches, if it is restricted to specific ones.
+    fn values(&self) -> Option<Vec<u32>> {
+        match self {
+            Self::Range(start, end) => Some((*start..=*end).collect()),
+            Self::Values(values) => Some(values.clone()),
+            Self::Any | Self::Step(_) => None,
+        }
+    }
+}
+
+/// What a cron expression's minute and hour fields amount to.
+enum CronTime {
+    /// Fixed times of day, e.g. "9:00 and 17:00".
+    At(String),
+    /// A repeating phrase, e.g. "Every 15 minutes" or "Every hour from 9:00 to 17:00".
+    Repeating(String),
+}
+
+/// Render a standard five-field cron expression field by field, e.g.
+/// "Every 15 minutes", "At 9:00 on weekdays", or "At 8:00 on the 1st of every month".
+/// Returns `None` for combinations that would not read better than the pattern.
+fn describe_cron_fields(pattern: &str) -> Option<String> {
+    let fields: Vec<&str> = pattern.split_whitespace().collect();
+    let [minute, hour, day, month, weekday] = fields.as_slice() else {
+        return None;
+    };
+    let minute = CronField::parse(minute, &[], 0)?;
+    let hour = CronField::parse(hour, &[], 0)?;
+    let day = CronField::parse(day, &[], 1)?;
+    let month = CronField::parse(month, &MONTHS, 1)?;
+    let weekday = CronField::parse(weekday, &WEEKDAYS, 0)?;
+
+    let time = describe_cron_time(&minute, &hour)?;
+
+    // Cron ORs day-of-month with day-of-week when both are set; leave that as cron.
+    let days = match (&day, &weekday) {
+        (CronField::Any, CronField::Any) => None,
+        (CronField::Any, weekday) => Some(describe_weekdays(weekday.values()?)),
+        (day, CronField::Any) => {
+            let days = day.values()?;
+            Some(format!("on the {}", describe_values(&days, ordinal)))
+        }
+        _ => return None,
+    };
+    let months = match &month {
+        CronField::Any => None,
+        month => Some(describe_values(&month.values()?, |month| {
+            MONTHS[(month as usize).clamp(1, 12) - 1].to_string()
+        })),
+    };
+    let on_month_days = matches!(day, CronField::Range(..) | CronField::Values(_));
+
+    let mut description = match (time, &days) {
+        (CronTime::At(at), None) => format!("Every day at {at}"),
+        (CronTime::At(at), Some(days)) => format!("At {at} {days}"),
+        (CronTime::Repeating(every), None) => every,
+        (CronTime::Repeating(every), Some(days)) => format!("{every} {days}"),
+    };
+    match (months, on_month_days) {
+        (None, true) => description.push_str(" of every month"),
+        (Some(months), true) => description.push_str(&format!(" of {months}")),
+        (Some(months), false) => description.push_str(&format!(" in {months}")),
+        (None, false) => {}
+    }
+    Some(description)
+}
+
+fn describe_cron_time(minute: &CronField, hour: &CronField) -> Option<CronTime> {
+    use CronField::*;
+
+    let clock = |hour: u32, minute: u32| match (hour, minute) {
+        (0, 0) => "midnight".to_string(),
+        _ => format!("{hour}:{minute:02}"),
+    };
+    let every_minutes = |step: u32| match step {
+        1 => "Every minute".to_string(),
+        step => format!("Every {step} minutes"),
+    };
+
+    let time = match (minute, hour) {
+        (Any, Any) => CronTime::Repeating(every_minutes(1)),
+        (Step(step), Any) => CronTime::Repeating(every_minutes(*step)),
+        (Any, Range(start, end)) => CronTime::Repeating(format!(
+            "{} from {}:00 to {end}:59",
+            every_minutes(1),
+            start
+        )),
+        (Step(step), Range(start, end)) => CronTime::Repeating(format!(
+            "{} from {}:00 to {end}:59",
+            every_minutes(*step),
+            start
+        )),
+        (Values(minutes), hour) if minutes.len() == 1 && hour.values().is_none() => {
+            let minute = minutes[0];
+            let every = match hour {
+                Step(step) if *step > 1 => format!("Every {step} hours"),
+                _ => "Every hour".to_string(),
+            };
+            match minute {
+                0 => CronTime::Repeating(every),
+                minute => CronTime::Repeating(format!("{every} at {minute} minutes past")),
             }
-            if cron_pattern.starts_with("0 ") && cron_pattern.matches(' ').count() == 4 {
-                // Pattern like "0 10 * * *" (at specific hour in local time)
-                let parts: Vec<&str> = cron_pattern.split_whitespace().collect();
-                if parts.len() == 5
-                    && parts[1].parse::<u32>().is_ok()
-                    && parts[2] == "*"
-                    && parts[3] == "*"
-                    && parts[4] == "*"
-                {
-                    let hour = parts[1].parse::<u32>().unwrap();
-                    return format!("Every day at {}:00", hour);
-                }
+        }
+        (Values(minutes), Range(start, end)) if minutes.len() == 1 => {
+            let minute = minutes[0];
+            CronTime::Repeating(format!(
+                "Every hour from {} to {}",
+                clock(*start, minute),
+                clock(*end, minute)
+            ))
+        }
+        (Values(minutes), hour) => {
+            let hours = hour.values()?;
+            // Beyond a handful of times the pattern itself is easier to read.
+            if minutes.len() * hours.len() > 4 {
+                return None;
             }
+            let times: Vec<String> = hours
+                .iter()
+                .flat_map(|hour| minutes.iter().map(|minute| clock(*hour, *minute)))
+                .collect();
+            CronTime::At(join_list(&times))
+        }
+        _ => return None,
+    };
+    Some(time)
+}
 
-            // Fall back to the pattern string
-            pattern.to_string()
+/// "on weekdays", "on weekends", "on Mondays and Thursdays", or "on Tuesday through Thursday".
+fn describe_weekdays(mut days: Vec<u32>) -> String {
+    // Both 0 and 7 mean Sunday.
+    for day in days.iter_mut() {
+        *day %= 7;
+    }
+    days.sort_unstable();
+    days.dedup();
+    let name = |day: u32| WEEKDAYS[day as usize].to_string();
+    match days.as_slice() {
+        [1, 2, 3, 4, 5] => "on weekdays".to_string(),
+        [0, 6] => "on weekends".to_string(),
+        days if is_run(days) => format!("on {}", describe_values(days, name)),
+        days => {
+            let plurals: Vec<String> = days.iter().map(|day| format!("{}s", name(*day))).collect();
+            format!("on {}", join_list(&plurals))
         }
-        Err(_) => cron_pattern.to_string(),
     }
 }
 
-/// Format a DateTime for display
-pub fn format_last_run(last_run: &Option<DateTime<Local>>) -> String {
+/// Whether `values` (sorted) are three or more consecutive numbers.
+fn is_run(values: &[u32]) -> bool {
+    values.len() >= 3 && values.windows(2).all(|pair| pair[1] == pair[0] + 1)
+}
+
+/// List `values` by name, collapsing a run into "first through last".
+fn describe_values(values: &[u32], name: impl Fn(u32) -> String) -> String {
+    match values {
+        [first, .., last] if is_run(values) => format!("{} through {}", name(*first), name(*last)),
+        _ => join_list(&values.iter().map(|value| name(*value)).collect::<Vec<_>>()),
+    }
+}
+
+/// "a", "a and b", "a, b and c".
+fn join_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+/// Describe a fixed interval in the largest whole unit, e.g. "Every 15 minutes".
+fn describe_interval(interval: Duration) -> String {
+    let secs = interval.as_secs().max(1);
+    let (count, unit) = [(86_400, "day"), (3_600, "hour"), (60, "minute")]
+        .into_iter()
+        .find(|(unit_secs, _)| secs.is_multiple_of(*unit_secs))
+        .map_or((secs, "second"), |(unit_secs, unit)| {
+            (secs / unit_secs, unit)
+        });
+    if count == 1 {
+        format!("Every {}", unit)
+    } else {
+        format!("Every {} {}s", count, unit)
+    }
+}
+
+/// Whether the label helpers below show a 12-hour clock; see [`set_time_format`].
+static TWELVE_HOUR_CLOCK: AtomicBool = AtomicBool::new(false);
+
+/// Choose the clock for every label formatted from now on. Shells call it through
+/// [`Config::apply_time_format`](crate::config::Config::apply_time_format).
+pub fn set_time_format(format: TimeFormat) {
+    TWELVE_HOUR_CLOCK.store(format == TimeFormat::TwelveHour, Ordering::Relaxed);
+}
+
+fn time_format() -> TimeFormat {
+    if TWELVE_HOUR_CLOCK.load(Ordering::Relaxed) {
+        TimeFormat::TwelveHour
+    } else {
+        TimeFormat::TwentyFourHour
+    }
+}
+
+/// Format a stored (UTC) run time for display in the local timezone
+pub fn format_last_run(last_run: &Option<DateTime<Utc>>) -> String {
     match last_run {
-        Some(dt) => format_relative_datetime(dt),
+        Some(dt) => format_datetime(&dt.with_timezone(&Local)),
         None => "Never".to_string(),
     }
 }
 
-/// Human-friendly relative datetime like "tomorrow at 10:00" or
-/// "in 3 weeks (on Dec 21st, 2025 at 10:00)".
-fn format_relative_datetime(dt: &DateTime<Local>) -> String {
-    let now = Local::now();
+/// What was loaded, for the startup notification, e.g. "3 tunnels, 2 scheduled
+/// tasks. Next: Daily Backup, Tomorrow at 06:00". Paused tasks are not "next".
+pub fn startup_summary(tunnel_count: usize, scheduler: &TaskScheduler) -> String {
+    let tasks = scheduler.get_all_tasks();
+    let plural = |count: usize, noun: &str| match count {
+        1 => format!("1 {noun}"),
+        count => format!("{count} {noun}s"),
+    };
+    let counts = format!(
+        "{}, {}",
+        plural(tunnel_count, "tunnel"),
+        plural(tasks.len(), "scheduled task")
+    );
+    match next_scheduled(&tasks) {
+        Some((time, name)) => format!("{counts}. Next: {name}, {}", format_datetime(&time)),
+        None => counts,
+    }
+}
+
+/// Live status for the tray icon's tooltip, e.g. "2 tunnels active · next task 06:00".
+/// The next run shows only its time when it is less than a day away.
+pub fn tray_tooltip(active_tunnels: usize, scheduler: &TaskScheduler) -> String {
+    let tunnels = match active_tunnels {
+        0 => "No tunnels active".to_string(),
+        1 => "1 tunnel active".to_string(),
+        count => format!("{count} tunnels active"),
+    };
+    match next_scheduled(&scheduler.get_all_tasks()) {
+        Some((time, _)) => {
+            let clock = time.format(time_format().pattern());
+            if time - Local::now() < chrono::Duration::days(1) {
+                format!("{tunnels} · next task {clock}")
+            } else {
+                format!("{tunnels} · next task {} {clock}", time.format("%b %-d"))
+            }
+        }
+        None => tunnels,
+    }
+}
+
+/// The soonest run among tasks that are not paused, with the task's name.
+fn next_scheduled(tasks: &HashMap<String, ScheduledTask>) -> Option<(DateTime<Local>, &str)> {
+    tasks
+        .values()
+        .filter(|task| task.enabled)
+        .filter_map(|task| Some((task.upcoming(1).into_iter().next()?, task.name.as_str())))
+        .min()
+}
+
+/// Format a task's next run, or "Disabled" while its schedule is paused
+pub fn format_next_run(task: &ScheduledTask) -> String {
+    if task.enabled {
+        format_last_run(&task.next_run)
+    } else {
+        "Disabled".to_string()
+    }
+}
+
+/// Format a task's last run, calling out a non-zero exit code
+pub fn format_last_result(task: &ScheduledTask) -> String {
+    if task.last_timed_out {
+        return format!("timed out, {}", format_last_run(&task.last_run));
+    }
+    match task.last_exit_code {
+        Some(code) if code != 0 => {
+            format!(
+                "failed (exit {}), {}",
+                code,
+                format_last_run(&task.last_run)
+            )
+        }
+        _ => format_last_run(&task.last_run),
+    }
+}
+
+/// A countdown to the next run with its two largest units, e.g. "in 3h 12m",
+/// "in 2d 5h", or "in 40s"; "now" once it is due. Unlike [`format_next_run`] it
+/// changes every minute, so shells redraw it on their regular refresh.
+pub fn format_countdown(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) if secs == 0 => "now".to_string(),
+        (0, 0, 0) => format!("in {secs}s"),
+        (0, 0, _) => format!("in {minutes}m"),
+        (0, _, _) => format!("in {hours}h {minutes}m"),
+        _ => format!("in {days}d {hours}h"),
+    }
+}
+
+/// One history entry, e.g. "Yesterday at 06:00: failed (exit 1), took 2m 3s"
+pub fn format_run_record(record: &RunRecord) -> String {
+    let outcome = match (record.timed_out, record.success, record.exit_code) {
+        (true, _, _) => "timed out".to_string(),
+        (false, true, _) => "succeeded".to_string(),
+        (false, false, Some(code)) => format!("failed (exit {code})"),
+        (false, false, None) => "failed".to_string(),
+    };
+    let mut line = format!("{}: {outcome}", format_last_run(&Some(record.timestamp)));
+    match record.duration_ms {
+        Some(ms) if ms < 1000 => line.push_str(", took <1s"),
+        Some(ms) => {
+            let took = humantime::format_duration(Duration::from_secs(ms / 1000));
+            line.push_str(&format!(", took {took}"));
+        }
+        None => {}
+    }
+    line
+}
+
+#[cfg(feature = "human-time")]
+fn format_datetime(dt: &DateTime<Local>) -> String {
+    format_relative_datetime(dt, &Local::now(), time_format())
+}
+
+/// Plain absolute timestamp used when the `human-time` feature is disabled.
+#[cfg(not(feature = "human-time"))]
+fn format_datetime(dt: &DateTime<Local>) -> String {
+    format!(
+        "{} at {}",
+        dt.format("%b %d, %Y"),
+        dt.format(time_format().pattern())
+    )
+}
+
+/// Human-friendly datetime relative to `now`, like "tomorrow at 10:00",
+/// "in 3 weeks (on Dec 21st, 2025 at 10:00)", or "3 months ago (on Jul 2nd at 10:00)".
+/// Only the time of day follows `clock`, e.g. "tomorrow at 6:00 AM".
+#[cfg(feature = "human-time")]
+fn format_relative_datetime(
+    dt: &DateTime<Local>,
+    now: &DateTime<Local>,
+    clock: TimeFormat,
+) -> String {
     let date_diff = dt.date_naive().signed_duration_since(now.date_naive());
     let diff_days = date_diff.num_days();
-    let time_part = dt.format("%H:%M").to_string();
+    let time_part = dt.format(clock.pattern()).to_string();
 
     let phrase = match diff_days {
         0 => format!("today at {time_part}"),
@@ -575,7 +2242,12 @@ fn format_relative_datetime(dt: &DateTime<Local>) -> String {
                 format!("{} {}, {}", dt.format("%b"), ordinal(dt.day()), dt.year())
             };
 
-            let relative = humantime_fmt::format_relative((*dt).into());
+            let distance = describe_distance(now.date_naive(), dt.date_naive());
+            let relative = if diff_days > 0 {
+                format!("in {distance}")
+            } else {
+                format!("{distance} ago")
+            };
             format!("{relative} (on {date_str} at {time_part})")
         }
     };
@@ -583,6 +2255,29 @@ fn format_relative_datetime(dt: &DateTime<Local>) -> String {
     capitalize_first(&phrase)
 }
 
+/// Coarse distance between two dates a week or more apart, in either order:
+/// "3 weeks", "2 months", "1 year". Months and years follow the calendar.
+#[cfg(feature = "human-time")]
+fn describe_distance(a: NaiveDate, b: NaiveDate) -> String {
+    let (earlier, later) = if a <= b { (a, b) } else { (b, a) };
+    let days = (later - earlier).num_days();
+    let mut months = i64::from(later.year() - earlier.year()) * 12 + i64::from(later.month())
+        - i64::from(earlier.month());
+    if later.day() < earlier.day() {
+        months -= 1;
+    }
+    let (count, unit) = match days {
+        0..60 => (days / 7, "week"),
+        _ if months < 12 => (months, "month"),
+        _ => (months / 12, "year"),
+    };
+    if count == 1 {
+        format!("{count} {unit}")
+    } else {
+        format!("{count} {unit}s")
+    }
+}
+
 /// Return ordinal suffix for a day (1st, 2nd, 3rd, 4th, ...).
 fn ordinal(day: u32) -> String {
     let suffix = match day % 100 {
@@ -606,3 +2301,1114 @@ pub fn capitalize_first(s: &str) -> String {
         None => String::new(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{RecordingSpawner, SpawnedCommand, TempPaths};
+
+    fn warning_for(pattern: &str) -> Option<String> {
+        let cron = Cron::from_str(pattern).unwrap();
+        schedule_warning(&cron, &Utc::now())
+    }
+
+    #[cfg(feature = "human-time")]
+    #[test]
+    fn relative_datetimes_read_in_the_right_tense_across_years() {
+        use chrono::TimeZone;
+        let at = |y, m, d, h| Local.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+
+        assert_eq!(
+            format_relative_datetime(
+                &at(2026, 3, 25, 10),
+                &at(2025, 12, 20, 12),
+                TimeFormat::TwentyFourHour
+            ),
+            "In 3 months (on Mar 25th, 2026 at 10:00)"
+        );
+        assert_eq!(
+            format_relative_datetime(
+                &at(2025, 10, 5, 10),
+                &at(2026, 1, 10, 12),
+                TimeFormat::TwentyFourHour
+            ),
+            "3 months ago (on Oct 5th, 2025 at 10:00)"
+        );
+        assert_eq!(
+            format_relative_datetime(
+                &at(2026, 1, 20, 10),
+                &at(2025, 12, 28, 12),
+                TimeFormat::TwentyFourHour
+            ),
+            "In 3 weeks (on Jan 20th, 2026 at 10:00)"
+        );
+        assert_eq!(
+            format_relative_datetime(
+                &at(2024, 1, 15, 10),
+                &at(2026, 2, 1, 12),
+                TimeFormat::TwentyFourHour
+            ),
+            "2 years ago (on Jan 15th, 2024 at 10:00)"
+        );
+        assert_eq!(
+            format_relative_datetime(
+                &at(2026, 12, 1, 10),
+                &at(2026, 1, 2, 12),
+                TimeFormat::TwentyFourHour
+            ),
+            "In 10 months (on Dec 1st at 10:00)"
+        );
+    }
+
+    #[cfg(feature = "human-time")]
+    #[test]
+    fn relative_datetimes_use_the_chosen_clock() {
+        use chrono::TimeZone;
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let at = |d, h| Local.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap();
+
+        assert_eq!(
+            format_relative_datetime(&at(11, 6), &now, TimeFormat::TwelveHour),
+            "Tomorrow at 6:00 AM"
+        );
+        assert_eq!(
+            format_relative_datetime(&at(9, 18), &now, TimeFormat::TwelveHour),
+            "Yesterday at 6:00 PM"
+        );
+        assert_eq!(
+            format_relative_datetime(&at(11, 6), &now, TimeFormat::TwentyFourHour),
+            "Tomorrow at 06:00"
+        );
+    }
+
+    #[test]
+    fn flags_schedules_that_never_fire() {
+        assert!(warning_for("0 0 30 2 *").is_some());
+        assert!(warning_for("0 0 31 4 *").is_some());
+        assert!(warning_for("0 6 * * *").is_none());
+        assert!(warning_for("*/15 * * * *").is_none());
+    }
+
+    /// A scheduler keeping its state in a fresh directory, removed with the paths.
+    fn temp_scheduler(name: &str) -> (TaskScheduler, TempPaths) {
+        let paths = TempPaths::new(name);
+        let scheduler = TaskScheduler::new(String::new(), &paths);
+        (scheduler, paths)
+    }
+
+    fn task(cron_schedule: &str) -> ScheduledTask {
+        ScheduledTask::new(&task_config(cron_schedule), None).unwrap()
+    }
+
+    fn task_config(cron_schedule: &str) -> ScheduledTaskConfig {
+        ScheduledTaskConfig {
+            name: cron_schedule.to_string(),
+            command: "true".to_string(),
+            args: Vec::new(),
+            cron_schedule: cron_schedule.to_string(),
+            timezone: None,
+            env: HashMap::new(),
+            shell: false,
+            wait_for_completion: false,
+            timeout_secs: None,
+            jitter_secs: None,
+            active_from: None,
+            active_until: None,
+            active_hours: None,
+            run_on_startup: false,
+            path: None,
+            notify_on_complete: false,
+            then: Vec::new(),
+            then_on_failure: false,
+            enabled: true,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_a_bounded_run_history() {
+        let mut failing = task("0 * * * *");
+        failing.command = "false".to_string();
+        failing.wait_for_completion = true;
+        let tasks = Mutex::new(HashMap::from([("failing".to_string(), failing)]));
+        let in_flight = Mutex::new(HashSet::new());
+        let path = std::env::var("PATH").unwrap_or_default();
+        for _ in 0..3 {
+            run_unlocked(
+                &tasks,
+                &in_flight,
+                "failing",
+                &path,
+                &StdProcessSpawner,
+                &EventBus::default(),
+                None,
+                false,
+            );
+        }
+
+        let mut tasks = tasks.into_inner().unwrap();
+        assert_eq!(tasks["failing"].history.len(), 3);
+        let states = collect_states(&mut tasks, 2);
+        assert_eq!(tasks["failing"].history.len(), 2);
+
+        let saved = toml::to_string(&states).unwrap();
+        let loaded: HashMap<String, TaskState> = toml::from_str(&saved).unwrap();
+        let record = &loaded["failing"].history[1];
+        assert_eq!(loaded["failing"].history.len(), 2);
+        assert_eq!(record.exit_code, Some(1));
+        assert!(!record.success && record.duration_ms.is_some());
+        let line = format_run_record(record);
+        assert!(line.contains(": failed (exit 1), took "), "{line}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_task_path_overrides_the_global_one() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let output = std::env::temp_dir().join(format!(
+            "something-bg-task-path-{}-{unique}",
+            std::process::id()
+        ));
+        let mut task = task("0 * * * *");
+        task.command = format!("echo \"$PATH\" > {}", output.display());
+        task.shell = true;
+        task.wait_for_completion = true;
+        task.path = Some("/usr/bin:/bin:/task-only".to_string());
+
+        task.execute("/usr/bin:/bin:/global", &StdProcessSpawner)
+            .unwrap();
+        let seen = fs::read_to_string(&output).unwrap();
+        let _ = fs::remove_file(&output);
+        assert_eq!(seen.trim(), "/usr/bin:/bin:/task-only");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_exit_codes_when_waiting_for_completion() {
+        let path = std::env::var("PATH").unwrap_or_default();
+        let mut failing = task("0 * * * *");
+        failing.command = "false".to_string();
+        failing.wait_for_completion = true;
+
+        assert!(matches!(
+            failing.execute(&path, &StdProcessSpawner),
+            Err(SchedulerError::Failed { code: 1, .. })
+        ));
+        assert_eq!(failing.last_exit_code, Some(1));
+        assert!(failing.last_run.is_some());
+        assert!(format_last_result(&failing).starts_with("failed (exit 1)"));
+
+        failing.command = "true".to_string();
+        assert!(failing.execute(&path, &StdProcessSpawner).is_ok());
+        assert_eq!(failing.last_exit_code, Some(0));
+    }
+
+    #[test]
+    fn reports_a_missing_program_with_the_path() {
+        let mut missing = task("0 * * * *");
+        missing.command = "something-bg-no-such-program".to_string();
+
+        let err = missing
+            .execute("/usr/bin:/bin", &StdProcessSpawner)
+            .unwrap_err();
+        assert!(
+            matches!(err, SchedulerError::CommandNotFound { .. }),
+            "{err}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "Failed to execute task '0 * * * *': Command 'something-bg-no-such-program' \
+             not found on PATH (/usr/bin:/bin)"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn removing_a_task_stops_its_run() {
+        let (scheduler, _paths) = temp_scheduler("remove");
+        *scheduler.path.lock().unwrap() = std::env::var("PATH").unwrap_or_default();
+        let mut sleeper = task_config("0 6 * * *");
+        sleeper.command = "sleep".to_string();
+        sleeper.args = vec!["30".to_string()];
+        scheduler.add_task("sleeper".to_string(), &sleeper).unwrap();
+
+        let started = Instant::now();
+        thread::scope(|scope| {
+            let run = scope.spawn(|| scheduler.run_task_now_blocking("sleeper"));
+            while !scheduler.in_flight.lock().unwrap().contains("sleeper") {
+                thread::sleep(Duration::from_millis(10));
+            }
+            thread::sleep(Duration::from_millis(200));
+            scheduler.remove_task("sleeper").unwrap();
+            assert!(matches!(
+                run.join().unwrap(),
+                Err(SchedulerError::Stopped(_))
+            ));
+        });
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(scheduler.get_task("sleeper").is_none());
+        assert!(!scheduler.states.lock().unwrap().contains_key("sleeper"));
+        assert!(matches!(
+            scheduler.remove_task("sleeper"),
+            Err(SchedulerError::TaskNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_tasks_that_exceed_their_timeout() {
+        let path = std::env::var("PATH").unwrap_or_default();
+        let mut slow = task("0 * * * *");
+        slow.command = "sleep".to_string();
+        slow.args = vec!["30".to_string()];
+        slow.timeout = Some(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let err = slow.execute(&path, &StdProcessSpawner).unwrap_err();
+        assert!(matches!(err, SchedulerError::TimedOut { .. }), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(slow.last_timed_out);
+        assert!(format_last_result(&slow).starts_with("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn an_unbounded_timeout_waits_for_the_task_without_panicking() {
+        let path = std::env::var("PATH").unwrap_or_default();
+        let mut quick = task("0 * * * *");
+        quick.command = "true".to_string();
+        quick.timeout = Some(Duration::from_secs(u64::MAX));
+
+        assert!(quick.execute(&path, &StdProcessSpawner).is_ok());
+        assert!(!quick.last_timed_out);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn long_runs_do_not_hold_the_tasks_lock() {
+        let mut slow = task("0 * * * *");
+        slow.command = "sleep".to_string();
+        slow.args = vec!["1".to_string()];
+        slow.wait_for_completion = true;
+        let tasks = Arc::new(Mutex::new(HashMap::from([("slow".to_string(), slow)])));
+        let in_flight = Arc::new(Mutex::new(HashSet::new()));
+
+        let runner = {
+            let (tasks, in_flight) = (Arc::clone(&tasks), Arc::clone(&in_flight));
+            let path = std::env::var("PATH").unwrap_or_default();
+            thread::spawn(move || {
+                run_unlocked(
+                    &tasks,
+                    &in_flight,
+                    "slow",
+                    &path,
+                    &StdProcessSpawner,
+                    &EventBus::default(),
+                    None,
+                    false,
+                )
+            })
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !in_flight.lock().unwrap().contains("slow") && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // Let the runner finish cloning the task and start waiting on the child.
+        thread::sleep(Duration::from_millis(200));
+
+        assert!(tasks.try_lock().is_ok());
+        assert!(
+            run_unlocked(
+                &tasks,
+                &in_flight,
+                "slow",
+                "",
+                &StdProcessSpawner,
+                &EventBus::default(),
+                None,
+                false
+            )
+            .is_none()
+        );
+        assert!(matches!(runner.join().unwrap(), Some(Ok(()))));
+        assert_eq!(tasks.lock().unwrap()["slow"].last_exit_code, Some(0));
+    }
+
+    #[test]
+    fn interval_schedules_count_from_the_last_run() {
+        let mut every = task("@every 15m");
+        let now = Utc::now();
+        let next = every.next_run.unwrap();
+        assert!(next > now && next <= now + chrono::Duration::minutes(15));
+
+        every.update_next_run();
+        assert_eq!(
+            every.next_run.unwrap() - every.last_run.unwrap(),
+            chrono::Duration::minutes(15)
+        );
+        assert!(Schedule::parse("@every 0s").is_err());
+        assert!(Schedule::parse("@every soon").is_err());
+    }
+
+    #[test]
+    fn jitter_delays_each_run_by_a_fresh_offset() {
+        let mut config = task_config("@every 1h");
+        config.jitter_secs = Some(600);
+        let mut jittered = ScheduledTask::new(&config, None).unwrap();
+
+        let mut offsets = HashSet::new();
+        for _ in 0..20 {
+            jittered.update_next_run();
+            let offset = jittered.next_run.unwrap() - jittered.last_run.unwrap();
+            assert!(offset >= chrono::Duration::hours(1), "{offset}");
+            assert!(offset <= chrono::Duration::seconds(3600 + 600), "{offset}");
+            offsets.insert(offset.num_seconds());
+        }
+        assert!(offsets.len() > 1, "jitter should differ between runs");
+
+        // Too large to represent: validation rejects it, and it must not panic here.
+        let now = Utc::now();
+        assert!(add_jitter(now, Some(Duration::from_secs(u64::MAX))) >= now);
+
+        config.jitter_secs = Some(0);
+        let mut exact = ScheduledTask::new(&config, None).unwrap();
+        exact.update_next_run();
+        assert_eq!(
+            exact.next_run.unwrap() - exact.last_run.unwrap(),
+            chrono::Duration::hours(1)
+        );
+    }
+
+    #[test]
+    fn counts_down_to_the_next_run() {
+        let mut task = task("0 * * * *");
+        let now = Utc::now();
+        task.next_run = Some(now + chrono::Duration::seconds(3 * 3600 + 12 * 60 + 5));
+        let remaining = task.time_until_next(&now).unwrap();
+        assert_eq!(format_countdown(remaining), "in 3h 12m");
+
+        task.next_run = Some(now - chrono::Duration::minutes(5));
+        assert_eq!(task.time_until_next(&now), Some(Duration::ZERO));
+        assert_eq!(format_countdown(Duration::ZERO), "now");
+        task.enabled = false;
+        assert_eq!(task.time_until_next(&now), None);
+
+        assert_eq!(format_countdown(Duration::from_secs(40)), "in 40s");
+        assert_eq!(format_countdown(Duration::from_secs(600)), "in 10m");
+        assert_eq!(
+            format_countdown(Duration::from_secs(2 * 86_400 + 5 * 3600)),
+            "in 2d 5h"
+        );
+
+        let (scheduler, _paths) = temp_scheduler("countdown");
+        scheduler
+            .add_task("hourly".to_string(), &task_config("@every 1h"))
+            .unwrap();
+        let remaining = scheduler.time_until_next("hourly").unwrap();
+        assert!(remaining <= Duration::from_secs(3600), "{remaining:?}");
+        assert_eq!(scheduler.time_until_next("missing"), None);
+    }
+
+    #[test]
+    fn runs_only_inside_the_active_window() {
+        use chrono::Timelike;
+
+        assert!("17:00-09:00".parse::<ActiveHours>().is_ok());
+        assert!("09:00-09:00".parse::<ActiveHours>().is_err());
+        assert!("9am-5pm".parse::<ActiveHours>().is_err());
+
+        let mut config = task_config("*/30 * * * *");
+        config.timezone = Some("UTC".to_string());
+        config.active_hours = Some("09:00-10:00".parse().unwrap());
+        let mut task = ScheduledTask::from_config(&config).unwrap();
+        let upcoming = task.upcoming(4);
+        assert_eq!(upcoming.len(), 4);
+        assert!(
+            upcoming.iter().all(|run| run.to_utc().hour() == 9),
+            "{upcoming:?}"
+        );
+
+        // Due at noon, outside the window: skipped to the next opening, not run late.
+        let noon = Utc.with_ymd_and_hms(2030, 1, 7, 12, 0, 0).unwrap();
+        task.next_run = Some(noon - chrono::Duration::minutes(30));
+        assert!(!task.should_run(&noon));
+        assert!(task.skip_outside_window(&noon));
+        assert_eq!(
+            task.next_run,
+            Some(Utc.with_ymd_and_hms(2030, 1, 8, 9, 0, 0).unwrap())
+        );
+        assert!(!task.skip_outside_window(&noon));
+
+        config.active_hours = Some("23:00-01:00".parse().unwrap());
+        let overnight = ScheduledTask::from_config(&config).unwrap();
+        assert!(
+            overnight
+                .upcoming(4)
+                .iter()
+                .all(|run| matches!(run.to_utc().hour(), 23 | 0)),
+        );
+
+        config.active_hours = None;
+        config.active_until = Some("2020-01-01T00:00:00Z".parse().unwrap());
+        let ended = ScheduledTask::from_config(&config).unwrap();
+        assert_eq!(ended.next_run, None);
+    }
+
+    #[test]
+    fn summarizes_what_was_loaded() {
+        let (scheduler, _paths) = temp_scheduler("summary");
+        assert_eq!(
+            startup_summary(1, &scheduler),
+            "1 tunnel, 0 scheduled tasks"
+        );
+
+        let mut backup = task_config("@every 2h");
+        backup.name = "Backup".to_string();
+        let mut sync = task_config("@every 1h");
+        sync.name = "Sync".to_string();
+        scheduler.add_task("backup".to_string(), &backup).unwrap();
+        scheduler.add_task("sync".to_string(), &sync).unwrap();
+        let summary = startup_summary(3, &scheduler);
+        assert!(
+            summary.starts_with("3 tunnels, 2 scheduled tasks. Next: Sync, "),
+            "{summary}"
+        );
+
+        scheduler.set_enabled("sync", false).unwrap();
+        assert!(startup_summary(3, &scheduler).contains("Next: Backup, "));
+    }
+
+    #[test]
+    fn keeps_tasks_with_an_invalid_schedule_as_failed() {
+        let (scheduler, _paths) = temp_scheduler("failed-tasks");
+        assert!(matches!(
+            scheduler.add_task("broken".to_string(), &task_config("0 25 * * *")),
+            Err(SchedulerError::CronParse(_))
+        ));
+        assert!(scheduler.get_task("broken").is_none());
+        let failed = scheduler.failed_tasks();
+        assert!(failed["broken"].contains("0 25 * * *"), "{failed:?}");
+
+        // A reload keeps the valid tasks and records the broken one again.
+        scheduler.reconfigure(
+            String::new(),
+            &[
+                ("broken".to_string(), task_config("0 25 * * *")),
+                ("sync".to_string(), task_config("@every 1h")),
+            ],
+        );
+        assert!(scheduler.get_task("sync").is_some());
+        assert!(scheduler.get_task("broken").is_none());
+        assert!(scheduler.failed_tasks().contains_key("broken"));
+
+        // Fixing the schedule moves it back among the live tasks.
+        scheduler
+            .add_task("broken".to_string(), &task_config("0 5 * * *"))
+            .unwrap();
+        assert!(scheduler.failed_tasks().is_empty());
+        assert!(scheduler.get_task("broken").is_some());
+
+        scheduler
+            .add_task("typo".to_string(), &task_config("not a schedule"))
+            .unwrap_err();
+        scheduler.remove_task("typo").unwrap();
+        assert!(scheduler.failed_tasks().is_empty());
+    }
+
+    #[test]
+    fn tooltip_shows_active_tunnels_and_the_next_task_time() {
+        let (scheduler, _paths) = temp_scheduler("tooltip");
+        assert_eq!(tray_tooltip(0, &scheduler), "No tunnels active");
+
+        scheduler
+            .add_task("sync".to_string(), &task_config("@every 1h"))
+            .unwrap();
+        let next = scheduler.get_task("sync").unwrap().upcoming(1)[0];
+        assert_eq!(
+            tray_tooltip(2, &scheduler),
+            format!("2 tunnels active · next task {}", next.format("%H:%M"))
+        );
+
+        scheduler.set_enabled("sync", false).unwrap();
+        assert_eq!(tray_tooltip(1, &scheduler), "1 tunnel active");
+    }
+
+    #[test]
+    fn lists_upcoming_occurrences_in_order() {
+        use chrono::Timelike;
+
+        let morning = task("0 6 * * *");
+        let upcoming = morning.upcoming(3);
+        assert_eq!(upcoming.len(), 3);
+        assert_eq!(upcoming[0], morning.next_run.unwrap().with_timezone(&Local));
+        assert!(upcoming.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(upcoming.iter().all(|time| time.hour() == 6));
+
+        let every = task("@every 15m");
+        let upcoming = every.upcoming(2);
+        assert_eq!(upcoming[1] - upcoming[0], chrono::Duration::minutes(15));
+
+        assert!(task("@reboot").upcoming(3).is_empty());
+        // Nothing is reserved up front for runs the schedule may never produce.
+        assert!(task("@reboot").upcoming(usize::MAX).is_empty());
+        assert!(morning.upcoming(0).is_empty());
+    }
+
+    #[test]
+    fn cron_hours_are_local_and_states_are_stored_in_utc() {
+        use chrono::Timelike;
+
+        let morning = task("0 6 * * *");
+        let next = morning.next_run.unwrap();
+        assert_eq!(next.with_timezone(&Local).hour(), 6);
+        assert_eq!(next.with_timezone(&Local).minute(), 0);
+
+        // States written with a local offset still load, normalized to UTC.
+        let state: TaskState =
+            toml::from_str("last_run = \"2025-03-01T06:00:00+02:00\"\n").unwrap();
+        assert_eq!(
+            state.last_run.unwrap().to_rfc3339(),
+            "2025-03-01T04:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn cron_hours_follow_the_task_timezone() {
+        use chrono::Timelike;
+
+        let mut config = task_config("0 9 * * *");
+        config.timezone = Some("America/New_York".to_string());
+        let standup = ScheduledTask::new(&config, None).unwrap();
+        let next = standup
+            .next_run
+            .unwrap()
+            .with_timezone(&chrono_tz::America::New_York);
+        assert_eq!((next.hour(), next.minute()), (9, 0));
+
+        config.timezone = Some("Nowhere/Special".to_string());
+        assert!(matches!(
+            ScheduledTask::new(&config, None),
+            Err(SchedulerError::CronParse(_))
+        ));
+
+        assert_eq!(
+            cron_to_human_readable("0 9 * * *", Some("America/New_York")),
+            "Every day at 9:00 (America/New_York)"
+        );
+        assert_eq!(
+            cron_to_human_readable("@every 15m", Some("America/New_York")),
+            "Every 15 minutes"
+        );
+    }
+
+    #[test]
+    fn disabled_tasks_skip_their_schedule_across_restarts() {
+        let (scheduler, paths) = temp_scheduler("enabled");
+        scheduler
+            .add_task("backup".to_string(), &task_config("0 6 * * *"))
+            .unwrap();
+        let later = Utc::now() + chrono::Duration::days(2);
+        assert!(scheduler.get_task("backup").unwrap().should_run(&later));
+
+        scheduler.set_enabled("backup", false).unwrap();
+        let paused = scheduler.get_task("backup").unwrap();
+        assert!(!paused.should_run(&later));
+        assert_eq!(format_next_run(&paused), "Disabled");
+        assert!(matches!(
+            scheduler.set_enabled("missing", false),
+            Err(SchedulerError::TaskNotFound(_))
+        ));
+
+        // The choice outlives the scheduler, and resuming drops it from the state.
+        let reloaded = TaskScheduler::new(String::new(), &paths);
+        reloaded
+            .add_task("backup".to_string(), &task_config("0 6 * * *"))
+            .unwrap();
+        assert!(!reloaded.get_task("backup").unwrap().enabled);
+        reloaded.set_enabled("backup", true).unwrap();
+        assert_eq!(reloaded.states.lock().unwrap()["backup"].enabled, None);
+    }
+
+    /// Move `key`'s next run into the past, as if the machine had been asleep.
+    fn make_overdue(scheduler: &TaskScheduler, key: &str) {
+        let mut tasks = scheduler.tasks.lock().unwrap();
+        tasks.get_mut(key).unwrap().next_run = Some(Utc::now() - chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn missed_tasks_run_with_their_argv_and_path_and_are_saved() {
+        let (mut scheduler, paths) = temp_scheduler("missed");
+        let spawner = Arc::new(RecordingSpawner::with_exit_code(3));
+        scheduler.set_spawner(spawner.clone());
+        let mut backup = task_config("0 6 * * *");
+        backup.command = "backup".to_string();
+        backup.args = vec!["--fast".to_string()];
+        backup.path = Some("/opt/bin".to_string());
+        backup.wait_for_completion = true;
+        scheduler.add_task("backup".to_string(), &backup).unwrap();
+
+        make_overdue(&scheduler, "backup");
+        scheduler.check_and_run_missed_tasks();
+        assert_eq!(
+            spawner.calls(),
+            vec![SpawnedCommand {
+                program: "backup".to_string(),
+                args: vec!["--fast".to_string()],
+                path: Some("/opt/bin".to_string()),
+            }]
+        );
+
+        // Running again finds nothing missed; a fresh scheduler sees the saved run.
+        scheduler.check_and_run_missed_tasks();
+        assert_eq!(spawner.calls().len(), 1);
+        assert!(paths.state_path().exists());
+        let reloaded = TaskScheduler::new(String::new(), &paths);
+        reloaded.add_task("backup".to_string(), &backup).unwrap();
+        let task = reloaded.get_task("backup").unwrap();
+        assert_eq!(task.last_exit_code, Some(3));
+        assert!(task.last_run.is_some());
+        assert!(task.next_run.unwrap() > Utc::now());
+    }
+
+    #[test]
+    fn then_runs_the_next_tasks_after_success_and_stops_at_cycles() {
+        let chained = |exit_code: i32, then_on_failure: bool| {
+            let (mut scheduler, paths) = temp_scheduler("then");
+            let spawner = Arc::new(RecordingSpawner::with_exit_code(exit_code));
+            scheduler.set_spawner(spawner.clone());
+            for (key, then) in [("backup", "upload"), ("upload", "backup")] {
+                let mut config = task_config("0 6 * * *");
+                config.command = key.to_string();
+                config.then = vec![then.to_string()];
+                config.then_on_failure = then_on_failure;
+                scheduler.add_task(key.to_string(), &config).unwrap();
+            }
+            let result = scheduler.run_task_now("backup");
+            let programs: Vec<String> = spawner.calls().into_iter().map(|c| c.program).collect();
+            (result, programs, paths)
+        };
+
+        // The cycle back to "backup" is cut after one pass.
+        let (result, programs, _paths) = chained(0, false);
+        assert!(result.is_ok());
+        assert_eq!(programs, ["backup", "upload"]);
+
+        let (result, programs, _paths) = chained(1, false);
+        assert!(matches!(
+            result,
+            Err(SchedulerError::Failed { code: 1, .. })
+        ));
+        assert_eq!(programs, ["backup"]);
+
+        let (_, programs, _paths) = chained(1, true);
+        assert_eq!(programs, ["backup", "upload"]);
+    }
+
+    #[test]
+    fn missed_task_check_skips_tasks_that_are_not_owed_a_run() {
+        let (mut scheduler, _paths) = temp_scheduler("not-missed");
+        let spawner = Arc::new(RecordingSpawner::new());
+        scheduler.set_spawner(spawner.clone());
+        for key in ["upcoming", "disabled", "already-ran", "reboot"] {
+            let cron = if key == "reboot" {
+                "@reboot"
+            } else {
+                "0 6 * * *"
+            };
+            scheduler
+                .add_task(key.to_string(), &task_config(cron))
+                .unwrap();
+        }
+        make_overdue(&scheduler, "disabled");
+        scheduler.set_enabled("disabled", false).unwrap();
+        make_overdue(&scheduler, "already-ran");
+        scheduler
+            .tasks
+            .lock()
+            .unwrap()
+            .get_mut("already-ran")
+            .unwrap()
+            .last_run = Some(Utc::now());
+
+        scheduler.check_and_run_missed_tasks();
+        assert_eq!(spawner.calls(), Vec::new());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notifies_on_success_only_when_asked() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+        impl crate::platform::Notifier for Recorder {
+            fn info(&self, title: &str, _body: &str) {
+                self.0.lock().unwrap().push(title.to_string());
+            }
+            fn warn(&self, _title: &str, _body: &str) {}
+            fn error(&self, _title: &str, _body: &str) {}
+        }
+
+        let (mut scheduler, _paths) = temp_scheduler("notify-complete");
+        let recorder = Arc::new(Recorder::default());
+        scheduler.set_notifier(recorder.clone());
+        *scheduler.path.lock().unwrap() = std::env::var("PATH").unwrap_or_default();
+        let mut loud = task_config("0 6 * * *");
+        loud.name = "Daily Backup".to_string();
+        loud.notify_on_complete = true;
+        scheduler.add_task("loud".to_string(), &loud).unwrap();
+        scheduler
+            .add_task("quiet".to_string(), &task_config("0 7 * * *"))
+            .unwrap();
+
+        scheduler.run_task_now("loud").unwrap();
+        scheduler.run_task_now("quiet").unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["Daily Backup finished (exit 0)"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_failed_run_now_is_reported_by_name() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+        impl crate::platform::Notifier for Recorder {
+            fn info(&self, _title: &str, _body: &str) {}
+            fn warn(&self, _title: &str, _body: &str) {}
+            fn error(&self, title: &str, _body: &str) {
+                self.0.lock().unwrap().push(title.to_string());
+            }
+        }
+
+        let (mut scheduler, _paths) = temp_scheduler("run-now-name");
+        let recorder = Arc::new(Recorder::default());
+        scheduler.set_notifier(recorder.clone());
+        *scheduler.path.lock().unwrap() = std::env::var("PATH").unwrap_or_default();
+        let mut broken = task_config("0 6 * * *");
+        broken.name = "Daily Backup".to_string();
+        broken.command = "false".to_string();
+        broken.wait_for_completion = true;
+        scheduler
+            .add_task("daily_backup".to_string(), &broken)
+            .unwrap();
+
+        assert!(scheduler.run_task_now("daily_backup").is_err());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["Scheduled task 'Daily Backup' failed"]
+        );
+    }
+
+    #[test]
+    fn stop_joins_the_loop_so_it_can_restart_in_place() {
+        let (scheduler, _paths) = temp_scheduler("restart");
+        for _ in 0..2 {
+            scheduler.start();
+            let stopping = std::time::Instant::now();
+            scheduler.stop();
+            // The loop was asleep for the whole poll interval; stopping wakes it.
+            assert!(stopping.elapsed() < Duration::from_secs(5));
+            assert!(scheduler.thread.lock().unwrap().is_none());
+            assert!(!*scheduler.running.lock().unwrap());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_kills_a_task_that_outlasts_the_grace_period() {
+        let (scheduler, _paths) = temp_scheduler("stop-kills");
+        *scheduler.path.lock().unwrap() = std::env::var("PATH").unwrap_or_default();
+        let mut config = task_config("0 6 * * *");
+        config.command = "sleep".to_string();
+        config.args = vec!["30".to_string()];
+        config.wait_for_completion = true;
+        config.run_on_startup = true;
+        scheduler.add_task("slow".to_string(), &config).unwrap();
+        scheduler.start();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !scheduler.in_flight.lock().unwrap().contains("slow") {
+            assert!(std::time::Instant::now() < deadline, "task never started");
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let stopping = std::time::Instant::now();
+        scheduler.stop_within(Duration::from_millis(200));
+        assert!(stopping.elapsed() < Duration::from_secs(5));
+        assert!(scheduler.thread.lock().unwrap().is_none());
+        let task = scheduler.get_task("slow").unwrap();
+        assert!(!task.stop.load(Ordering::SeqCst));
+        assert!(!task.history.back().unwrap().success);
+    }
+
+    #[test]
+    fn recovers_the_readable_entries_of_a_corrupt_state_file() {
+        let paths = TempPaths::new("corrupt-state");
+        let contents = "[backup]\n\
+            last_run = \"2024-05-01T06:00:00Z\"\n\
+            next_run = \"2024-05-02T06:00:00Z\"\n\
+            \n\
+            [[backup.history]]\n\
+            timestamp = \"2024-05-01T06:00:00Z\"\n\
+            success = true\n\
+            \n\
+            [mangled]\n\
+            last_exit_code = \"not a number\"\n\
+            \n\
+            [truncated]\n\
+            last_run = \"2024-05-";
+        fs::write(paths.state_path(), contents).unwrap();
+
+        let states = load_task_states(&paths.state_path());
+        assert_eq!(states.keys().collect::<Vec<_>>(), ["backup"]);
+        assert_eq!(states["backup"].history.len(), 1);
+        let backup = paths.dir().join("task_state.toml.corrupt");
+        assert_eq!(fs::read_to_string(backup).unwrap(), contents);
+    }
+
+    #[test]
+    fn saved_next_run_is_dropped_when_the_schedule_changes() {
+        let (scheduler, paths) = temp_scheduler("schedule-edit");
+        let mut config = task_config("0 9 * * *");
+        scheduler.add_task("standup".to_string(), &config).unwrap();
+        let sentinel = Utc::now() + chrono::Duration::days(3);
+        scheduler
+            .tasks
+            .lock()
+            .unwrap()
+            .get_mut("standup")
+            .unwrap()
+            .next_run = Some(sentinel);
+        scheduler.save_states();
+
+        // Unchanged, the saved time is kept across a restart.
+        let reloaded = TaskScheduler::new(String::new(), &paths);
+        reloaded.add_task("standup".to_string(), &config).unwrap();
+        assert_eq!(
+            reloaded.get_task("standup").unwrap().next_run,
+            Some(sentinel)
+        );
+
+        // Edited while the app was closed, it is recomputed for the new schedule.
+        config.cron_schedule = "@every 1h".to_string();
+        let edited = TaskScheduler::new(String::new(), &paths);
+        edited.add_task("standup".to_string(), &config).unwrap();
+        let next_run = edited.get_task("standup").unwrap().next_run.unwrap();
+        assert!(
+            next_run <= Utc::now() + chrono::Duration::hours(1),
+            "{next_run}"
+        );
+
+        // So is a change of timezone.
+        config.cron_schedule = "0 9 * * *".to_string();
+        scheduler.save_states();
+        config.timezone = Some("Pacific/Kiritimati".to_string());
+        let moved = TaskScheduler::new(String::new(), &paths);
+        moved.add_task("standup".to_string(), &config).unwrap();
+        assert_ne!(moved.get_task("standup").unwrap().next_run, Some(sentinel));
+    }
+
+    #[test]
+    fn reconfigure_keeps_next_run_only_for_unchanged_schedules() {
+        let (scheduler, _paths) = temp_scheduler("reconfigure");
+        let mut config = task_config("0 9 * * *");
+        scheduler.reconfigure(String::new(), &[("standup".to_string(), config.clone())]);
+        let sentinel = Utc::now() + chrono::Duration::days(3);
+        scheduler
+            .tasks
+            .lock()
+            .unwrap()
+            .get_mut("standup")
+            .unwrap()
+            .next_run = Some(sentinel);
+
+        scheduler.reconfigure(String::new(), &[("standup".to_string(), config.clone())]);
+        assert_eq!(
+            scheduler.get_task("standup").unwrap().next_run,
+            Some(sentinel)
+        );
+
+        config.timezone = Some("Asia/Tokyo".to_string());
+        scheduler.reconfigure(String::new(), &[("standup".to_string(), config)]);
+        assert_ne!(
+            scheduler.get_task("standup").unwrap().next_run,
+            Some(sentinel)
+        );
+
+        scheduler.reconfigure(String::new(), &[]);
+        assert!(scheduler.get_task("standup").is_none());
+    }
+
+    #[test]
+    fn expands_crontab_macros() {
+        let daily = task("@daily");
+        let midnight = Cron::from_str("0 0 * * *").unwrap();
+        let expected = midnight.find_next_occurrence(&Local::now(), false).unwrap();
+        assert_eq!(daily.next_run.unwrap().timestamp(), expected.timestamp());
+        assert!(Schedule::parse("@weekly").is_ok());
+        assert!(Schedule::parse("@fortnightly").is_err());
+
+        let reboot = task("@reboot");
+        assert!(reboot.runs_at_startup());
+        assert_eq!(reboot.next_run, None);
+        assert_eq!(cron_to_human_readable("@reboot", None), "At startup");
+        assert_eq!(cron_to_human_readable("@hourly", None), "Every hour");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_due_task_runs_once_even_when_requested_twice() {
+        let mut startup = task("0 0 1 1 *");
+        startup.command = "true".to_string();
+        startup.run_on_startup = true;
+        let now = Utc::now();
+        startup.next_run = Some(now);
+        let tasks = Mutex::new(HashMap::from([("startup".to_string(), startup)]));
+        let in_flight = Mutex::new(HashSet::new());
+        let path = std::env::var("PATH").unwrap_or_default();
+
+        let run = || {
+            run_unlocked(
+                &tasks,
+                &in_flight,
+                "startup",
+                &path,
+                &StdProcessSpawner,
+                &EventBus::default(),
+                Some(&now),
+                false,
+            )
+        };
+        assert!(matches!(run(), Some(Ok(()))));
+        assert!(run().is_none());
+        assert!(tasks.lock().unwrap()["startup"].next_run.unwrap() > now);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn blocking_runs_surface_the_exit_code() {
+        let mut failing = task("0 * * * *");
+        failing.command = "false".to_string();
+        let tasks = Mutex::new(HashMap::from([("failing".to_string(), failing)]));
+        let in_flight = Mutex::new(HashSet::new());
+        let path = std::env::var("PATH").unwrap_or_default();
+        let events = EventBus::default();
+        let observed = events.subscribe();
+        let run = |wait| {
+            run_unlocked(
+                &tasks,
+                &in_flight,
+                "failing",
+                &path,
+                &StdProcessSpawner,
+                &events,
+                None,
+                wait,
+            )
+        };
+
+        assert!(matches!(run(false), Some(Ok(()))));
+        let err = run(true).unwrap().unwrap_err();
+        assert!(
+            matches!(err, SchedulerError::Failed { code: 1, .. }),
+            "{err}"
+        );
+        let failing = &tasks.lock().unwrap()["failing"];
+        assert_eq!(failing.last_exit_code, Some(1));
+        assert!(!failing.wait_for_completion);
+
+        let finished: Vec<Event> = observed
+            .try_iter()
+            .filter(|event| matches!(event, Event::TaskFinished { .. }))
+            .collect();
+        assert_eq!(
+            finished,
+            vec![
+                Event::TaskFinished {
+                    key: "failing".into(),
+                    exit_code: None,
+                    error: None,
+                },
+                Event::TaskFinished {
+                    key: "failing".into(),
+                    exit_code: Some(1),
+                    error: Some(err.to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn describes_cron_fields() {
+        let cases = [
+            ("0 * * * *", "Every hour"),
+            ("0 0 * * *", "Every day at midnight"),
+            ("0 9 * * *", "Every day at 9:00"),
+            ("*/15 * * * *", "Every 15 minutes"),
+            ("* * * * *", "Every minute"),
+            ("30 * * * *", "Every hour at 30 minutes past"),
+            ("0 */6 * * *", "Every 6 hours"),
+            ("0 9 * * 1-5", "At 9:00 on weekdays"),
+            ("0 9 * * MON-FRI", "At 9:00 on weekdays"),
+            ("0 8 * * 1", "At 8:00 on Mondays"),
+            ("0 10 * * 0,6", "At 10:00 on weekends"),
+            (
+                "0 8 * * 1,3,5",
+                "At 8:00 on Mondays, Wednesdays and Fridays",
+            ),
+            ("0 8 * * 2-4", "At 8:00 on Tuesday through Thursday"),
+            ("30 9,17 * * *", "Every day at 9:30 and 17:30"),
+            (
+                "*/10 9-17 * * 1-5",
+                "Every 10 minutes from 9:00 to 17:59 on weekdays",
+            ),
+            ("0 9-17 * * *", "Every hour from 9:00 to 17:00"),
+            ("0 8 1 * *", "At 8:00 on the 1st of every month"),
+            ("0 8 1,15 * *", "At 8:00 on the 1st and 15th of every month"),
+            ("0 0 1 1,7 *", "At midnight on the 1st of January and July"),
+            ("0 12 * 12 *", "Every day at 12:00 in December"),
+            ("0 12 * 6-8 1", "At 12:00 on Mondays in June through August"),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(cron_to_human_readable(pattern, None), expected, "{pattern}");
+        }
+
+        // Day-of-month OR day-of-week, and too many times, stay as cron.
+        assert_eq!(cron_to_human_readable("0 8 1 * 1", None), "0 8 1 * 1");
+        assert_eq!(
+            cron_to_human_readable("0,30 8-12 * * *", None),
+            "0,30 8-12 * * *"
+        );
+    }
+
+    #[test]
+    fn describes_interval_schedules() {
+        assert_eq!(
+            cron_to_human_readable("@every 15m", None),
+            "Every 15 minutes"
+        );
+        assert_eq!(cron_to_human_readable("@every 2h", None), "Every 2 hours");
+        assert_eq!(cron_to_human_readable("@every 1h", None), "Every hour");
+        assert_eq!(
+            cron_to_human_readable("@every 90s", None),
+            "Every 90 seconds"
+        );
+    }
+
+    #[test]
+    fn sleeps_until_the_earliest_due_task() {
+        let now = Utc::now();
+        let mut tasks = HashMap::from([
+            ("daily".to_string(), task("0 0 * * *")),
+            ("hourly".to_string(), task("0 * * * *")),
+        ]);
+        let until = time_until_next_due(&tasks, &now).unwrap();
+        assert!(until <= Duration::from_secs(3600));
+
+        tasks.get_mut("daily").unwrap().next_run = Some(now - chrono::Duration::minutes(5));
+        assert_eq!(time_until_next_due(&tasks, &now), Some(Duration::ZERO));
+        assert_eq!(time_until_next_due(&HashMap::new(), &now), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! Test doubles for the platform traits, so scheduler and tunnel behaviour can
+//! be checked without real app folders or real commands. Enabled by the
+//! `testing` feature (and always in this crate's own tests).
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::platform::{AppPaths, ProcessSpawner};
+
+/// `AppPaths` inside a fresh directory under the system temp dir, removed
+/// again when dropped.
+pub struct TempPaths {
+    directory: PathBuf,
+}
+
+impl TempPaths {
+    /// Create the directory; `name` only makes it easier to spot.
+    pub fn new(name: &str) -> Self {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let directory = std::env::temp_dir().join(format!(
+            "something-bg-{name}-{}-{unique}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).expect("failed to create a temp directory");
+        Self { directory }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.directory
+    }
+}
+
+impl AppPaths for TempPaths {
+    fn config_path(&self) -> PathBuf {
+        self.directory.join("config.toml")
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.directory.join("task_state.toml")
+    }
+}
+
+impl Drop for TempPaths {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+/// One command handed to a [`RecordingSpawner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnedCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The `PATH` set on the command, if any.
+    pub path: Option<String>,
+}
+
+/// Records every command instead of running it, and hands back a stand-in
+/// child that exits at once with `exit_code`, so callers that wait still see
+/// a normal exit.
+#[derive(Debug, Default)]
+pub struct RecordingSpawner {
+    calls: Mutex<Vec<SpawnedCommand>>,
+    exit_code: i32,
+}
+
+impl RecordingSpawner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make every stand-in child exit with `code`.
+    pub fn with_exit_code(code: i32) -> Self {
+        Self {
+            exit_code: code,
+            ..Self::default()
+        }
+    }
+
+    /// Commands spawned so far, oldest first.
+    pub fn calls(&self) -> Vec<SpawnedCommand> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl ProcessSpawner for RecordingSpawner {
+    fn spawn(&self, command: &mut Command) -> std::io::Result<Child> {
+        let text = |value: &OsStr| value.to_string_lossy().into_owned();
+        self.calls.lock().unwrap().push(SpawnedCommand {
+            program: text(command.get_program()),
+            args: command.get_args().map(text).collect(),
+            path: command
+                .get_envs()
+                .find(|(key, _)| *key == "PATH")
+                .and_then(|(_, value)| value.map(text)),
+        });
+        stand_in(self.exit_code)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+}
+
+/// A command that does nothing but exit with `code`.
+fn stand_in(code: i32) -> Command {
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (shell, flag) = ("sh", "-c");
+    let mut command = Command::new(shell);
+    command.arg(flag).arg(format!("exit {code}"));
+    command
+}
@@ -2,13 +2,29 @@
 //! Handles starting/stopping configured commands and tracking active tunnels.
 
 use std::collections::{HashMap, HashSet};
-use std::ffi::OsStr;
-use std::process::Command;
-use std::sync::{Arc, Mutex};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    command_for, command_not_found, effective_command_line, expand_env_args, program_exists,
+    write_atomic,
+};
+use crate::events::{Event, EventBus};
+use crate::platform::{
+    ProcessSpawner, SharedNotifier, SharedSpawner, SharedTrayUi, StdProcessSpawner,
+};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct TunnelCommand {
@@ -16,6 +32,311 @@ pub struct TunnelCommand {
     pub args: Vec<String>,
     pub kill_command: String,
     pub kill_args: Vec<String>,
+    /// Grace period between SIGTERM and SIGKILL when stopping the tracked child.
+    pub kill_timeout: Duration,
+    /// Restarts allowed after the process exits or fails to spawn; `0` retries forever.
+    pub max_retries: u32,
+    /// Initial delay between restarts; doubles after each consecutive failure.
+    pub retry_backoff: Duration,
+    /// Directory to spawn the command in (`~` already expanded); inherits ours when unset.
+    pub working_dir: Option<PathBuf>,
+    /// Extra environment variables, applied after PATH so they can override it.
+    pub env: HashMap<String, String>,
+    /// Run the start and stop commands through the platform shell.
+    pub shell: bool,
+    /// Address that must accept a TCP connection before the tunnel counts as running.
+    pub ready_check: Option<ReadyCheck>,
+    /// Probe repeated while the tunnel runs; a failure restarts the process.
+    pub health_check: Option<HealthCheck>,
+    /// Wait before the first attempt when the tunnel is started at launch.
+    pub start_delay: Duration,
+    /// Tunnels that must be running before this one is spawned.
+    pub depends_on: Vec<String>,
+    /// Start inactive dependencies along with this tunnel instead of refusing to start.
+    pub start_dependencies: bool,
+    /// Why the config cannot be run as written, e.g. a malformed port template;
+    /// such a tunnel refuses to start or be tested.
+    pub invalid: Option<String>,
+}
+
+impl TunnelCommand {
+    /// The start command the tunnel spawns with `path` as PATH; see
+    /// [`effective_command_line`].
+    pub fn effective_command_line(&self, path: &str) -> String {
+        effective_command_line(
+            &self.command,
+            &self.args,
+            self.shell,
+            path,
+            &self.env,
+            self.working_dir.as_deref(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadyCheck {
+    /// `host:port`, resolved on every attempt.
+    pub address: String,
+    /// How long to keep trying before the start attempt counts as failed.
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub probe: HealthProbe,
+    /// Time between probes, counted from when the tunnel became ready.
+    pub interval: Duration,
+    /// How long one probe may take before it counts as failed.
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// `host:port` that must accept a TCP connection.
+    Tcp(String),
+    /// A command that must exit with status 0; run like the tunnel's start command.
+    Command { command: String, args: Vec<String> },
+}
+
+/// How often a tunnel thread checks whether its child process has exited.
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Lifecycle state of a single tunnel as seen by the manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunnelStatus {
+    /// Not enabled.
+    Stopped,
+    /// Enabled, but the process has not been spawned yet.
+    Starting,
+    /// The process is up.
+    Running,
+    /// The process exited or failed to spawn; waiting to restart it.
+    Retrying,
+    /// Retries were exhausted and the tunnel was deactivated.
+    Failed,
+}
+
+/// Why a tunnel could not be started; checked before anything is spawned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelError {
+    /// No tunnel with this key is configured.
+    NotFound(String),
+    /// The start command's program is not on the tunnel's PATH.
+    CommandNotFound {
+        tunnel: String,
+        command: String,
+        path: String,
+    },
+    /// A tunnel it depends on is off and may not be started, failed to start, or
+    /// depends on it in turn.
+    Dependency { tunnel: String, reason: String },
+    /// The tunnel is on, so testing it would fight the running process over its port.
+    Active(String),
+    /// The tunnel's config cannot be run as written; see [`TunnelCommand::invalid`].
+    Invalid { tunnel: String, reason: String },
+}
+
+impl fmt::Display for TunnelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(key) => write!(f, "Tunnel '{key}' not found"),
+            Self::CommandNotFound { command, path, .. } => {
+                write!(f, "{}", command_not_found(command, path))
+            }
+            Self::Dependency { reason, .. } | Self::Invalid { reason, .. } => {
+                write!(f, "{reason}")
+            }
+            Self::Active(key) => write!(f, "Tunnel '{key}' is on; switch it off to test it"),
+        }
+    }
+}
+
+impl std::error::Error for TunnelError {}
+
+/// How long [`TunnelManager::test`] lets the start command run before stopping it.
+pub const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a [`TunnelManager::test`] run did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestReport {
+    /// Exit code when the command exited within the timeout; `None` when it was
+    /// still running, killed by a signal, or never started.
+    pub exit_code: Option<i32>,
+    /// The command was still up at the timeout and was stopped again, which is
+    /// how a working tunnel behaves.
+    pub still_running: bool,
+    /// What the command wrote to stderr (or why it could not start), trimmed.
+    pub stderr: String,
+}
+
+impl TestReport {
+    /// Whether the tunnel looks usable: still running at the timeout, or a clean exit.
+    pub fn passed(&self) -> bool {
+        self.still_running || self.exit_code == Some(0)
+    }
+
+    /// One line for a notification: the outcome, then the last line of stderr,
+    /// which is where `ssh` puts errors like `Permission denied (publickey)`.
+    pub fn summary(&self) -> String {
+        let outcome = match self.exit_code {
+            _ if self.still_running => "Still running at the timeout".to_string(),
+            Some(code) => format!("Exited with status {code}"),
+            None => "Killed by a signal".to_string(),
+        };
+        match self
+            .stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+        {
+            // Without an exit code the output already says what happened, e.g.
+            // that the command could not be started.
+            Some(line) if self.exit_code.is_none() && !self.still_running => {
+                line.trim().to_string()
+            }
+            Some(line) => format!("{outcome}: {}", line.trim()),
+            None => outcome,
+        }
+    }
+}
+
+/// Upper bound for the exponential backoff between tunnel restarts.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Delay before restart number `failures` (1-based): `base * 2^(failures - 1)`, capped.
+fn retry_delay(base: Duration, failures: u32) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(16);
+    base.saturating_mul(factor).min(MAX_RETRY_BACKOFF)
+}
+
+/// Uptime after which a tunnel counts as having connected successfully, so a later
+/// drop (e.g. an SSH session dying overnight) starts a fresh backoff cycle.
+const HEALTHY_UPTIME: Duration = Duration::from_secs(60);
+
+/// Consecutive failures after the tunnel process exits. `uptime` is how long it was
+/// running (`None` if it never got there); a healthy run resets the count.
+fn failures_after_exit(failures: u32, uptime: Option<Duration>) -> u32 {
+    if uptime.is_some_and(|uptime| uptime >= HEALTHY_UPTIME) {
+        1
+    } else {
+        failures + 1
+    }
+}
+
+/// Limits how many tunnels are spawning or waiting to become ready at once, so
+/// bringing many up together (auto start, restore, wake) does not trip SSH
+/// multiplexing or auth rate limits. The rest queue in the `Starting` state.
+#[derive(Default)]
+struct StartSlots {
+    /// Slots in use and the limit; a limit of `0` means no limit.
+    state: Mutex<(usize, usize)>,
+    freed: Condvar,
+}
+
+/// A held start slot, handed back when dropped.
+struct StartSlot<'a>(&'a StartSlots);
+
+impl StartSlots {
+    fn set_limit(&self, limit: usize) {
+        self.state.lock().unwrap().1 = limit;
+        self.freed.notify_all();
+    }
+
+    /// Wait for a free slot; `None` once `is_active` turns false while queued.
+    fn acquire(&self, key: &str, is_active: &dyn Fn() -> bool) -> Option<StartSlot<'_>> {
+        let mut state = self.state.lock().unwrap();
+        let mut logged = false;
+        while state.1 != 0 && state.0 >= state.1 {
+            if !is_active() {
+                return None;
+            }
+            if !logged {
+                info!(
+                    "Tunnel '{key}' is waiting for one of {} start slots",
+                    state.1
+                );
+                logged = true;
+            }
+            state = self
+                .freed
+                .wait_timeout(state, CHILD_POLL_INTERVAL)
+                .unwrap()
+                .0;
+        }
+        state.0 += 1;
+        Some(StartSlot(self))
+    }
+}
+
+impl Drop for StartSlot<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.0 -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// Longest wait for a tunnel's dependencies to come up before it counts as failed.
+const DEPENDENCY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Block until every tunnel in `depends_on` is running (and so ready, for those
+/// with a `ready_check`). Fails as soon as one of them is stopped or gives up, or
+/// after `DEPENDENCY_TIMEOUT`; returns `Ok` early once `is_active` turns false.
+fn wait_for_dependencies(
+    command_key: &str,
+    depends_on: &[String],
+    statuses: &Mutex<HashMap<String, TunnelStatus>>,
+    is_active: &dyn Fn() -> bool,
+) -> Result<(), String> {
+    let deadline = Instant::now() + DEPENDENCY_TIMEOUT;
+    loop {
+        let mut waiting_for = None;
+        {
+            let statuses = statuses.lock().unwrap();
+            for dependency in depends_on {
+                match statuses.get(dependency) {
+                    Some(TunnelStatus::Running) => {}
+                    Some(TunnelStatus::Starting | TunnelStatus::Retrying) => {
+                        waiting_for.get_or_insert(dependency);
+                    }
+                    Some(TunnelStatus::Stopped | TunnelStatus::Failed) | None => {
+                        return Err(format!(
+                            "'{dependency}' stopped before '{command_key}' could start"
+                        ));
+                    }
+                }
+            }
+        }
+        let Some(dependency) = waiting_for else {
+            return Ok(());
+        };
+        if !is_active() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Timed out waiting for '{dependency}' to connect before '{command_key}'"
+            ));
+        }
+        thread::sleep(CHILD_POLL_INTERVAL);
+    }
+}
+
+/// Sleep for `duration` in short slices, returning early (with `false`)
+/// as soon as `is_active` reports the tunnel was stopped. A duration too long
+/// to represent as a deadline sleeps until then.
+fn sleep_while(duration: Duration, is_active: &dyn Fn() -> bool) -> bool {
+    let deadline = Instant::now().checked_add(duration);
+    while deadline.is_none_or(|deadline| Instant::now() < deadline) {
+        if !is_active() {
+            return false;
+        }
+        thread::sleep(deadline.map_or(CHILD_POLL_INTERVAL, |deadline| {
+            CHILD_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))
+        }));
+    }
+    is_active()
 }
 
 /// Manages the lifecycle of tunnels (start, stop, cleanup).
@@ -23,21 +344,393 @@ pub struct TunnelCommand {
 #[derive(Clone)]
 pub struct TunnelManager {
     pub commands_config: Arc<Mutex<HashMap<String, TunnelCommand>>>,
-    pub active_tunnels: Arc<Mutex<HashSet<String>>>,
+    /// Enabled tunnels; read through `active_keys` or `is_active`.
+    active_tunnels: Arc<Mutex<HashSet<String>>>,
     pub active_commands: Arc<Mutex<HashMap<String, TunnelCommand>>>,
     pub generations: Arc<Mutex<HashMap<String, u64>>>,
     pub env_path: Arc<Mutex<String>>,
+    /// Child processes spawned for active tunnels, so they can be stopped directly.
+    pub children: Arc<Mutex<HashMap<String, Child>>>,
+    /// Last known status per tunnel; absent keys are `Stopped`.
+    pub statuses: Arc<Mutex<HashMap<String, TunnelStatus>>>,
+    start_slots: Arc<StartSlots>,
+    notifier: Option<SharedNotifier>,
+    tray: Option<SharedTrayUi>,
+    /// Where the active set is saved for `auto_restore`; `None` disables persistence.
+    state_file: Option<PathBuf>,
+    /// Whether `cleanup` stops the active tunnels; see `set_kill_on_quit`.
+    kill_on_quit: Arc<AtomicBool>,
+    spawner: SharedSpawner,
+    events: EventBus,
+}
+
+/// Active tunnel keys as saved between launches.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TunnelState {
+    #[serde(default)]
+    active: Vec<String>,
+}
+
+fn load_tunnel_state(path: &Path) -> Vec<String> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match fs::read_to_string(path).map(|contents| toml::from_str::<TunnelState>(&contents)) {
+        Ok(Ok(state)) => state.active,
+        Ok(Err(e)) => {
+            warn!("Failed to parse tunnel state file: {}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("Failed to read tunnel state file: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save_tunnel_state(path: Option<&PathBuf>, active_tunnels: &Mutex<HashSet<String>>) {
+    let Some(path) = path else {
+        return;
+    };
+    let mut active: Vec<String> = active_tunnels.lock().unwrap().iter().cloned().collect();
+    active.sort();
+
+    if let Some(parent) = path.parent()
+        && let Err(e) = fs::create_dir_all(parent)
+    {
+        error!("Failed to create state directory: {}", e);
+        return;
+    }
+    match toml::to_string_pretty(&TunnelState { active }) {
+        Ok(contents) => {
+            if let Err(e) = write_atomic(path, contents) {
+                error!("Failed to write tunnel state file: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize tunnel state: {}", e),
+    }
+}
+
+/// Tell the tray whether `key` is active and whether any tunnel still is.
+fn publish_state(tray: Option<&SharedTrayUi>, active_tunnels: &Mutex<HashSet<String>>, key: &str) {
+    let Some(tray) = tray else {
+        return;
+    };
+    let (active, any_active) = {
+        let tunnels = active_tunnels.lock().unwrap();
+        (tunnels.contains(key), !tunnels.is_empty())
+    };
+    tray.set_menu_item_state(key, active);
+    tray.set_active(any_active);
+}
+
+/// The tunnel's start command with its PATH, environment, and working directory
+/// set. PATH is resolved by the shell from config; the manager owns it so spawning
+/// never has to touch the config file.
+fn start_command(command: &TunnelCommand, env_path: &str) -> std::process::Command {
+    tunnel_process(command, &command.command, &command.args, env_path)
+}
+
+/// `program args...` set up like the tunnel's start command: through the shell
+/// when it uses one, with the same PATH, environment, and working directory.
+fn tunnel_process(
+    command: &TunnelCommand,
+    program: &str,
+    args: &[String],
+    env_path: &str,
+) -> std::process::Command {
+    let args = expand_env_args(args, env_path, &command.env);
+    let mut cmd = command_for(program, &args, command.shell);
+    cmd.env("PATH", env_path);
+    cmd.envs(&command.env);
+    if let Some(dir) = &command.working_dir {
+        cmd.current_dir(dir);
+    }
+    cmd
+}
+
+/// Let a test child run for up to `timeout`, then stop it, collecting its stderr.
+fn run_test(
+    spawner: &dyn ProcessSpawner,
+    key: &str,
+    mut child: Child,
+    timeout: Duration,
+    kill_timeout: Duration,
+) -> TestReport {
+    // Drained on another thread so a chatty command cannot fill the pipe and stall.
+    let output = child.stderr.take().map(|mut pipe| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            let _ = sender.send(bytes);
+        });
+        receiver
+    });
+
+    let deadline = Instant::now() + timeout;
+    let (exit_code, still_running) = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break (status.code(), false),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                terminate_child(spawner, key, &mut child, kill_timeout);
+                break (None, true);
+            }
+            Err(e) => {
+                warn!("Failed to wait for the test of tunnel '{key}': {e}");
+                let _ = spawner.kill(&mut child);
+                let _ = child.wait();
+                break (None, false);
+            }
+        }
+    };
+    // A helper that escaped the process group could hold the pipe open; don't wait on it.
+    let stderr = output
+        .and_then(|receiver| receiver.recv_timeout(Duration::from_secs(1)).ok())
+        .unwrap_or_default();
+    TestReport {
+        exit_code,
+        still_running,
+        stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+    }
+}
+
+/// Stop a tunnel, preferring the tracked child handle over the configured kill command.
+/// The kill command is only used when no handle exists (e.g. a process started elsewhere).
+fn stop_tunnel(
+    children: &Mutex<HashMap<String, Child>>,
+    spawner: &dyn ProcessSpawner,
+    key: &str,
+    command: Option<&TunnelCommand>,
+    env_path: &str,
+) -> Result<(), String> {
+    let child = children.lock().unwrap().remove(key);
+    if let Some(mut child) = child {
+        info!("Stopping tunnel '{key}' (pid {})", child.id());
+        let timeout = command.map_or(
+            Duration::from_secs(crate::config::DEFAULT_KILL_TIMEOUT_SECS),
+            |command| command.kill_timeout,
+        );
+        terminate_child(spawner, key, &mut child, timeout);
+        return Ok(());
+    }
+
+    match command {
+        Some(command) => stop_command(spawner, key, command, env_path),
+        None => Err(format!(
+            "No command configuration found while stopping '{key}'"
+        )),
+    }
+}
+
+/// Ask the child to exit with SIGTERM, escalating to SIGKILL after `timeout`.
+/// Windows has no SIGTERM equivalent for console processes, so it goes straight
+/// to `TerminateProcess` via `Child::kill`.
+fn terminate_child(spawner: &dyn ProcessSpawner, key: &str, child: &mut Child, timeout: Duration) {
+    #[cfg(unix)]
+    {
+        // Signal the whole process group when the spawner created one, so helpers
+        // the tunnel forked get the chance to exit too; otherwise just the child.
+        let pid = child.id() as libc::pid_t;
+        // SAFETY: the pid belongs to a child we spawned and have not reaped yet.
+        let signalled =
+            unsafe { libc::kill(-pid, libc::SIGTERM) == 0 || libc::kill(pid, libc::SIGTERM) == 0 };
+        if signalled {
+            // Too long to represent as a deadline means waiting until it exits.
+            let deadline = Instant::now().checked_add(timeout);
+            while deadline.is_none_or(|deadline| Instant::now() < deadline) {
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        debug!("Tunnel '{key}' exited after SIGTERM with {status}");
+                        // Helpers that ignored SIGTERM go down with the rest of the group.
+                        let _ = spawner.kill(child);
+                        return;
+                    }
+                    Ok(None) => thread::sleep(Duration::from_millis(50)),
+                    Err(e) => {
+                        warn!("Failed to wait for tunnel '{key}': {e}");
+                        break;
+                    }
+                }
+            }
+            warn!("Tunnel '{key}' did not exit within {timeout:?} of SIGTERM; killing it");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = timeout;
+
+    if let Err(e) = spawner.kill(child) {
+        warn!("Failed to kill tunnel '{key}': {e}");
+    }
+    let _ = child.wait();
+}
+
+/// Block until the tracked child for `key` exits, the tunnel is stopped, or
+/// `probe` fails `health`, which it is run against every interval; the probe's
+/// reason is returned and the child is left for the caller to stop. Returns
+/// without touching the child once `is_active` reports it belongs to someone else.
+fn wait_for_child(
+    children: &Mutex<HashMap<String, Child>>,
+    key: &str,
+    is_active: &dyn Fn() -> bool,
+    health: Option<&HealthCheck>,
+    probe: &dyn Fn(&HealthCheck) -> Result<(), String>,
+) -> Option<String> {
+    // An interval too long to represent as a deadline never comes due.
+    let mut next_probe = health.and_then(|check| Instant::now().checked_add(check.interval));
+    loop {
+        thread::sleep(CHILD_POLL_INTERVAL);
+        {
+            let mut children = children.lock().unwrap();
+            if !is_active() {
+                return None;
+            }
+            let child = children.get_mut(key)?;
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    info!("Tunnel '{key}' process exited with {status}");
+                    children.remove(key);
+                    return None;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Failed to wait for tunnel '{key}': {e}");
+                    children.remove(key);
+                    return None;
+                }
+            }
+        }
+
+        // Probed without the lock held, so a slow check never delays a stop.
+        if let (Some(check), Some(due)) = (health, next_probe)
+            && Instant::now() >= due
+        {
+            if let Err(reason) = probe(check) {
+                return is_active().then_some(reason);
+            }
+            next_probe = Instant::now().checked_add(check.interval);
+        }
+    }
 }
 
-fn stop_command(key: &str, command: &TunnelCommand) -> Result<(), String> {
-    info!("Stopping command: {} {:?}", command.command, command.args);
-    let mut child = Command::new(&command.kill_command)
-        .args(&command.kill_args)
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn()
+/// Run one health probe for a running tunnel.
+fn probe_health(
+    spawner: &dyn ProcessSpawner,
+    command: &TunnelCommand,
+    check: &HealthCheck,
+    env_path: &str,
+) -> Result<(), String> {
+    match &check.probe {
+        HealthProbe::Tcp(address) => {
+            let addrs = address
+                .to_socket_addrs()
+                .map_err(|e| format!("Could not resolve {address}: {e}"))?;
+            let connected = addrs
+                .into_iter()
+                .any(|addr| TcpStream::connect_timeout(&addr, check.timeout).is_ok());
+            if connected {
+                Ok(())
+            } else {
+                Err(format!("{address} did not accept a connection"))
+            }
+        }
+        HealthProbe::Command {
+            command: program,
+            args,
+        } => {
+            let mut cmd = tunnel_process(command, program, args, env_path);
+            cmd.stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null());
+            let mut child = spawner
+                .spawn(&mut cmd)
+                .map_err(|e| format!("Could not run {program}: {e}"))?;
+            let deadline = Instant::now().checked_add(check.timeout);
+            loop {
+                match child.try_wait() {
+                    Ok(Some(status)) if status.success() => return Ok(()),
+                    Ok(Some(status)) => return Err(format!("{program} exited with {status}")),
+                    Ok(None) if deadline.is_none_or(|deadline| Instant::now() < deadline) => {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                    Ok(None) => {
+                        let _ = spawner.kill(&mut child);
+                        let _ = child.wait();
+                        return Err(format!(
+                            "{program} did not finish within {:?}",
+                            check.timeout
+                        ));
+                    }
+                    Err(e) => {
+                        let _ = spawner.kill(&mut child);
+                        let _ = child.wait();
+                        return Err(format!("Failed to wait for {program}: {e}"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Poll `check.address` until it accepts a connection. Gives up (returning `false`)
+/// on timeout, when the tunnel is stopped, or when its process exits first.
+fn wait_until_ready(
+    check: &ReadyCheck,
+    children: &Mutex<HashMap<String, Child>>,
+    key: &str,
+    is_active: &dyn Fn() -> bool,
+) -> bool {
+    let deadline = Instant::now().checked_add(check.timeout);
+    loop {
+        let connected = check.address.to_socket_addrs().is_ok_and(|mut addrs| {
+            addrs.any(|addr| TcpStream::connect_timeout(&addr, CHILD_POLL_INTERVAL).is_ok())
+        });
+        if connected {
+            return true;
+        }
+
+        let mut tracked = children.lock().unwrap();
+        let exited = tracked
+            .get_mut(key)
+            .is_none_or(|child| !matches!(child.try_wait(), Ok(None)));
+        if exited {
+            info!("Tunnel '{key}' process exited before it was ready");
+            tracked.remove(key);
+            return false;
+        }
+        drop(tracked);
+        if !is_active() {
+            return false;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            warn!(
+                "Tunnel '{key}' did not accept connections on {} within {:?}",
+                check.address, check.timeout
+            );
+            return false;
+        }
+        thread::sleep(CHILD_POLL_INTERVAL);
+    }
+}
+
+fn stop_command(
+    spawner: &dyn ProcessSpawner,
+    key: &str,
+    command: &TunnelCommand,
+    env_path: &str,
+) -> Result<(), String> {
+    info!(
+        "Stopping command: {} {:?}",
+        command.kill_command, command.kill_args
+    );
+    let mut stop = tunnel_process(command, &command.kill_command, &command.kill_args, env_path);
+    stop.stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    let mut child = spawner
+        .spawn(&mut stop)
         .map_err(|e| format!("Failed to start stop command for tunnel '{key}': {e}"))?;
-    let deadline = Instant::now() + Duration::from_secs(5);
+    let deadline = Instant::now().checked_add(command.kill_timeout);
 
     loop {
         match child.try_wait() {
@@ -45,14 +738,12 @@ fn stop_command(key: &str, command: &TunnelCommand) -> Result<(), String> {
                 debug!("Tunnel '{key}' stopped successfully");
                 return Ok(());
             }
-            Ok(Some(status)) => {
-                return Err(format!(
-                    "Stop command for tunnel '{key}' exited with status {status}"
-                ));
+            Ok(Some(status)) => return Err(stop_failure(key, command, status.code())),
+            Ok(None) if deadline.is_none_or(|deadline| Instant::now() < deadline) => {
+                thread::sleep(Duration::from_millis(50));
             }
-            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
             Ok(None) => {
-                let _ = child.kill();
+                let _ = spawner.kill(&mut child);
                 let _ = child.wait();
                 return Err(format!("Stop command for tunnel '{key}' timed out"));
             }
@@ -65,133 +756,748 @@ fn stop_command(key: &str, command: &TunnelCommand) -> Result<(), String> {
     }
 }
 
+/// Why a stop command that exited with `code` failed. `pkill`, `killall`, and
+/// `kill` exit with 1 (`taskkill` with 128) when no process matched: the tunnel
+/// was already gone, or its `stop` arguments do not match how it was started and
+/// it is still running.
+fn stop_failure(key: &str, command: &TunnelCommand, code: Option<i32>) -> String {
+    // With `shell = true` the program is the first word of the command line.
+    let program = command
+        .kill_command
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    let name = Path::new(program)
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match (name.as_str(), code) {
+        ("pkill" | "killall" | "kill", Some(1)) | ("taskkill", Some(128)) => format!(
+            "Stop command for tunnel '{key}' matched no running process ({name} exited \
+             with {}); if the tunnel is still running, its stop arguments do not match it",
+            code.unwrap_or_default()
+        ),
+        (_, Some(code)) => format!("Stop command for tunnel '{key}' exited with status {code}"),
+        (_, None) => format!("Stop command for tunnel '{key}' was killed by a signal"),
+    }
+}
+
 impl TunnelManager {
-    /// Toggles a tunnel by name (command_key) on or off.
-    /// If turning on, spawns a thread to run the SSH command.
-    /// If turning off, kills the process.
-    /// Toggle a tunnel on/off. Returns `true` if any tunnels are active after the toggle.
-    pub fn toggle(&self, command_key: &str, enable: bool) -> bool {
+    pub fn new(commands: HashMap<String, TunnelCommand>, env_path: String) -> Self {
+        Self {
+            commands_config: Arc::new(Mutex::new(commands)),
+            active_tunnels: Arc::new(Mutex::new(HashSet::new())),
+            active_commands: Arc::new(Mutex::new(HashMap::new())),
+            generations: Arc::new(Mutex::new(HashMap::new())),
+            env_path: Arc::new(Mutex::new(env_path)),
+            children: Arc::new(Mutex::new(HashMap::new())),
+            statuses: Arc::new(Mutex::new(HashMap::new())),
+            start_slots: Arc::new(StartSlots::default()),
+            notifier: None,
+            tray: None,
+            state_file: None,
+            kill_on_quit: Arc::new(AtomicBool::new(true)),
+            spawner: Arc::new(StdProcessSpawner),
+            events: EventBus::default(),
+        }
+    }
+
+    /// Start tunnel and stop commands through `spawner` instead of `std::process` directly.
+    pub fn set_spawner(&mut self, spawner: SharedSpawner) {
+        self.spawner = spawner;
+    }
+
+    /// Notify the user when a tunnel gives up; without one, failures are only logged.
+    pub fn set_notifier(&mut self, notifier: SharedNotifier) {
+        self.notifier = Some(notifier);
+    }
+
+    /// Keep `tray` in sync with tunnel state, including tunnels that give up on their own.
+    pub fn set_tray_ui(&mut self, tray: SharedTrayUi) {
+        self.tray = Some(tray);
+    }
+
+    /// Subscribe to tunnel lifecycle events. Each call returns an independent receiver.
+    pub fn events(&self) -> Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Let at most `limit` tunnels be starting (spawned but not yet running) at once;
+    /// the others wait their turn. `0` removes the limit. Applies to queued starts too.
+    pub fn set_max_concurrent_starts(&self, limit: usize) {
+        self.start_slots.set_limit(limit);
+    }
+
+    /// Whether `cleanup` stops the active tunnels (the default). When `false`, quitting
+    /// leaves their processes running and forgets them: they are not restored at the
+    /// next launch, the app shows them as off, and stopping them is up to the user.
+    pub fn set_kill_on_quit(&self, kill: bool) {
+        self.kill_on_quit.store(kill, Ordering::SeqCst);
+    }
+
+    /// Save the active set to `path` whenever it changes, for `restore_active` at next launch.
+    pub fn set_state_file(&mut self, path: PathBuf) {
+        self.state_file = Some(path);
+    }
+
+    /// Bring up tunnels at launch: those saved by a previous run (see `restore_active`),
+    /// then every `auto_start` key not already started by the restore.
+    pub fn start_at_launch(&self, auto_start: &[String]) {
+        self.restore_active();
+        for key in auto_start {
+            if !self.active_tunnels.lock().unwrap().contains(key) {
+                info!("Auto-starting tunnel '{key}'");
+                let _ = self.start_delayed(key);
+            }
+        }
+    }
+
+    /// Re-enable the tunnels saved by a previous run, through the normal connect and
+    /// retry path. Keys no longer in the config are skipped.
+    pub fn restore_active(&self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        for key in load_tunnel_state(path) {
+            if self.commands_config.lock().unwrap().contains_key(&key) {
+                info!("Restoring tunnel '{key}' from the previous session");
+                let _ = self.start_delayed(&key);
+            } else {
+                warn!("Not restoring tunnel '{key}': it is no longer configured");
+            }
+        }
+        save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+    }
+
+    /// Current lifecycle status of the tunnel identified by `key`.
+    pub fn tunnel_status(&self, key: &str) -> TunnelStatus {
+        self.statuses
+            .lock()
+            .unwrap()
+            .get(key)
+            .copied()
+            .unwrap_or(TunnelStatus::Stopped)
+    }
+
+    /// Switch tunnel `command_key` on or off, returning `Ok(any_active)` with whether
+    /// any tunnel is active afterwards. Switching off always succeeds. Switching on
+    /// fails with a [`TunnelError`], without spawning anything, when the tunnel is not
+    /// configured, its config is invalid, its program is not on PATH, or its
+    /// dependencies cannot be brought up; see `start`.
+    pub fn toggle(&self, command_key: &str, enable: bool) -> Result<bool, TunnelError> {
         if enable {
-            let command = {
-                let config = self.commands_config.lock().unwrap();
-                config.get(command_key).cloned()
+            self.start(command_key)?;
+            return Ok(self.has_active_tunnels());
+        }
+
+        let was_active = self.active_tunnels.lock().unwrap().remove(command_key);
+        let mut generations = self.generations.lock().unwrap();
+        *generations.entry(command_key.to_owned()).or_default() += 1;
+        drop(generations);
+        self.statuses.lock().unwrap().remove(command_key);
+
+        let command = self
+            .active_commands
+            .lock()
+            .unwrap()
+            .remove(command_key)
+            .or_else(|| {
+                self.commands_config
+                    .lock()
+                    .unwrap()
+                    .get(command_key)
+                    .cloned()
+            });
+
+        let env_path = self.env_path.lock().unwrap().clone();
+        match stop_tunnel(
+            &self.children,
+            self.spawner.as_ref(),
+            command_key,
+            command.as_ref(),
+            &env_path,
+        ) {
+            Ok(()) => {}
+            // An active tunnel without a tracked process was between attempts (backing
+            // off, queued, or waiting on a dependency), so there was nothing to stop.
+            Err(e) if was_active => debug!("{e}"),
+            Err(e) => {
+                error!("{e}");
+                // The stop command ran for a process this app was not tracking, which
+                // may have been left behind.
+                if command.is_some()
+                    && let Some(notifier) = &self.notifier
+                {
+                    notifier.warn(&format!("Tunnel '{command_key}' may still be running"), &e);
+                }
+            }
+        }
+        if was_active {
+            self.events.emit(Event::TunnelStopped {
+                key: command_key.to_owned(),
+            });
+        }
+
+        publish_state(self.tray.as_ref(), &self.active_tunnels, command_key);
+        save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+        Ok(self.has_active_tunnels())
+    }
+
+    /// Start the tunnel unless it is already active. Returns `true` only when a new
+    /// connection was started; an already-active tunnel is left as is.
+    /// Tunnels it `depends_on` are started first, or, without `start_dependencies`,
+    /// must already be on; otherwise it fails without starting. A refused start is
+    /// also reported like a failed one: status, event, and notification.
+    pub fn start(&self, command_key: &str) -> Result<bool, TunnelError> {
+        self.start_chain(command_key, &mut Vec::new(), false)
+    }
+
+    /// `start` for launch, waiting out the tunnel's `start_delay` first. The tunnel
+    /// is active (and connecting) meanwhile, so switching it off cancels the wait.
+    fn start_delayed(&self, command_key: &str) -> Result<bool, TunnelError> {
+        self.start_chain(command_key, &mut Vec::new(), true)
+    }
+
+    /// `start`, with `starting` holding the dependents further up the chain.
+    fn start_chain(
+        &self,
+        command_key: &str,
+        starting: &mut Vec<String>,
+        delayed: bool,
+    ) -> Result<bool, TunnelError> {
+        let command = {
+            let config = self.commands_config.lock().unwrap();
+            config.get(command_key).cloned()
+        };
+        let Some(command) = command else {
+            // Stale menu item or a tunnel removed by a config reload.
+            warn!("No command configuration found while starting '{command_key}'");
+            self.active_tunnels.lock().unwrap().remove(command_key);
+            self.active_commands.lock().unwrap().remove(command_key);
+            self.statuses.lock().unwrap().remove(command_key);
+            publish_state(self.tray.as_ref(), &self.active_tunnels, command_key);
+            save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+            return Err(TunnelError::NotFound(command_key.to_owned()));
+        };
+        if self.active_tunnels.lock().unwrap().contains(command_key) {
+            debug!("Tunnel '{command_key}' is already active; not starting it again");
+            return Ok(false);
+        }
+        if let Some(reason) = &command.invalid {
+            let error = TunnelError::Invalid {
+                tunnel: command_key.to_owned(),
+                reason: reason.clone(),
+            };
+            self.fail_start(command_key, &error.to_string());
+            return Err(error);
+        }
+        // The same lookup the spawn does, so the common typo fails here and not in
+        // the background thread.
+        let path = command
+            .env
+            .get("PATH")
+            .cloned()
+            .unwrap_or_else(|| self.env_path.lock().unwrap().clone());
+        if !command.shell && !program_exists(&command.command, &path) {
+            let error = TunnelError::CommandNotFound {
+                tunnel: command_key.to_owned(),
+                command: command.command.clone(),
+                path,
+            };
+            self.fail_start(command_key, &error.to_string());
+            return Err(error);
+        }
+        if let Err(reason) = self.start_dependencies(command_key, &command, starting) {
+            self.fail_start(command_key, &reason);
+            return Err(TunnelError::Dependency {
+                tunnel: command_key.to_owned(),
+                reason,
+            });
+        }
+
+        // Claiming the key and bumping the generation under one lock keeps a second
+        // start (a double click, or auto start racing the user) from spawning a
+        // second process that would fight the first over the local port.
+        let generation = {
+            let mut active = self.active_tunnels.lock().unwrap();
+            if !active.insert(command_key.to_owned()) {
+                debug!("Tunnel '{command_key}' is already active; not starting it again");
+                return Ok(false);
+            }
+            let mut generations = self.generations.lock().unwrap();
+            let generation = generations.entry(command_key.to_owned()).or_default();
+            *generation += 1;
+            *generation
+        };
+        self.active_commands
+            .lock()
+            .unwrap()
+            .insert(command_key.to_owned(), command.clone());
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(command_key.to_owned(), TunnelStatus::Starting);
+        let delay = if delayed {
+            command.start_delay
+        } else {
+            Duration::ZERO
+        };
+        self.spawn_loop(command_key, command, generation, delay);
+        publish_state(self.tray.as_ref(), &self.active_tunnels, command_key);
+        save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+        Ok(true)
+    }
+
+    /// Make sure every tunnel `command` depends on is enabled, starting the inactive
+    /// ones when `start_dependencies` allows it.
+    fn start_dependencies(
+        &self,
+        command_key: &str,
+        command: &TunnelCommand,
+        starting: &mut Vec<String>,
+    ) -> Result<(), String> {
+        starting.push(command_key.to_owned());
+        let result = command.depends_on.iter().try_for_each(|dependency| {
+            if starting.contains(dependency) {
+                return Err(format!(
+                    "'{command_key}' and '{dependency}' depend on each other"
+                ));
+            }
+            if self.active_tunnels.lock().unwrap().contains(dependency) {
+                return Ok(());
+            }
+            if !command.start_dependencies {
+                return Err(format!(
+                    "Connect '{dependency}' first; '{command_key}' depends on it"
+                ));
+            }
+            info!("Starting '{dependency}' first; '{command_key}' depends on it");
+            let _ = self.start_chain(dependency, starting, false);
+            if self.active_tunnels.lock().unwrap().contains(dependency) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Could not start '{dependency}', which '{command_key}' depends on"
+                ))
+            }
+        });
+        starting.pop();
+        result
+    }
+
+    /// Report a tunnel that was refused before anything was spawned for it.
+    fn fail_start(&self, command_key: &str, reason: &str) {
+        error!("Tunnel '{command_key}' not started: {reason}");
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(command_key.to_owned(), TunnelStatus::Failed);
+        publish_state(self.tray.as_ref(), &self.active_tunnels, command_key);
+        self.events.emit(Event::TunnelFailed {
+            key: command_key.to_owned(),
+            error: reason.to_string(),
+        });
+        if let Some(notifier) = &self.notifier {
+            notifier.error(&format!("Tunnel '{command_key}' not started"), reason);
+        }
+    }
+
+    /// Keep the tunnel's process running for as long as `generation` is current,
+    /// restarting it with backoff when it exits.
+    fn spawn_loop(
+        &self,
+        command_key: &str,
+        command: TunnelCommand,
+        generation: u64,
+        delay: Duration,
+    ) {
+        let active_tunnels = self.active_tunnels.clone();
+        let active_commands = self.active_commands.clone();
+        let generations = self.generations.clone();
+        let children = self.children.clone();
+        let statuses = self.statuses.clone();
+        let start_slots = self.start_slots.clone();
+        let command_key = command_key.to_owned();
+        let env_path = self.env_path.lock().unwrap().clone();
+        let notifier = self.notifier.clone();
+        let tray = self.tray.clone();
+        let state_file = self.state_file.clone();
+        let spawner = self.spawner.clone();
+        let events = self.events.clone();
+
+        thread::spawn(move || {
+            let mut failures: u32 = 0;
+
+            // Define closure to check if tunnel is still active
+            let is_active = || {
+                active_tunnels.lock().unwrap().contains(&command_key)
+                    && generations
+                        .lock()
+                        .unwrap()
+                        .get(&command_key)
+                        .is_some_and(|current| *current == generation)
             };
-            let Some(command) = command else {
-                warn!("No command configuration found while starting '{command_key}'");
-                return self.has_active_tunnels();
+            // Holding the status lock across the check keeps a concurrent stop
+            // from being overwritten by this (now stale) thread.
+            let set_status = |status| {
+                let mut statuses = statuses.lock().unwrap();
+                if is_active() {
+                    statuses.insert(command_key.clone(), status);
+                }
             };
 
-            let generation = {
-                let mut generations = self.generations.lock().unwrap();
-                let generation = generations.entry(command_key.to_owned()).or_default();
-                *generation += 1;
-                *generation
+            // Drop the tunnel from the active set so the UI reflects the failure.
+            let mark_failed = |reason: &str| {
+                let mut statuses = statuses.lock().unwrap();
+                let mut active = active_tunnels.lock().unwrap();
+                let current = generations.lock().unwrap().get(&command_key).copied();
+                if current != Some(generation) {
+                    return;
+                }
+                active.remove(&command_key);
+                active_commands.lock().unwrap().remove(&command_key);
+                statuses.insert(command_key.clone(), TunnelStatus::Failed);
+                drop(active);
+                drop(statuses);
+                publish_state(tray.as_ref(), &active_tunnels, &command_key);
+                save_tunnel_state(state_file.as_ref(), &active_tunnels);
+                events.emit(Event::TunnelFailed {
+                    key: command_key.clone(),
+                    error: reason.to_string(),
+                });
+                if let Some(notifier) = &notifier {
+                    notifier.error(&format!("Tunnel '{command_key}' stopped"), reason);
+                }
             };
-            self.active_tunnels
-                .lock()
-                .unwrap()
-                .insert(command_key.to_owned());
-            self.active_commands
-                .lock()
-                .unwrap()
-                .insert(command_key.to_owned(), command.clone());
-
-            let active_tunnels = self.active_tunnels.clone();
-            let generations = self.generations.clone();
-            let command_key = command_key.to_owned();
-            let env_path = self.env_path.lock().unwrap().clone();
-
-            thread::spawn(move || {
-                let mut attempts = 0;
-
-                // Define closure to check if tunnel is still active
-                let is_active = || {
-                    active_tunnels.lock().unwrap().contains(&command_key)
-                        && generations
-                            .lock()
-                            .unwrap()
-                            .get(&command_key)
-                            .is_some_and(|current| *current == generation)
+
+            if !delay.is_zero() {
+                info!("Starting tunnel '{command_key}' in {delay:?}");
+                if !sleep_while(delay, &is_active) {
+                    return;
+                }
+            }
+
+            while is_active() {
+                // Checked before every attempt, so a retry never runs against a
+                // dependency that has stopped or failed in the meantime.
+                if let Err(reason) =
+                    wait_for_dependencies(&command_key, &command.depends_on, &statuses, &is_active)
+                {
+                    warn!("Tunnel '{command_key}' not started: {reason}");
+                    mark_failed(&reason);
+                    break;
+                }
+
+                if let Some(dir) = &command.working_dir
+                    && !dir.is_dir()
+                {
+                    error!(
+                        "Working directory for tunnel '{command_key}' does not exist: {}",
+                        dir.display()
+                    );
+                    mark_failed(&format!(
+                        "Working directory {} does not exist",
+                        dir.display()
+                    ));
+                    break;
+                }
+
+                // Held until the attempt is running or has failed; retries queue again.
+                let Some(slot) = start_slots.acquire(&command_key, &is_active) else {
+                    break;
                 };
+                info!(
+                    "Spawning command: {} {:?} (attempt {})",
+                    command.command,
+                    command.args,
+                    failures + 1
+                );
 
-                while is_active() && attempts < 5 {
-                    info!(
-                        "Spawning command: {} {:?} (attempt {})",
-                        command.command, command.args, attempts
-                    );
+                debug!("Spawning with PATH: {env_path}");
+                let mut cmd = start_command(&command, &env_path);
+
+                // Discard the output (silence the process)
+                cmd.stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null());
 
-                    let mut cmd = Command::new(&command.command);
-
-                    // PATH to use for subprocesses (provided by platform/app)
-                    let config_path = env_path.clone();
-
-                    // Update PATH to include configured paths
-                    let new_path = cmd
-                        .get_envs()
-                        .find(|(key, _)| key == &OsStr::new("PATH"))
-                        .and_then(|(_, value)| {
-                            value.map(|path| format!("{}:{}", config_path, path.to_string_lossy()))
-                        })
-                        .unwrap_or_else(|| config_path.clone());
-
-                    debug!("Update PATH to: {new_path}");
-                    cmd.env("PATH", new_path);
-
-                    match cmd
-                        .args(&command.args)
-                        // Discard the output (silence the process)
-                        .stdout(std::process::Stdio::null())
-                        .stderr(std::process::Stdio::null())
-                        .spawn()
-                    {
-                        Ok(mut child) => {
-                            info!("Tunnel process started");
+                let mut running_since = None;
+                match spawner.spawn(&mut cmd) {
+                    Ok(mut child) => {
+                        info!("Tunnel process started (pid {})", child.id());
+                        let mut tracked = children.lock().unwrap();
+                        if !is_active() {
+                            // Stopped while spawning; nobody else knows about this child.
+                            drop(tracked);
+                            let _ = spawner.kill(&mut child);
                             let _ = child.wait();
+                            break;
+                        }
+                        tracked.insert(command_key.clone(), child);
+                        drop(tracked);
+
+                        // Stay `Starting` (connecting) until the forward is usable.
+                        let ready = match &command.ready_check {
+                            Some(check) => {
+                                set_status(TunnelStatus::Starting);
+                                wait_until_ready(check, &children, &command_key, &is_active)
+                            }
+                            None => true,
+                        };
+                        drop(slot);
+                        if ready {
+                            set_status(TunnelStatus::Running);
+                            running_since = Some(Instant::now());
+                            if is_active() {
+                                events.emit(Event::TunnelStarted {
+                                    key: command_key.clone(),
+                                });
+                            }
+                            let probe = |check: &HealthCheck| {
+                                probe_health(spawner.as_ref(), &command, check, &env_path)
+                            };
+                            let unhealthy = wait_for_child(
+                                &children,
+                                &command_key,
+                                &is_active,
+                                command.health_check.as_ref(),
+                                &probe,
+                            );
+                            if let Some(reason) = unhealthy {
+                                warn!(
+                                    "Tunnel '{command_key}' failed its health check: {reason}; restarting it"
+                                );
+                                if let Some(notifier) = &notifier {
+                                    notifier.warn(
+                                        &format!("Tunnel '{command_key}' is not responding"),
+                                        &format!("{reason}; restarting it"),
+                                    );
+                                }
+                                let mut tracked = children.lock().unwrap();
+                                let child = if is_active() {
+                                    tracked.remove(&command_key)
+                                } else {
+                                    None
+                                };
+                                drop(tracked);
+                                if let Some(mut child) = child {
+                                    terminate_child(
+                                        spawner.as_ref(),
+                                        &command_key,
+                                        &mut child,
+                                        command.kill_timeout,
+                                    );
+                                }
+                            }
+                        } else if is_active()
+                            && children.lock().unwrap().contains_key(&command_key)
+                            && let Err(e) = stop_tunnel(
+                                &children,
+                                spawner.as_ref(),
+                                &command_key,
+                                Some(&command),
+                                &env_path,
+                            )
+                        {
+                            error!("{e}");
                         }
-                        Err(e) => error!("Failed to start tunnel command: {}", e),
                     }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound && !command.shell => {
+                        // Retrying cannot help until the PATH or the install changes.
+                        let reason = command_not_found(&command.command, &env_path);
+                        error!("Failed to start tunnel '{command_key}': {reason}");
+                        mark_failed(&reason);
+                        break;
+                    }
+                    Err(e) => {
+                        drop(slot);
+                        error!("Failed to start tunnel command: {}", e);
+                    }
+                }
+
+                if !is_active() {
+                    break;
+                }
+
+                failures =
+                    failures_after_exit(failures, running_since.map(|since| since.elapsed()));
 
-                    attempts += 1;
+                if command.max_retries != 0 && failures > command.max_retries {
+                    warn!(
+                        "Tunnel '{command_key}' failed {failures} times; giving up after {} retries",
+                        command.max_retries
+                    );
+                    mark_failed(&format!(
+                        "Gave up after {} failed restarts",
+                        command.max_retries
+                    ));
+                    break;
                 }
 
-                if attempts == 5 {
-                    warn!("Failed to start command after 5 attempts");
+                set_status(TunnelStatus::Retrying);
+                let delay = retry_delay(command.retry_backoff, failures);
+                info!("Restarting tunnel '{command_key}' in {delay:?}");
+                if !sleep_while(delay, &is_active) {
+                    break;
                 }
+            }
+        });
+    }
+
+    /// What tunnel `command_key` spawns, for pasting into a terminal; `None` for
+    /// unknown tunnels.
+    pub fn effective_command_line(&self, command_key: &str) -> Option<String> {
+        let path = self.env_path.lock().unwrap().clone();
+        let commands = self.commands_config.lock().unwrap();
+        Some(commands.get(command_key)?.effective_command_line(&path))
+    }
+
+    /// Run tunnel `command_key`'s start command for up to [`TEST_TIMEOUT`] with its
+    /// stderr captured, then stop it, to check the host is reachable and auth works
+    /// without leaving the tunnel on. Blocks until done, and also reports the result
+    /// through the notifier. Refused while the tunnel is on.
+    pub fn test(&self, command_key: &str) -> Result<TestReport, TunnelError> {
+        self.test_for(command_key, TEST_TIMEOUT)
+    }
+
+    fn test_for(&self, command_key: &str, timeout: Duration) -> Result<TestReport, TunnelError> {
+        let result = self.run_test(command_key, timeout);
+        match &result {
+            Ok(report) if report.passed() => info!("Tunnel '{command_key}' test passed"),
+            Ok(report) => warn!("Tunnel '{command_key}' test failed: {}", report.summary()),
+            Err(e) => warn!("Tunnel '{command_key}' not tested: {e}"),
+        }
+        if let Some(notifier) = &self.notifier {
+            match &result {
+                Ok(report) if report.passed() => notifier.info(
+                    &format!("Tunnel '{command_key}' test passed"),
+                    &report.summary(),
+                ),
+                Ok(report) => notifier.error(
+                    &format!("Tunnel '{command_key}' test failed"),
+                    &report.summary(),
+                ),
+                Err(e) => notifier.error(
+                    &format!("Tunnel '{command_key}' not tested"),
+                    &e.to_string(),
+                ),
+            }
+        }
+        result
+    }
+
+    fn run_test(&self, command_key: &str, timeout: Duration) -> Result<TestReport, TunnelError> {
+        let command = self
+            .commands_config
+            .lock()
+            .unwrap()
+            .get(command_key)
+            .cloned();
+        let Some(command) = command else {
+            return Err(TunnelError::NotFound(command_key.to_owned()));
+        };
+        if self.active_tunnels.lock().unwrap().contains(command_key) {
+            return Err(TunnelError::Active(command_key.to_owned()));
+        }
+        if let Some(reason) = command.invalid {
+            return Err(TunnelError::Invalid {
+                tunnel: command_key.to_owned(),
+                reason,
             });
-        } else {
-            self.active_tunnels.lock().unwrap().remove(command_key);
-            let mut generations = self.generations.lock().unwrap();
-            *generations.entry(command_key.to_owned()).or_default() += 1;
-            drop(generations);
+        }
+        let env_path = self.env_path.lock().unwrap().clone();
+        let path = command
+            .env
+            .get("PATH")
+            .cloned()
+            .unwrap_or_else(|| env_path.clone());
+        if !command.shell && !program_exists(&command.command, &path) {
+            return Err(TunnelError::CommandNotFound {
+                tunnel: command_key.to_owned(),
+                command: command.command.clone(),
+                path,
+            });
+        }
 
-            let command = self
-                .active_commands
-                .lock()
-                .unwrap()
-                .remove(command_key)
-                .or_else(|| {
-                    self.commands_config
-                        .lock()
-                        .unwrap()
-                        .get(command_key)
-                        .cloned()
-                });
+        info!("Testing tunnel '{command_key}' for up to {timeout:?}");
+        let mut cmd = start_command(&command, &env_path);
+        cmd.stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped());
+        Ok(match self.spawner.spawn(&mut cmd) {
+            Ok(child) => run_test(
+                self.spawner.as_ref(),
+                command_key,
+                child,
+                timeout,
+                command.kill_timeout,
+            ),
+            Err(e) => TestReport {
+                exit_code: None,
+                still_running: false,
+                stderr: format!("Failed to start: {e}"),
+            },
+        })
+    }
 
-            if let Some(command) = command {
-                if let Err(e) = stop_command(command_key, &command) {
-                    error!("{e}");
-                }
-            } else {
-                warn!("No command configuration found while stopping '{command_key}'");
+    /// Kill the tunnel's process and start a fresh one without it ever leaving the
+    /// active set. Bumping the generation first retires the old spawn loop, so it
+    /// cannot restart or mark failed the process it was watching. Returns `false`
+    /// when the tunnel is not active.
+    pub fn reconnect(&self, command_key: &str) -> bool {
+        let generation = {
+            let active = self.active_tunnels.lock().unwrap();
+            if !active.contains(command_key) {
+                warn!("Not reconnecting '{command_key}': the tunnel is not active");
+                return false;
             }
+            let mut generations = self.generations.lock().unwrap();
+            let generation = generations.entry(command_key.to_owned()).or_default();
+            *generation += 1;
+            *generation
+        };
+
+        let command = self
+            .active_commands
+            .lock()
+            .unwrap()
+            .get(command_key)
+            .cloned();
+        let Some(command) = command else {
+            warn!("No command configuration found while reconnecting '{command_key}'");
+            return false;
+        };
+
+        info!("Reconnecting tunnel '{command_key}'");
+        // Moved out first so the lock is not held while the old process exits.
+        let child = self.children.lock().unwrap().remove(command_key);
+        if let Some(mut child) = child {
+            terminate_child(
+                self.spawner.as_ref(),
+                command_key,
+                &mut child,
+                command.kill_timeout,
+            );
         }
 
-        self.has_active_tunnels()
+        {
+            // A toggle off while the old process was stopping wins over the reconnect.
+            let mut statuses = self.statuses.lock().unwrap();
+            let active = self.active_tunnels.lock().unwrap();
+            let current = self.generations.lock().unwrap().get(command_key).copied();
+            if !active.contains(command_key) || current != Some(generation) {
+                return false;
+            }
+            statuses.insert(command_key.to_owned(), TunnelStatus::Starting);
+        }
+        self.spawn_loop(command_key, command, generation, Duration::ZERO);
+        true
     }
 
     /// Apply new definitions, restarting only active tunnels affected by the change.
     pub fn reconfigure(&self, commands: HashMap<String, TunnelCommand>, env_path: String) {
-        let path_changed = *self.env_path.lock().unwrap() != env_path;
+        // Tunnels are stopped the way they were started, with the old PATH.
+        let old_path = self.env_path.lock().unwrap().clone();
+        let path_changed = old_path != env_path;
         let active_commands = self.active_commands.lock().unwrap().clone();
         let affected: Vec<String> = active_commands
             .iter()
@@ -210,12 +1516,19 @@ impl TunnelManager {
             let Some(active_command) = active_commands.get(key) else {
                 continue;
             };
-            if let Err(e) = stop_command(key, active_command) {
+            if let Err(e) = stop_tunnel(
+                &self.children,
+                self.spawner.as_ref(),
+                key,
+                Some(active_command),
+                &old_path,
+            ) {
                 error!("Config reload could not restart tunnel '{key}': {e}");
                 continue;
             }
             self.active_tunnels.lock().unwrap().remove(key);
             self.active_commands.lock().unwrap().remove(key);
+            self.statuses.lock().unwrap().remove(key);
         }
 
         *self.commands_config.lock().unwrap() = commands;
@@ -225,28 +1538,80 @@ impl TunnelManager {
             if !self.active_tunnels.lock().unwrap().contains(&key)
                 && self.commands_config.lock().unwrap().contains_key(&key)
             {
-                self.toggle(&key, true);
+                let _ = self.toggle(&key, true);
+            } else {
+                publish_state(self.tray.as_ref(), &self.active_tunnels, &key);
             }
         }
+        save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
     }
 
-    /// Cleans up all tunnels when the app terminates.
+    /// Add tunnel `key`, or replace its command. An active tunnel whose command
+    /// changed is restarted with the new one, as on a config reload.
+    pub fn upsert(&self, key: String, command: TunnelCommand) {
+        let mut commands = self.commands_config.lock().unwrap().clone();
+        commands.insert(key, command);
+        let env_path = self.env_path.lock().unwrap().clone();
+        self.reconfigure(commands, env_path);
+    }
+
+    /// Remove tunnel `key`, stopping it first if it is active. Returns `false`
+    /// for unknown keys.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut commands = self.commands_config.lock().unwrap().clone();
+        if commands.remove(key).is_none() {
+            return false;
+        }
+        let env_path = self.env_path.lock().unwrap().clone();
+        self.reconfigure(commands, env_path);
+        true
+    }
+
+    /// Cleans up all tunnels when the app terminates. The saved active set is
+    /// written first and left as is, so `restore_active` brings them back.
+    /// Without `kill_on_quit` the processes are left running and the saved set is
+    /// cleared instead, as there is nothing to bring back.
     pub fn cleanup(&self) {
+        let kill = self.kill_on_quit.load(Ordering::SeqCst);
+        if kill && self.has_active_tunnels() {
+            save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+        }
         let active_commands = self.active_commands.lock().unwrap().clone();
-        let mut active = self.active_tunnels.lock().unwrap();
+        let env_path = self.env_path.lock().unwrap().clone();
+        // Clear all active. The lock is released before stopping anything: tunnel
+        // threads take `children` and then `active_tunnels`, so holding this one
+        // while `stop_tunnel` waits on `children` can deadlock.
+        let stopped: Vec<String> = self.active_tunnels.lock().unwrap().drain().collect();
 
-        for key in active.iter() {
-            debug!("Cleaning up tunnel: {}", key);
-            if let Some(command) = active_commands.get(key)
-                && let Err(e) = stop_command(key, command)
-            {
-                error!("{e}");
+        if kill {
+            for key in &stopped {
+                debug!("Cleaning up tunnel: {}", key);
+                if let Err(e) = stop_tunnel(
+                    &self.children,
+                    self.spawner.as_ref(),
+                    key,
+                    active_commands.get(key),
+                    &env_path,
+                ) {
+                    error!("{e}");
+                }
             }
         }
 
-        // Clear all active
-        active.clear();
+        if !kill {
+            // The tunnel threads stop watching once their key is inactive. Dropping a
+            // `Child` neither kills nor reaps it, and the spawner already moved it out
+            // of our process group (or into its own job), so it outlives the app.
+            info!("Leaving {} tunnel(s) running on quit", stopped.len());
+            self.children.lock().unwrap().clear();
+            save_tunnel_state(self.state_file.as_ref(), &self.active_tunnels);
+        }
         self.active_commands.lock().unwrap().clear();
+        self.statuses.lock().unwrap().clear();
+        for key in stopped {
+            publish_state(self.tray.as_ref(), &self.active_tunnels, &key);
+            self.events.emit(Event::TunnelStopped { key });
+        }
         debug!("All tunnels cleaned up");
     }
 
@@ -283,4 +1648,984 @@ impl TunnelManager {
         let tunnels = self.active_tunnels.lock().unwrap();
         !tunnels.is_empty()
     }
+
+    /// Number of tunnels currently enabled, e.g. for `scheduler::tray_tooltip`.
+    pub fn active_count(&self) -> usize {
+        self.active_tunnels.lock().unwrap().len()
+    }
+
+    /// Whether tunnel `command_key` is enabled (it may still be starting or retrying).
+    pub fn is_active(&self, command_key: &str) -> bool {
+        self.active_tunnels.lock().unwrap().contains(command_key)
+    }
+
+    /// The enabled tunnels' keys, sorted; a snapshot that later toggles don't change.
+    pub fn active_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .active_tunnels
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::TrayUi;
+    use std::process::Command;
+
+    #[cfg(unix)]
+    fn manager_with(key: &str, command: &str, args: &[&str]) -> TunnelManager {
+        let command = TunnelCommand {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            kill_command: "false".to_string(),
+            kill_args: Vec::new(),
+            kill_timeout: Duration::from_secs(1),
+            max_retries: 1,
+            retry_backoff: Duration::ZERO,
+            working_dir: None,
+            env: HashMap::new(),
+            shell: false,
+            ready_check: None,
+            health_check: None,
+            start_delay: Duration::ZERO,
+            depends_on: Vec::new(),
+            start_dependencies: true,
+            invalid: None,
+        };
+        TunnelManager::new(
+            HashMap::from([(key.to_string(), command)]),
+            std::env::var("PATH").unwrap_or_default(),
+        )
+    }
+
+    #[cfg(unix)]
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn a_healthy_run_resets_the_failure_count() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(failures_after_exit(5, Some(hour)), 1);
+        assert_eq!(failures_after_exit(5, Some(HEALTHY_UPTIME)), 1);
+        assert_eq!(failures_after_exit(2, Some(Duration::from_secs(1))), 3);
+        assert_eq!(failures_after_exit(2, None), 3);
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let base = Duration::from_secs(2);
+        assert_eq!(retry_delay(base, 1), Duration::from_secs(2));
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(8));
+        assert_eq!(retry_delay(base, 40), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_delay(Duration::ZERO, 5), Duration::ZERO);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn an_unbounded_kill_timeout_does_not_panic_when_stopping() {
+        let manager = manager_with("huge", "sleep", &["30"]);
+        manager
+            .commands_config
+            .lock()
+            .unwrap()
+            .get_mut("huge")
+            .unwrap()
+            .kill_timeout = Duration::from_secs(u64::MAX);
+
+        manager.start("huge").unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("huge") == TunnelStatus::Running
+        ));
+        // The tracked child exits on SIGTERM, well before the timeout.
+        assert_eq!(manager.toggle("huge", false), Ok(false));
+        assert!(!manager.children.lock().unwrap().contains_key("huge"));
+        // With no child left the stop command runs, and fails at once.
+        assert_eq!(manager.toggle("huge", false), Ok(false));
+    }
+
+    #[test]
+    fn an_unbounded_ready_timeout_still_ends_when_the_process_exits() {
+        let check = ReadyCheck {
+            address: "127.0.0.1:1".to_string(),
+            timeout: Duration::from_secs(u64::MAX),
+        };
+        let children = Mutex::new(HashMap::new());
+        assert!(!wait_until_ready(&check, &children, "gone", &|| true));
+    }
+
+    #[test]
+    fn an_unbounded_delay_still_ends_when_the_tunnel_stops() {
+        let active = AtomicBool::new(true);
+        let stopper = || {
+            thread::sleep(Duration::from_millis(300));
+            active.store(false, Ordering::SeqCst);
+        };
+        thread::scope(|scope| {
+            scope.spawn(stopper);
+            assert!(!sleep_while(Duration::MAX, &|| active.load(Ordering::SeqCst)));
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn gives_up_and_deactivates_after_exhausting_retries() {
+        let manager = manager_with("flaky", "false", &[]);
+        manager.toggle("flaky", true).unwrap();
+        assert!(wait_until(|| !manager.has_active_tunnels()));
+        assert!(manager.active_commands.lock().unwrap().is_empty());
+        assert_eq!(manager.tunnel_status("flaky"), TunnelStatus::Failed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tray_follows_toggles_and_tunnels_that_give_up() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<(String, bool)>>);
+        impl TrayUi for Recorder {
+            fn refresh(&self) {}
+            fn set_active(&self, _any_active: bool) {}
+            fn set_menu_item_state(&self, key: &str, active: bool) {
+                self.0.lock().unwrap().push((key.to_string(), active));
+            }
+        }
+
+        let tray = Arc::new(Recorder::default());
+        let mut manager = manager_with("flaky", "false", &[]);
+        manager.set_tray_ui(tray.clone());
+        manager.toggle("flaky", true).unwrap();
+        assert!(wait_until(|| tray.0.lock().unwrap().len() == 2));
+        assert_eq!(
+            *tray.0.lock().unwrap(),
+            vec![("flaky".to_string(), true), ("flaky".to_string(), false)]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ready_check_gates_running_status() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().to_string();
+        let closed = {
+            let unused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            unused.local_addr().unwrap().to_string()
+        };
+        let with_check = |address: &str| {
+            let manager = manager_with("forward", "sleep", &["30"]);
+            manager
+                .commands_config
+                .lock()
+                .unwrap()
+                .get_mut("forward")
+                .unwrap()
+                .ready_check = Some(ReadyCheck {
+                address: address.to_string(),
+                timeout: Duration::from_millis(300),
+            });
+            manager
+        };
+
+        let ready = with_check(&open);
+        ready.toggle("forward", true).unwrap();
+        assert!(wait_until(
+            || ready.tunnel_status("forward") == TunnelStatus::Running
+        ));
+        ready.cleanup();
+
+        let never_ready = with_check(&closed);
+        never_ready.toggle("forward", true).unwrap();
+        assert!(wait_until(
+            || never_ready.tunnel_status("forward") == TunnelStatus::Failed
+        ));
+        assert!(never_ready.children.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn emits_lifecycle_events() {
+        let manager = manager_with("events", "sleep", &["30"]);
+        let events = manager.events();
+        let next = || events.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        manager.toggle("events", true).unwrap();
+        assert_eq!(
+            next(),
+            Event::TunnelStarted {
+                key: "events".into()
+            }
+        );
+        manager.toggle("events", false).unwrap();
+        assert_eq!(
+            next(),
+            Event::TunnelStopped {
+                key: "events".into()
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stopping_a_tunnel_also_stops_the_processes_it_started() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let pid_file = std::env::temp_dir().join(format!(
+            "something-bg-helper-{}-{unique}.pid",
+            std::process::id()
+        ));
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let manager = manager_with("forks", "sh", &["-c", &script]);
+
+        manager.toggle("forks", true).unwrap();
+        assert!(wait_until(
+            || fs::read_to_string(&pid_file).is_ok_and(|pid| pid.ends_with('\n'))
+        ));
+        let helper: libc::pid_t = fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        manager.toggle("forks", false).unwrap();
+
+        // SAFETY: signal 0 only checks whether the process still exists.
+        assert!(wait_until(|| unsafe { libc::kill(helper, 0) } != 0));
+        let _ = fs::remove_file(pid_file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reconnect_replaces_the_process_without_deactivating() {
+        let manager = manager_with("reconnect", "sleep", &["30"]);
+        let events = manager.events();
+        let next = || events.recv_timeout(Duration::from_secs(5)).unwrap();
+        let pid = || {
+            manager
+                .children
+                .lock()
+                .unwrap()
+                .get("reconnect")
+                .map(Child::id)
+        };
+
+        assert!(!manager.reconnect("reconnect"));
+        manager.toggle("reconnect", true).unwrap();
+        assert_eq!(
+            next(),
+            Event::TunnelStarted {
+                key: "reconnect".into()
+            }
+        );
+        let first = pid();
+
+        assert!(manager.reconnect("reconnect"));
+        assert!(manager.has_active_tunnels());
+        assert_eq!(
+            next(),
+            Event::TunnelStarted {
+                key: "reconnect".into()
+            }
+        );
+        assert!(pid().is_some() && pid() != first);
+        assert_eq!(manager.tunnel_status("reconnect"), TunnelStatus::Running);
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restarts_a_running_tunnel_that_fails_its_health_check() {
+        let manager = manager_with("forward", "sleep", &["30"]);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        {
+            let mut commands = manager.commands_config.lock().unwrap();
+            let forward = commands.get_mut("forward").unwrap();
+            forward.max_retries = 0;
+            forward.health_check = Some(HealthCheck {
+                probe: HealthProbe::Tcp(address),
+                interval: Duration::from_millis(100),
+                timeout: Duration::from_secs(1),
+            });
+        }
+        let events = manager.events();
+        let next = || events.recv_timeout(Duration::from_secs(5)).unwrap();
+        let pid = || {
+            manager
+                .children
+                .lock()
+                .unwrap()
+                .get("forward")
+                .map(Child::id)
+        };
+
+        manager.toggle("forward", true).unwrap();
+        let started = Event::TunnelStarted {
+            key: "forward".into(),
+        };
+        assert_eq!(next(), started);
+        let first = pid();
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(pid(), first);
+
+        // The forward stops answering while the process stays up.
+        drop(listener);
+        assert_eq!(next(), started);
+        assert!(pid().is_some() && pid() != first);
+        assert!(manager.is_active("forward"));
+
+        manager.toggle("forward", false).unwrap();
+        thread::sleep(Duration::from_millis(500));
+        assert!(pid().is_none());
+        assert!(!manager.is_active("forward"));
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn starts_beyond_the_limit_wait_for_a_free_slot() {
+        let manager = manager_with("first", "sleep", &["30"]);
+        let closed = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        {
+            let mut commands = manager.commands_config.lock().unwrap();
+            let first = commands.get_mut("first").unwrap();
+            first.ready_check = Some(ReadyCheck {
+                address: closed,
+                timeout: Duration::from_secs(30),
+            });
+            let second = TunnelCommand {
+                ready_check: None,
+                ..first.clone()
+            };
+            commands.insert("second".to_string(), second);
+        }
+        manager.set_max_concurrent_starts(1);
+
+        // `first` never becomes ready, so it keeps the only slot.
+        manager.toggle("first", true).unwrap();
+        assert!(wait_until(|| manager
+            .children
+            .lock()
+            .unwrap()
+            .contains_key("first")));
+        manager.toggle("second", true).unwrap();
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(manager.tunnel_status("second"), TunnelStatus::Starting);
+        assert!(!manager.children.lock().unwrap().contains_key("second"));
+
+        manager.toggle("first", false).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("second") == TunnelStatus::Running
+        ));
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dependencies_start_first_and_must_be_ready() {
+        let manager = manager_with("bastion", "sleep", &["30"]);
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        {
+            let mut commands = manager.commands_config.lock().unwrap();
+            let bastion = commands.get_mut("bastion").unwrap();
+            bastion.ready_check = Some(ReadyCheck {
+                address: format!("127.0.0.1:{port}"),
+                timeout: Duration::from_secs(10),
+            });
+            let forward = TunnelCommand {
+                depends_on: vec!["bastion".to_string()],
+                ready_check: None,
+                ..bastion.clone()
+            };
+            commands.insert("forward".to_string(), forward.clone());
+            commands.insert(
+                "manual".to_string(),
+                TunnelCommand {
+                    start_dependencies: false,
+                    ..forward
+                },
+            );
+        }
+        let events = manager.events();
+
+        // Without `start_dependencies` the dependent is refused, and nothing starts.
+        assert!(matches!(
+            manager.start("manual"),
+            Err(TunnelError::Dependency { .. })
+        ));
+        assert_eq!(manager.tunnel_status("manual"), TunnelStatus::Failed);
+        assert!(matches!(
+            events.recv_timeout(Duration::from_secs(5)).unwrap(),
+            Event::TunnelFailed { key, .. } if key == "manual"
+        ));
+        assert!(!manager.has_active_tunnels());
+
+        // The bastion is started too, and the forward waits until it is ready.
+        assert_eq!(manager.start("forward"), Ok(true));
+        assert!(manager.active_tunnels.lock().unwrap().contains("bastion"));
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(manager.tunnel_status("forward"), TunnelStatus::Starting);
+        assert!(!manager.children.lock().unwrap().contains_key("forward"));
+
+        let _listener = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("forward") == TunnelStatus::Running
+        ));
+        assert_eq!(manager.tunnel_status("bastion"), TunnelStatus::Running);
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_dependent_gives_up_instead_of_retrying_once_its_dependency_stops() {
+        let manager = manager_with("bastion", "sleep", &["30"]);
+        {
+            let mut commands = manager.commands_config.lock().unwrap();
+            let bastion = commands.get("bastion").unwrap().clone();
+            commands.insert(
+                "forward".to_string(),
+                TunnelCommand {
+                    args: vec!["0.2".to_string()],
+                    max_retries: 0,
+                    depends_on: vec!["bastion".to_string()],
+                    ..bastion
+                },
+            );
+        }
+        let events = manager.events();
+
+        assert_eq!(manager.start("forward"), Ok(true));
+        assert!(wait_until(
+            || manager.tunnel_status("forward") == TunnelStatus::Running
+        ));
+        manager.toggle("bastion", false).unwrap();
+
+        // The forward keeps exiting; its next attempt finds the bastion stopped.
+        assert!(wait_until(
+            || manager.tunnel_status("forward") == TunnelStatus::Failed
+        ));
+        let failure = std::iter::from_fn(|| events.recv_timeout(Duration::from_secs(5)).ok())
+            .find_map(|event| match event {
+                Event::TunnelFailed { key, error } if key == "forward" => Some(error),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(failure, "'bastion' stopped before 'forward' could start");
+        assert!(!manager.has_active_tunnels());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_missing_program_fails_at_once_and_names_the_path() {
+        let mut manager = manager_with("missing", "something-bg-no-such-program", &[]);
+        let spawner = Arc::new(crate::testing::RecordingSpawner::new());
+        manager.set_spawner(spawner.clone());
+        let events = manager.events();
+
+        assert!(matches!(
+            manager.toggle("missing", true),
+            Err(TunnelError::CommandNotFound { .. })
+        ));
+        let error = match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+            Event::TunnelFailed { error, .. } => error,
+            other => panic!("unexpected event {other:?}"),
+        };
+        assert!(
+            error.starts_with("Command 'something-bg-no-such-program' not found on PATH ("),
+            "{error}"
+        );
+        assert_eq!(manager.tunnel_status("missing"), TunnelStatus::Failed);
+        assert!(!manager.has_active_tunnels());
+        assert!(spawner.calls().is_empty(), "nothing should be spawned");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notifies_when_a_tunnel_gives_up() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+        impl crate::platform::Notifier for Recorder {
+            fn info(&self, _title: &str, _body: &str) {}
+            fn warn(&self, _title: &str, _body: &str) {}
+            fn error(&self, title: &str, _body: &str) {
+                self.0.lock().unwrap().push(title.to_string());
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut manager = manager_with("flaky", "false", &[]);
+        manager.set_notifier(recorder.clone());
+        manager.toggle("flaky", true).unwrap();
+        assert!(wait_until(|| !recorder.0.lock().unwrap().is_empty()));
+        assert_eq!(
+            recorder.0.lock().unwrap().as_slice(),
+            ["Tunnel 'flaky' stopped"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn warns_about_a_failed_stop_only_when_a_process_may_be_left() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+        impl crate::platform::Notifier for Recorder {
+            fn info(&self, _title: &str, _body: &str) {}
+            fn warn(&self, title: &str, _body: &str) {
+                self.0.lock().unwrap().push(title.to_string());
+            }
+            fn error(&self, _title: &str, _body: &str) {}
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut manager = manager_with("flaky", "true", &[]);
+        manager.set_notifier(recorder.clone());
+        {
+            let mut commands = manager.commands_config.lock().unwrap();
+            let flaky = commands.get_mut("flaky").unwrap();
+            flaky.max_retries = 0;
+            flaky.retry_backoff = Duration::from_secs(30);
+        }
+
+        // Backing off between attempts: the failing stop command is expected.
+        manager.toggle("flaky", true).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("flaky") == TunnelStatus::Retrying
+        ));
+        manager.toggle("flaky", false).unwrap();
+        assert!(recorder.0.lock().unwrap().is_empty());
+
+        // Not started here, so something else may be running it.
+        manager.toggle("flaky", false).unwrap();
+        assert_eq!(
+            recorder.0.lock().unwrap().as_slice(),
+            ["Tunnel 'flaky' may still be running"]
+        );
+
+        // Unknown tunnels have no stop command to fail.
+        manager.toggle("missing", false).unwrap();
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_runs_the_command_without_activating_the_tunnel() {
+        let manager = manager_with(
+            "denied",
+            "sh",
+            &[
+                "-c",
+                "echo 'git@example.com: Permission denied (publickey).' >&2; exit 255",
+            ],
+        );
+        let report = manager.test_for("denied", Duration::from_secs(5)).unwrap();
+        assert_eq!(report.exit_code, Some(255));
+        assert!(!report.passed());
+        assert_eq!(
+            report.summary(),
+            "Exited with status 255: git@example.com: Permission denied (publickey)."
+        );
+        assert!(!manager.has_active_tunnels());
+        assert_eq!(manager.tunnel_status("denied"), TunnelStatus::Stopped);
+
+        let manager = manager_with("sleeper", "sleep", &["30"]);
+        let report = manager
+            .test_for("sleeper", Duration::from_millis(300))
+            .unwrap();
+        assert!(report.still_running && report.passed());
+        assert!(!manager.has_active_tunnels());
+
+        manager.toggle("sleeper", true).unwrap();
+        assert_eq!(
+            manager.test("sleeper"),
+            Err(TunnelError::Active("sleeper".to_string()))
+        );
+        assert_eq!(
+            manager.test("nope"),
+            Err(TunnelError::NotFound("nope".to_string()))
+        );
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn explains_a_stop_command_that_matched_nothing() {
+        let manager = manager_with("api", "sleep", &["30"]);
+        let path = std::env::var("PATH").unwrap_or_default();
+        let mut command = manager.commands_config.lock().unwrap()["api"].clone();
+        command.kill_command = "pkill".to_string();
+        command.kill_args = vec!["-f".to_string(), "something-bg-no-such-tunnel".to_string()];
+        let error = stop_command(&StdProcessSpawner, "api", &command, &path).unwrap_err();
+        assert!(
+            error.contains("matched no running process (pkill exited with 1)"),
+            "{error}"
+        );
+
+        command.kill_command = "false".to_string();
+        command.kill_args.clear();
+        assert_eq!(
+            stop_command(&StdProcessSpawner, "api", &command, &path).unwrap_err(),
+            "Stop command for tunnel 'api' exited with status 1"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_commands_run_with_the_tunnels_environment() {
+        let manager = manager_with("api", "sleep", &["30"]);
+        let dir = fs::canonicalize(std::env::temp_dir()).unwrap();
+        let mut command = manager.commands_config.lock().unwrap()["api"].clone();
+        command.env = HashMap::from([("TUNNEL_HOST".to_string(), "db".to_string())]);
+        command.working_dir = Some(dir.clone());
+        command.kill_command = "sh".to_string();
+        command.kill_args = vec![
+            "-c".to_string(),
+            format!(
+                "test \"$TUNNEL_HOST\" = db && test \"$(pwd -P)\" = '{}' && test \"$PATH\" = /usr/bin:/bin",
+                dir.display()
+            ),
+        ];
+        stop_command(&StdProcessSpawner, "api", &command, "/usr/bin:/bin").unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn starts_tunnels_through_the_configured_spawner() {
+        #[derive(Default)]
+        struct Counting(std::sync::atomic::AtomicUsize);
+        impl ProcessSpawner for Counting {
+            fn spawn(&self, command: &mut Command) -> std::io::Result<Child> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                command.spawn()
+            }
+        }
+
+        let spawner = Arc::new(Counting::default());
+        let mut manager = manager_with("sleeper", "sleep", &["30"]);
+        manager.set_spawner(spawner.clone());
+        assert_eq!(manager.start("sleeper"), Ok(true));
+        // A second start before or after the process is up is a no-op.
+        assert_eq!(manager.start("sleeper"), Ok(false));
+        manager.toggle("sleeper", true).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("sleeper") == TunnelStatus::Running
+        ));
+        assert_eq!(manager.start("sleeper"), Ok(false));
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(spawner.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fails_without_spawning_when_the_working_dir_is_missing() {
+        let manager = manager_with("api", "sleep", &["30"]);
+        if let Some(command) = manager.commands_config.lock().unwrap().get_mut("api") {
+            command.working_dir = Some(PathBuf::from("/nonexistent/something_bg"));
+        }
+        manager.toggle("api", true).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("api") == TunnelStatus::Failed
+        ));
+        assert!(!manager.has_active_tunnels());
+        assert!(manager.children.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reconfigure_keeps_unchanged_tunnels_and_stops_removed_ones() {
+        let manager = manager_with("kept", "sleep", &["30"]);
+        let path = manager.env_path.lock().unwrap().clone();
+        let mut commands = manager.commands_config.lock().unwrap().clone();
+        commands.insert("dropped".to_string(), commands["kept"].clone());
+        manager.reconfigure(commands.clone(), path.clone());
+        manager.toggle("kept", true).unwrap();
+        manager.toggle("dropped", true).unwrap();
+        assert!(wait_until(|| manager.children.lock().unwrap().len() == 2));
+        let kept_pid = manager.children.lock().unwrap()["kept"].id();
+
+        commands.remove("dropped");
+        manager.reconfigure(commands, path);
+
+        assert_eq!(manager.children.lock().unwrap()["kept"].id(), kept_pid);
+        assert!(!manager.children.lock().unwrap().contains_key("dropped"));
+        assert!(!manager.active_tunnels.lock().unwrap().contains("dropped"));
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tunnels_can_be_added_changed_and_removed_live() {
+        let manager = manager_with("first", "sleep", &["30"]);
+        let mut second = manager.commands_config.lock().unwrap()["first"].clone();
+        manager.upsert("second".to_string(), second.clone());
+        manager.toggle("second", true).unwrap();
+        assert!(wait_until(|| manager
+            .children
+            .lock()
+            .unwrap()
+            .contains_key("second")));
+        let pid = manager.children.lock().unwrap()["second"].id();
+
+        // A changed command restarts the running tunnel with it.
+        second.args = vec!["31".to_string()];
+        manager.upsert("second".to_string(), second);
+        assert!(wait_until(|| manager
+            .children
+            .lock()
+            .unwrap()
+            .get("second")
+            .is_some_and(|child| child.id() != pid)));
+        assert_eq!(
+            manager.active_commands.lock().unwrap()["second"].args,
+            ["31"]
+        );
+
+        assert!(manager.remove("second"));
+        assert!(!manager.remove("second"));
+        assert!(!manager.children.lock().unwrap().contains_key("second"));
+        assert!(!manager.has_active_tunnels());
+        assert!(
+            manager
+                .commands_config
+                .lock()
+                .unwrap()
+                .contains_key("first")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stops_the_tracked_child_without_the_kill_command() {
+        let manager = manager_with("sleeper", "sleep", &["30"]);
+        manager.toggle("sleeper", true).unwrap();
+        assert!(wait_until(|| manager
+            .children
+            .lock()
+            .unwrap()
+            .contains_key("sleeper")));
+        let pid = manager.children.lock().unwrap()["sleeper"].id();
+        assert!(wait_until(
+            || manager.tunnel_status("sleeper") == TunnelStatus::Running
+        ));
+
+        assert_eq!(manager.toggle("sleeper", false), Ok(false));
+        assert!(manager.children.lock().unwrap().is_empty());
+        assert_eq!(manager.tunnel_status("sleeper"), TunnelStatus::Stopped);
+        let alive = Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restart_replaces_active_children_and_ignores_idle_state() {
+        let manager = manager_with("sleeper", "sleep", &["30"]);
+        manager.restart_active_tunnels();
+        assert!(!manager.has_active_tunnels());
+
+        manager.toggle("sleeper", true).unwrap();
+        assert!(wait_until(|| manager
+            .children
+            .lock()
+            .unwrap()
+            .contains_key("sleeper")));
+        let first = manager.children.lock().unwrap()["sleeper"].id();
+
+        manager.restart_active_tunnels();
+        assert!(wait_until(|| {
+            manager
+                .children
+                .lock()
+                .unwrap()
+                .get("sleeper")
+                .is_some_and(|child| child.id() != first)
+        }));
+        assert!(manager.has_active_tunnels());
+
+        manager.cleanup();
+        assert!(manager.children.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn spawns_with_the_managers_env_path() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let output = std::env::temp_dir().join(format!(
+            "something-bg-tunnel-path-{}-{unique}",
+            std::process::id()
+        ));
+        let script = format!("printf %s \"$PATH\" > '{}'; sleep 30", output.display());
+        let manager = manager_with("path", "sh", &["-c", &script]);
+        let env_path = format!(
+            "/something-bg-test-bin:{}",
+            std::env::var("PATH").unwrap_or_default()
+        );
+        *manager.env_path.lock().unwrap() = env_path.clone();
+
+        manager.toggle("path", true).unwrap();
+        assert!(wait_until(
+            || std::fs::read_to_string(&output).is_ok_and(|path| path == env_path)
+        ));
+
+        manager.cleanup();
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn leaves_tunnels_running_on_quit_when_asked() {
+        let manager = manager_with("sleeper", "sleep", &["30"]);
+        manager.set_kill_on_quit(false);
+        manager.toggle("sleeper", true).unwrap();
+        assert!(wait_until(
+            || manager.tunnel_status("sleeper") == TunnelStatus::Running
+        ));
+        let pid = manager.children.lock().unwrap()["sleeper"].id() as libc::pid_t;
+
+        manager.cleanup();
+        assert!(!manager.has_active_tunnels());
+        assert!(manager.children.lock().unwrap().is_empty());
+        thread::sleep(Duration::from_millis(500));
+        // SAFETY: signal 0 only checks that the process exists.
+        assert_eq!(
+            unsafe { libc::kill(pid, 0) },
+            0,
+            "the tunnel should outlive cleanup"
+        );
+        // SAFETY: the process group the spawner created for the tunnel.
+        unsafe { libc::kill(-pid, libc::SIGKILL) };
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restores_tunnels_that_were_active_at_cleanup() {
+        let unique = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let state_file = std::env::temp_dir().join(format!(
+            "something-bg-tunnel-state-{}-{unique}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&state_file, "active = [\"sleeper\", \"removed\"]\n").unwrap();
+
+        let mut manager = manager_with("sleeper", "sleep", &["30"]);
+        manager.set_state_file(state_file.clone());
+        manager.restore_active();
+        assert!(manager.active_tunnels.lock().unwrap().contains("sleeper"));
+        assert_eq!(load_tunnel_state(&state_file), vec!["sleeper".to_string()]);
+
+        // Quitting keeps the saved set; switching a tunnel off clears it.
+        manager.cleanup();
+        assert_eq!(load_tunnel_state(&state_file), vec!["sleeper".to_string()]);
+        manager.restore_active();
+        manager.toggle("sleeper", false).unwrap();
+        assert!(load_tunnel_state(&state_file).is_empty());
+
+        // Auto-start does not double-start a restored tunnel.
+        std::fs::write(&state_file, "active = [\"sleeper\"]\n").unwrap();
+        let generation = |manager: &TunnelManager| manager.generations.lock().unwrap()["sleeper"];
+        let before = generation(&manager);
+        manager.start_at_launch(&["sleeper".to_string()]);
+        assert_eq!(generation(&manager), before + 1);
+        manager.cleanup();
+
+        let _ = std::fs::remove_file(state_file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn waits_out_the_start_delay_only_at_launch() {
+        let manager = manager_with("late", "sleep", &["30"]);
+        manager
+            .commands_config
+            .lock()
+            .unwrap()
+            .get_mut("late")
+            .unwrap()
+            .start_delay = Duration::from_millis(500);
+        let has_child = || manager.children.lock().unwrap().contains_key("late");
+
+        manager.start_at_launch(&["late".to_string()]);
+        assert!(manager.is_active("late"));
+        assert_eq!(manager.tunnel_status("late"), TunnelStatus::Starting);
+        thread::sleep(Duration::from_millis(200));
+        assert!(!has_child());
+        assert!(wait_until(has_child));
+
+        // Switching it off during the delay cancels the start.
+        manager.toggle("late", false).unwrap();
+        manager.start_at_launch(&["late".to_string()]);
+        manager.toggle("late", false).unwrap();
+        thread::sleep(Duration::from_millis(700));
+        assert!(!has_child());
+
+        // A start from the menu does not wait.
+        manager.toggle("late", true).unwrap();
+        thread::sleep(Duration::from_millis(250));
+        assert!(has_child());
+        manager.cleanup();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_active_keys_in_order() {
+        let manager = manager_with("web", "sleep", &["30"]);
+        let command = manager.commands_config.lock().unwrap()["web"].clone();
+        manager.upsert("api".to_string(), command);
+        assert!(manager.active_keys().is_empty());
+
+        manager.toggle("web", true).unwrap();
+        manager.toggle("api", true).unwrap();
+        assert_eq!(manager.active_keys(), ["api", "web"]);
+        assert!(manager.is_active("api") && !manager.is_active("db"));
+
+        manager.toggle("web", false).unwrap();
+        assert_eq!(manager.active_keys(), ["api"]);
+        manager.cleanup();
+    }
+
+    #[test]
+    fn toggling_an_unknown_key_is_harmless() {
+        let manager = TunnelManager::new(HashMap::new(), String::new());
+        manager
+            .active_tunnels
+            .lock()
+            .unwrap()
+            .insert("gone".to_string());
+
+        assert_eq!(
+            manager.toggle("gone", true),
+            Err(TunnelError::NotFound("gone".to_string()))
+        );
+        assert_eq!(manager.toggle("gone", false), Ok(false));
+        assert_eq!(manager.tunnel_status("gone"), TunnelStatus::Stopped);
+    }
 }